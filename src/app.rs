@@ -1,20 +1,39 @@
 use crate::{
+    cache::ListingCache,
+    course_file::{export_course, import_course},
+    course_meta::{Choice, ClearConditionPresence, CourseTheme, DayTime, GameStyle},
+    download_queue::{DownloadQueue, MAX_CONCURRENT_DOWNLOADS},
     emu::*,
     icon,
-    pages::{InitPage, SavePage, SettingsPage},
-    smmdb::{Course2Response, Difficulty, QueryParams, SortOptions},
+    library::Library,
+    pages::{
+        BackupsPage, ConflictResolution, CourseDetailPage, FillSummary, InitPage, SaveOverlay,
+        SavePage, SaveSide, SaveWrite, SettingsPage, Slot, Transfer, TransferMode,
+    },
+    settings::{validate_base_url, EndpointProfile},
+    smmdb::{Account, ApiError, Course2Response, Difficulty, QueryParams, SortOptions, SortValue},
     styles::*,
-    EmuSave, Page, Progress, Settings, Smmdb,
+    upload_registry::UploadRegistry,
+    widgets::DropTarget,
+    EmuSave, Page, Progress, Settings, Smmdb, UploadProgress,
 };
 
-use futures::future;
+use futures::{future, stream, StreamExt};
 use iced::{
-    button, container, executor, Application, Background, Button, Column, Command, Container,
+    button, container, executor, time, Application, Background, Button, Column, Command, Container,
     Element, Length, Row, Space, Subscription,
 };
 use iced_native::{keyboard, subscription, Event};
 use nfd::Response;
-use std::{convert::TryInto, path::PathBuf};
+use std::{
+    collections::VecDeque,
+    convert::TryInto,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const CONNECTIVITY_CHECK_INTERVAL: Duration = Duration::from_secs(30);
 
 pub struct App {
     state: AppState,
@@ -22,6 +41,14 @@ pub struct App {
     settings: Settings,
     current_page: Page,
     smmdb: Smmdb,
+    downloads: DownloadQueue,
+    next_upload_id: u64,
+    library: Library,
+    /// Shared by every opened save.
+    uploads: Arc<Mutex<UploadRegistry>>,
+    is_writing: bool,
+    /// Messages, which change a save and wait for the running write to finish.
+    queued_messages: VecDeque<Message>,
     window_size: WindowSize,
     settings_button: button::State,
 }
@@ -33,9 +60,16 @@ pub enum AppState {
     SwapSelect(usize),
     DownloadSelect(usize),
     DeleteSelect(usize),
-    Downloading {
+    SmmdbDeleteSelect(String),
+    ReplaceSelect(String),
+    LibraryDeleteSelect(String),
+    TransferSelect(Transfer),
+    TransferConflict(Transfer, usize),
+    Uploading {
+        id: u64,
         save_index: usize,
-        smmdb_id: String,
+        smmdb_id: Option<String>,
+        data: Arc<Vec<u8>>,
         progress: f32,
     },
 }
@@ -46,45 +80,164 @@ pub enum AppErrorState {
     None,
 }
 
+#[derive(Clone, Debug)]
+pub enum AfterWrite {
+    Report(&'static str),
+    FinishDownload(u64),
+    /// Removes the stashed course from the library again, if it could not be deleted.
+    StashCourse(String),
+    /// Removes the moved course from the library and stashes the course it replaced.
+    MoveFromLibrary(String, Option<(smmdb_lib::Course2, Option<String>)>),
+    /// Deletes a moved course from its save, once the copy has been written.
+    Transfer(Transfer),
+    Fill(FillSummary, Vec<(usize, String)>),
+}
+
 #[derive(Clone, Debug)]
 pub enum Message {
     Empty,
     SetWindowSize(WindowSize),
     OpenSave(EmuSave),
     OpenCustomSave,
-    LoadSave(smmdb_lib::Save, String),
+    LoadSave(smmdb_lib::Save, String, PathBuf),
     LoadSaveError(String),
     FetchCourses(QueryParams),
-    FetchError(String),
+    FetchError(ApiError),
+    RetryFetch,
+    GoOffline,
+    DismissSmmdbError,
     SetSmmdbCourses(Vec<Course2Response>),
+    SetCachedSmmdbCourses(Vec<Course2Response>, i64),
+    CheckConnectivity,
     SetSmmdbCourseThumbnail(Vec<u8>, String),
     InitSwapCourse(usize),
     SwapCourse(usize, usize),
+    DropCourse(usize, DropTarget),
     InitDownloadCourse(usize),
     DownloadCourse(usize, String),
-    DownloadProgressed(Progress),
+    OpenCourseDetail(String),
+    SetCourseDetailThumbnail(Vec<u8>, String),
+    SelectDetailSlot(Slot),
+    CloseCourseDetail,
+    DownloadProgressed(u64, Progress),
+    CancelDownload(u64),
+    RetryDownload(u64),
     InitDeleteCourse(usize),
     DeleteCourse(usize),
+    Undo,
+    Redo,
+    ToggleStagedMode(bool),
+    OpenOtherSave,
+    LoadOtherSave(Result<smmdb_lib::Save, String>, PathBuf),
+    CloseOtherSave,
+    InitTransferCourse(Transfer),
+    TransferCourse(Transfer, usize),
+    ResolveTransferConflict(Transfer, usize, ConflictResolution),
+    ApplyStagedChanges,
+    DiscardStagedChanges,
+    SaveWritten(SaveSide, Result<smmdb_lib::Save, String>, AfterWrite),
+    ExportCourse(usize),
+    ImportCourse(usize),
+    UploadCourse(usize),
+    UploadProgressed(UploadProgress),
+    ToggleOwnCourses(bool),
+    InitDeleteSmmdbCourse(String),
+    DeleteSmmdbCourse(String),
+    RemoveSmmdbCourse(String),
+    ChangeSmmdbCourseDifficulty(String, Difficulty),
+    SetSmmdbCourseDifficulty(String, Difficulty),
+    InitReplaceSmmdbCourse(String),
+    ReplaceSmmdbCourse(usize, String),
     TitleChanged(String),
     UploaderChanged(String),
     DifficultyChanged(Difficulty),
     SortChanged(SortOptions),
+    AddSortKey(SortValue),
+    RemoveSortKey(usize),
+    ToggleSortDirection(usize),
+    MoveSortKeyUp(usize),
+    ToggleAdvancedSearch,
+    TitleExactChanged(bool),
+    TitleCaseSensitiveChanged(bool),
+    TitleTrimmedChanged(bool),
+    IdsChanged(String),
+    OwnerChanged(String),
+    GameStyleFilterChanged(Choice<GameStyle>),
+    ThemeFilterChanged(Choice<CourseTheme>),
+    DayTimeFilterChanged(Choice<DayTime>),
+    ClearConditionFilterChanged(Choice<ClearConditionPresence>),
+    MinTimeChanged(String),
+    MaxTimeChanged(String),
     ApplyFilters,
-    PaginateForward,
-    PaginateBackward,
+    FillLimitChanged(String),
+    FillEmptySlots,
+    InstallFilledCourses(Vec<(usize, String, Result<Vec<u8>, ApiError>)>, Vec<String>),
+    DismissFillSummary,
+    StashCourse(usize),
+    StashSmmdbCourse(String),
+    AddSmmdbCourseToLibrary(String, Result<Vec<u8>, ApiError>),
+    LibraryFilterChanged(String),
+    LibraryTagInputChanged(String, String),
+    AddLibraryTags(String),
+    RemoveLibraryTag(String, String),
+    SelectLibrarySlot(String, Slot),
+    MoveFromLibrary(String, usize),
+    InitDeleteLibraryCourse(String),
+    DeleteLibraryCourse(String),
+    DismissLibraryError,
+    LoadMoreCourses,
+    SetPrefetchedSmmdbCourses(u64, u32, Vec<Course2Response>),
+    PrefetchError(u64, ApiError),
     UpvoteCourse(String),
     DownvoteCourse(String),
     ResetCourseVote(String),
     SetVoteCourse(String, i32),
     OpenSettings,
     TrySaveSettings(Settings),
-    SaveSettings(Settings),
-    RejectSettings(String),
+    SaveSettings(Settings, Option<Account>),
+    SetAccount(Account),
+    RejectSettings(ApiError),
     CloseSettings,
     ChangeApiKey(String),
+    ChangeEndpointProfile(EndpointProfile),
+    ChangeBaseUrl(String),
+    ChangeBackupRetention(String),
+    OpenBackups,
+    PreviewBackup(String),
+    SetBackupPreview(String, Result<smmdb_lib::Save, String>),
+    RestoreBackup(String),
+    CloseBackups,
     ResetState,
 }
 
+impl Message {
+    fn changes_save(&self) -> bool {
+        matches!(
+            self,
+            Message::LoadSave(..)
+                | Message::SwapCourse(..)
+                | Message::DropCourse(..)
+                | Message::DownloadProgressed(_, Progress::Finished(_))
+                | Message::DeleteCourse(_)
+                | Message::Undo
+                | Message::Redo
+                | Message::ToggleStagedMode(_)
+                | Message::LoadOtherSave(..)
+                | Message::CloseOtherSave
+                | Message::TransferCourse(..)
+                | Message::ResolveTransferConflict(..)
+                | Message::ApplyStagedChanges
+                | Message::DiscardStagedChanges
+                | Message::ImportCourse(_)
+                | Message::UploadProgressed(UploadProgress::Finished(_))
+                | Message::InstallFilledCourses(..)
+                | Message::StashCourse(_)
+                | Message::MoveFromLibrary(..)
+                | Message::RestoreBackup(_)
+        )
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum WindowSize {
     S,
@@ -97,23 +250,40 @@ impl Application for App {
     type Flags = ();
 
     fn new(_flags: ()) -> (App, Command<Self::Message>) {
-        let components = guess_emu_dir().unwrap();
+        let emu_saves = guess_emu_dir().unwrap();
         let settings = Settings::load().unwrap();
+        let library = Library::load().unwrap();
         let smmdb = Smmdb::new(settings.apikey.clone());
         let query_params = smmdb.get_query_params().clone();
+        let mut commands = vec![Command::perform(async {}, move |_| {
+            Message::FetchCourses(query_params.clone())
+        })];
+        if let Some(apikey) = settings.apikey.clone() {
+            commands.push(Command::perform(
+                Smmdb::try_sign_in(settings.base_url.clone(), apikey),
+                |res| match res {
+                    Ok(account) => Message::SetAccount(account),
+                    Err(err) => Message::FetchError(err),
+                },
+            ));
+        }
         (
             App {
                 state: AppState::Default,
                 error_state: AppErrorState::None,
                 settings,
-                current_page: Page::Init(InitPage::new(components)),
+                current_page: Page::Init(InitPage::new(emu_saves)),
                 smmdb,
+                downloads: DownloadQueue::new(),
+                next_upload_id: 0,
+                library,
+                uploads: Arc::new(Mutex::new(UploadRegistry::load())),
+                is_writing: false,
+                queued_messages: VecDeque::new(),
                 window_size: WindowSize::M,
                 settings_button: button::State::new(),
             },
-            Command::perform(async {}, move |_| {
-                Message::FetchCourses(query_params.clone())
-            }),
+            Command::batch(commands),
         )
     }
 
@@ -122,6 +292,10 @@ impl Application for App {
     }
 
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
+        if self.is_writing && message.changes_save() {
+            self.queued_messages.push_back(message);
+            return Command::none();
+        }
         match message {
             Message::Empty => Command::none(),
             Message::SetWindowSize(window_size) => {
@@ -132,19 +306,20 @@ impl Application for App {
             Message::OpenSave(save) => {
                 self.state = AppState::Loading;
                 let display_name = save.get_display_name().clone();
+                let location = save.get_location().clone();
                 Command::perform(
                     async move {
                         futures::join!(
-                            smmdb_lib::Save::new(save.get_location().clone()),
-                            future::ok::<String, String>(display_name)
+                            smmdb_lib::Save::new(location.clone()),
+                            future::ok::<(String, PathBuf), String>((display_name, location))
                         )
                     },
                     move |res| match res {
-                        (Ok(smmdb_save), Ok(display_name)) => {
-                            Message::LoadSave(smmdb_save, display_name)
+                        (Ok(smmdb_save), Ok((display_name, location))) => {
+                            Message::LoadSave(smmdb_save, display_name, location)
                         }
                         (Err(err), _) => Message::LoadSaveError(err.into()),
-                        _ => todo!(),
+                        (_, Err(err)) => Message::LoadSaveError(err),
                     },
                 )
             }
@@ -159,17 +334,19 @@ impl Application for App {
                                     Ok(smmdb_save) => Message::LoadSave(
                                         smmdb_save,
                                         file_path.clone().to_string_lossy().into(),
+                                        file_path.clone(),
                                     ),
                                     Err(err) => Message::LoadSaveError(err.into()),
                                 }
                             })
                         }
                         Response::OkayMultiple(_files) => {
-                            println!("Not multifile select");
+                            log::warn!("Expected a single folder to be picked");
+                            self.state = AppState::Default;
                             Command::none()
                         }
                         Response::Cancel => {
-                            println!("User canceled");
+                            self.state = AppState::Default;
                             Command::none()
                         }
                     },
@@ -178,57 +355,79 @@ impl Application for App {
                     }),
                 }
             }
-            Message::LoadSave(smmdb_save, display_name) => {
+            Message::LoadSave(smmdb_save, display_name, location) => {
                 self.state = AppState::Default;
                 self.error_state = AppErrorState::None;
-                self.current_page = Page::Save(SavePage::new(smmdb_save, display_name));
+                self.downloads.clear();
+                self.current_page = Page::Save(SavePage::new(
+                    smmdb_save,
+                    display_name,
+                    location,
+                    self.uploads.clone(),
+                    self.settings.backup_retention,
+                ));
                 Command::none()
             }
             Message::LoadSaveError(err) => {
-                eprintln!("{}", &err);
+                log::error!("{}", err);
+                self.state = AppState::Default;
                 self.error_state =
                     AppErrorState::Some(format!("Could not load save file. Full error:\n{}", err));
                 Command::none()
             }
-            Message::FetchCourses(query_params) => Command::perform(
-                Smmdb::update(query_params, self.settings.apikey.clone()),
-                move |res| match res {
-                    Ok(courses) => Message::SetSmmdbCourses(courses),
-                    Err(err) => Message::FetchError(err.to_string()),
-                },
-            ),
+            Message::FetchCourses(query_params) => self.fetch_courses_with(query_params, false),
             Message::FetchError(err) => {
-                dbg!(&err);
-                self.error_state = AppErrorState::Some(err);
+                log::error!("{:?}", err);
+                if let AppState::Loading = self.state {
+                    self.state = AppState::Default;
+                }
+                self.smmdb.set_error(Some(err));
+                Command::none()
+            }
+            Message::RetryFetch => {
+                self.state = AppState::Loading;
+                self.smmdb.set_error(None);
+                self.fetch_courses()
+            }
+            Message::GoOffline => {
+                self.smmdb.set_offline(true);
+                let query_params = self.smmdb.get_query_params().clone();
+                let base_url = self.settings.base_url.clone();
+                let apikey = self.settings.apikey.clone();
+                Command::perform(
+                    async move { ListingCache::load(&base_url, apikey.as_deref(), &query_params) },
+                    |listing| match listing {
+                        Some(listing) => {
+                            Message::SetCachedSmmdbCourses(listing.courses, listing.fetched_at)
+                        }
+                        None => Message::Empty,
+                    },
+                )
+            }
+            Message::CheckConnectivity => {
+                self.fetch_courses_with(self.smmdb.get_query_params().clone(), true)
+            }
+            Message::DismissSmmdbError => {
+                self.smmdb.set_error(None);
                 Command::none()
             }
             Message::SetSmmdbCourses(courses) => {
-                self.state = AppState::Default;
+                if let AppState::Loading = self.state {
+                    self.state = AppState::Default;
+                }
                 self.error_state = AppErrorState::None;
+                self.smmdb.set_error(None);
+                self.smmdb.set_offline(false);
                 self.smmdb.set_courses(courses);
-                let course_ids: Vec<String> =
-                    self.smmdb.get_course_panels().keys().cloned().collect();
-
-                let mut commands = Vec::<Command<Message>>::new();
-                for id in course_ids {
-                    commands.push(Command::perform(
-                        async move {
-                            futures::join!(
-                                Smmdb::fetch_thumbnail(id.clone()),
-                                futures::future::ok::<String, String>(id)
-                            )
-                        },
-                        |(thumbnail, id)| {
-                            if let (Ok(thumbnail), Ok(id)) = (thumbnail, id) {
-                                Message::SetSmmdbCourseThumbnail(thumbnail, id)
-                            } else {
-                                // TODO handle error
-                                Message::Empty
-                            }
-                        },
-                    ));
+                Command::batch(vec![self.fetch_thumbnails(), self.prefetch_courses()])
+            }
+            Message::SetCachedSmmdbCourses(courses, fetched_at) => {
+                if let AppState::Loading = self.state {
+                    self.state = AppState::Default;
                 }
-                Command::batch(commands)
+                self.smmdb.set_error(None);
+                self.smmdb.set_cached_courses(courses, fetched_at);
+                self.fetch_thumbnails()
             }
             Message::SetSmmdbCourseThumbnail(thumbnail, id) => {
                 self.smmdb.set_course_panel_thumbnail(&id, thumbnail);
@@ -239,82 +438,494 @@ impl Application for App {
                 Command::none()
             }
             Message::SwapCourse(first, second) => {
+                if self.has_reserved_slot(vec![first, second]) {
+                    return Command::none();
+                }
+                let save_page = match self.current_page {
+                    Page::Save(ref mut save_page) => save_page,
+                    _ => return Command::none(),
+                };
                 self.state = AppState::Loading;
-
-                match self.current_page {
-                    Page::Save(ref mut save_page) => {
-                        let fut = save_page.swap_courses(first as u8, second as u8);
-                        futures::executor::block_on(fut).unwrap();
-                        // TODO find better way than block_on
-                        Command::perform(async {}, |_| Message::ResetState)
-                    }
-                    _ => Command::none(),
+                let res = save_page.swap_courses(first as u8, second as u8);
+                self.write_save(
+                    SaveSide::Primary,
+                    res,
+                    AfterWrite::Report("Could not swap the courses."),
+                )
+            }
+            Message::DropCourse(from, target) => {
+                let to = target.get_index(from);
+                let slots = match target {
+                    DropTarget::Swap(_) => vec![from, to],
+                    DropTarget::Insert(_) => (from.min(to)..=from.max(to)).collect(),
+                };
+                if self.has_reserved_slot(slots) {
+                    return Command::none();
                 }
+                let save_page = match self.current_page {
+                    Page::Save(ref mut save_page) => save_page,
+                    _ => return Command::none(),
+                };
+                self.state = AppState::Loading;
+                let res = match target {
+                    DropTarget::Swap(_) => save_page.swap_courses(from as u8, to as u8),
+                    DropTarget::Insert(_) => save_page.move_course(from as u8, to as u8),
+                };
+                self.write_save(
+                    SaveSide::Primary,
+                    res,
+                    AfterWrite::Report("Could not move the course."),
+                )
             }
             Message::InitDownloadCourse(index) => {
                 self.state = AppState::DownloadSelect(index);
                 Command::none()
             }
             Message::DownloadCourse(save_index, smmdb_id) => {
-                self.state = AppState::Downloading {
-                    save_index,
-                    smmdb_id,
-                    progress: 0.,
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.close_overlay();
+                }
+                let title = match self.smmdb.get_course_panels().get(&smmdb_id) {
+                    Some(panel) => panel
+                        .get_course()
+                        .get_course()
+                        .get_header()
+                        .get_title()
+                        .to_string(),
+                    None => smmdb_id.clone(),
+                };
+                self.downloads.push(smmdb_id, title, save_index);
+                self.state = AppState::Default;
+                Command::none()
+            }
+            Message::OpenCourseDetail(id) => {
+                let save_page = match self.current_page {
+                    Page::Save(ref mut save_page) => save_page,
+                    _ => return Command::none(),
+                };
+                let empty_slots: Vec<usize> = save_page
+                    .get_empty_slots()
+                    .into_iter()
+                    .filter(|index| !self.downloads.is_reserved(*index))
+                    .collect();
+                let panel = match self.smmdb.get_course_panels().get(&id) {
+                    Some(panel) => panel,
+                    None => return Command::none(),
+                };
+                let course = panel.get_course().clone();
+                let thumbnail = panel.get_thumbnail().cloned();
+                let last_modified = course.get_last_modified();
+                self.state = AppState::Default;
+                save_page.open_overlay(SaveOverlay::CourseDetail(CourseDetailPage::new(
+                    course,
+                    thumbnail,
+                    empty_slots,
+                )));
+
+                let base_url = self.settings.base_url.clone();
+                Command::perform(
+                    Smmdb::fetch_thumbnail(base_url, id.clone(), last_modified, "l"),
+                    move |res| match res {
+                        Ok(thumbnail) => Message::SetCourseDetailThumbnail(thumbnail, id.clone()),
+                        Err(_) => Message::Empty,
+                    },
+                )
+            }
+            Message::SetCourseDetailThumbnail(thumbnail, id) => {
+                if let Some(course_detail_page) = self
+                    .current_page
+                    .get_save_page_mut()
+                    .and_then(SavePage::get_course_detail_mut)
+                {
+                    if *course_detail_page.get_course().get_id() == id {
+                        course_detail_page.set_thumbnail(thumbnail);
+                    }
+                }
+                Command::none()
+            }
+            Message::SelectDetailSlot(slot) => {
+                if let Some(course_detail_page) = self
+                    .current_page
+                    .get_save_page_mut()
+                    .and_then(SavePage::get_course_detail_mut)
+                {
+                    course_detail_page.select_slot(slot);
+                }
+                Command::none()
+            }
+            Message::CloseCourseDetail => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.close_overlay();
+                }
+                Command::none()
+            }
+            Message::DownloadProgressed(job_id, message) => {
+                match message {
+                    Progress::Started => {
+                        self.downloads.set_progress(job_id, Some(0.));
+                    }
+                    Progress::Advanced(percentage) => {
+                        self.downloads.set_progress(job_id, Some(percentage));
+                    }
+                    Progress::Received(_) => {
+                        self.downloads.set_progress(job_id, None);
+                    }
+                    Progress::Finished(data) => {
+                        let save_index = match self
+                            .downloads
+                            .get_jobs()
+                            .iter()
+                            .find(|job| job.get_id() == job_id)
+                        {
+                            Some(job) => job.get_save_index(),
+                            None => return Command::none(),
+                        };
+                        return self.add_downloaded_course(job_id, save_index, data);
+                    }
+                    Progress::Errored(err) => {
+                        log::error!("{:?}", err);
+                        self.downloads.fail(job_id, err);
+                    }
+                }
+                Command::none()
+            }
+            Message::CancelDownload(job_id) => {
+                self.downloads.cancel(job_id);
+                Command::none()
+            }
+            Message::RetryDownload(job_id) => {
+                self.downloads.retry(job_id);
+                Command::none()
+            }
+            Message::InitDeleteCourse(index) => {
+                self.state = AppState::DeleteSelect(index);
+                Command::none()
+            }
+            Message::DeleteCourse(index) => {
+                if self.has_reserved_slot(vec![index]) {
+                    return Command::none();
+                }
+                let save_page = match self.current_page {
+                    Page::Save(ref mut save_page) => save_page,
+                    _ => return Command::none(),
+                };
+                self.state = AppState::Loading;
+                let res = save_page.delete_course(index as u8);
+                self.write_save(
+                    SaveSide::Primary,
+                    res,
+                    AfterWrite::Report("Could not delete the course."),
+                )
+            }
+            Message::Undo | Message::Redo => {
+                let slots = match self.current_page {
+                    Page::Save(ref save_page) if !save_page.has_focused_text_input() => {
+                        match message {
+                            Message::Undo => save_page.get_history().get_undo_slots(),
+                            _ => save_page.get_history().get_redo_slots(),
+                        }
+                    }
+                    _ => return Command::none(),
+                };
+                if self.has_reserved_slot(slots) {
+                    return Command::none();
+                }
+                let save_page = match self.current_page {
+                    Page::Save(ref mut save_page) => save_page,
+                    _ => return Command::none(),
+                };
+                self.state = AppState::Default;
+                let res = match message {
+                    Message::Undo => save_page.undo(),
+                    _ => save_page.redo(),
+                };
+                self.write_save(
+                    SaveSide::Primary,
+                    res,
+                    AfterWrite::Report("Could not undo or redo the change."),
+                )
+            }
+            Message::ToggleStagedMode(staged) => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.set_staged(staged);
+                }
+                Command::none()
+            }
+            Message::ApplyStagedChanges => {
+                let save_page = match self.current_page {
+                    Page::Save(ref mut save_page) => save_page,
+                    _ => return Command::none(),
+                };
+                self.state = AppState::Loading;
+                let write = save_page.apply_staged_changes();
+                self.write_save(
+                    SaveSide::Primary,
+                    Ok(write),
+                    AfterWrite::Report("Could not write the changes to the save."),
+                )
+            }
+            Message::SaveWritten(side, res, after) => {
+                self.is_writing = false;
+                let res = match self.current_page.get_save_page_mut() {
+                    Some(save_page) => save_page.finish_write(side, res),
+                    None => res.map(|_| ()),
+                };
+                let command = self.after_write(res, after);
+                self.run_queued_messages(command)
+            }
+            Message::DiscardStagedChanges => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.discard_staged_changes();
+                }
+                self.state = AppState::Default;
+                self.error_state = AppErrorState::None;
+                Command::none()
+            }
+            Message::OpenOtherSave => match nfd::open_pick_folder(None) {
+                Ok(Response::Okay(file_path)) => {
+                    self.state = AppState::Loading;
+                    let file_path: PathBuf = file_path.into();
+                    Command::perform(smmdb_lib::Save::new(file_path.clone()), move |res| {
+                        Message::LoadOtherSave(res.map_err(|err| err.into()), file_path.clone())
+                    })
+                }
+                Ok(_) => Command::none(),
+                Err(err) => {
+                    self.error_state = AppErrorState::Some(format!("{:?}", err));
+                    Command::none()
+                }
+            },
+            Message::LoadOtherSave(res, location) => {
+                self.state = AppState::Default;
+                let save_page = match self.current_page {
+                    Page::Save(ref mut save_page) => save_page,
+                    _ => return Command::none(),
                 };
+                let res = match res {
+                    Ok(smmdb_save) => save_page.open_other_save(
+                        smmdb_save,
+                        location.to_string_lossy().into(),
+                        location,
+                        self.settings.backup_retention,
+                    ),
+                    Err(err) => Err(anyhow::anyhow!(err)),
+                };
+                if let Err(err) = res {
+                    log::error!("{:?}", err);
+                    self.error_state =
+                        AppErrorState::Some(format!("Could not open the second save.\n{}", err));
+                }
+                Command::none()
+            }
+            Message::CloseOtherSave => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.close_other_save();
+                }
+                self.state = AppState::Default;
+                Command::none()
+            }
+            Message::InitTransferCourse(transfer) => {
+                self.state = AppState::TransferSelect(transfer);
                 Command::none()
             }
-            Message::DownloadProgressed(message) => {
-                match &mut self.state {
-                    AppState::Downloading {
-                        save_index,
-                        progress,
-                        ..
-                    } => match message {
-                        Progress::Started => {
+            Message::TransferCourse(transfer, to) => {
+                let target = match transfer.from {
+                    SaveSide::Primary => SaveSide::Secondary,
+                    SaveSide::Secondary => SaveSide::Primary,
+                };
+                let is_occupied = match self.current_page {
+                    Page::Save(ref save_page) => save_page.is_slot_occupied(target, to),
+                    _ => false,
+                };
+                if is_occupied {
+                    self.state = AppState::TransferConflict(transfer, to);
+                    Command::none()
+                } else {
+                    self.transfer_course(transfer, to, None)
+                }
+            }
+            Message::ResolveTransferConflict(transfer, to, resolution) => {
+                self.transfer_course(transfer, to, Some(resolution))
+            }
+            Message::ExportCourse(index) => {
+                self.state = AppState::Default;
+                let course = match self.current_page {
+                    Page::Save(ref save_page) => match save_page.get_course(index) {
+                        Some(course) => course,
+                        None => return Command::none(),
+                    },
+                    _ => return Command::none(),
+                };
+                match nfd::open_save_dialog(Some("bcd"), None) {
+                    Ok(Response::Okay(file_path)) => {
+                        if let Err(err) = export_course(course, &PathBuf::from(file_path)) {
+                            log::error!("{:?}", err);
+                            self.error_state = AppErrorState::Some(format!(
+                                "Could not export the course.\n{}",
+                                err
+                            ));
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        self.error_state = AppErrorState::Some(format!("{:?}", err));
+                    }
+                }
+                Command::none()
+            }
+            Message::ImportCourse(index) => {
+                self.state = AppState::Default;
+                if self.has_reserved_slot(vec![index]) {
+                    return Command::none();
+                }
+                let save_page = match self.current_page {
+                    Page::Save(ref mut save_page) => save_page,
+                    _ => return Command::none(),
+                };
+                match nfd::open_file_dialog(None, None) {
+                    Ok(Response::Okay(file_path)) => {
+                        let res = import_course(&PathBuf::from(file_path))
+                            .and_then(|course| save_page.add_course(index as u8, course));
+                        self.write_save(
+                            SaveSide::Primary,
+                            res,
+                            AfterWrite::Report("Could not import the course."),
+                        )
+                    }
+                    Ok(_) => Command::none(),
+                    Err(err) => {
+                        self.error_state = AppErrorState::Some(format!("{:?}", err));
+                        Command::none()
+                    }
+                }
+            }
+            Message::UploadCourse(index) => {
+                self.upload_course(index, None);
+                Command::none()
+            }
+            Message::UploadProgressed(message) => {
+                if let AppState::Uploading {
+                    save_index,
+                    smmdb_id,
+                    progress,
+                    ..
+                } = &mut self.state
+                {
+                    match message {
+                        UploadProgress::Started => {
                             *progress = 0.;
                         }
-                        Progress::Advanced(percentage) => {
+                        UploadProgress::Advanced(percentage) => {
                             *progress = percentage;
                         }
-                        Progress::Finished(data) => {
-                            let save_index = save_index.clone();
-                            match self.current_page {
-                                Page::Save(ref mut save_page) => {
-                                    let course: smmdb_lib::Course2 = data.try_into().unwrap();
-                                    let fut = save_page.add_course(save_index as u8, course);
-                                    futures::executor::block_on(fut).unwrap();
-                                    // TODO find better way than block_on
-                                    return Command::perform(async {}, |_| Message::ResetState);
-                                }
-                                _ => {
-                                    // TODO
-                                }
+                        UploadProgress::Finished(course) => {
+                            if let Page::Save(ref mut save_page) = self.current_page {
+                                save_page.set_smmdb_id(*save_index, course.get_id().clone());
+                            }
+                            let is_replace = smmdb_id.is_some();
+                            self.state = AppState::Default;
+                            if is_replace {
+                                self.smmdb.set_course(course);
+                                return self.fetch_thumbnails();
                             }
                         }
-                        Progress::Errored => {
-                            // TODO
+                        UploadProgress::Errored(err) => {
+                            log::error!("{:?}", err);
+                            self.smmdb.set_error(Some(err));
+                            self.state = AppState::Default;
                         }
-                    },
-                    _ => {}
-                };
+                    }
+                }
                 Command::none()
             }
-            Message::InitDeleteCourse(index) => {
-                self.state = AppState::DeleteSelect(index);
+            Message::ToggleOwnCourses(only_own) => {
+                let owner = if only_own {
+                    self.smmdb
+                        .get_account()
+                        .map(|account| account.get_id().clone())
+                } else {
+                    None
+                };
+                self.smmdb.set_owner(owner);
+                self.state = AppState::Loading;
+                self.smmdb.reset_pagination();
+                self.fetch_courses()
+            }
+            Message::InitDeleteSmmdbCourse(smmdb_id) => {
+                self.state = AppState::SmmdbDeleteSelect(smmdb_id);
                 Command::none()
             }
-            Message::DeleteCourse(index) => {
-                self.state = AppState::Loading;
-
-                match self.current_page {
-                    Page::Save(ref mut save_page) => {
-                        let fut = save_page.delete_course(index as u8);
-                        futures::executor::block_on(fut).unwrap();
-                        // TODO find better way than block_on
-                        Command::perform(async {}, |_| Message::ResetState)
+            Message::DeleteSmmdbCourse(smmdb_id) => {
+                if let Some(apikey) = self.settings.apikey.clone() {
+                    self.state = AppState::Loading;
+                    Command::perform(
+                        Smmdb::delete_course(
+                            self.settings.base_url.clone(),
+                            smmdb_id.clone(),
+                            apikey,
+                        ),
+                        move |res| match res {
+                            Ok(()) => Message::RemoveSmmdbCourse(smmdb_id.clone()),
+                            Err(err) => Message::FetchError(err),
+                        },
+                    )
+                } else {
+                    self.state = AppState::Default;
+                    self.smmdb.set_error(Some(ApiError::Other(
+                        "You have to be signed in to delete courses.".to_string(),
+                    )));
+                    Command::none()
+                }
+            }
+            Message::RemoveSmmdbCourse(smmdb_id) => {
+                self.state = AppState::Default;
+                self.smmdb.remove_course(&smmdb_id);
+                {
+                    let mut uploads = self.uploads.lock().unwrap();
+                    uploads.forget(&smmdb_id);
+                    if let Err(err) = uploads.save() {
+                        log::warn!("Could not save uploaded courses: {}", err);
                     }
-                    _ => Command::none(),
                 }
+                if let Some(save_page) = self.current_page.get_save_page_mut() {
+                    save_page.forget_smmdb_id(&smmdb_id);
+                }
+                Command::none()
+            }
+            Message::ChangeSmmdbCourseDifficulty(smmdb_id, difficulty) => {
+                if let Some(apikey) = self.settings.apikey.clone() {
+                    Command::perform(
+                        Smmdb::set_course_difficulty(
+                            self.settings.base_url.clone(),
+                            smmdb_id.clone(),
+                            difficulty,
+                            apikey,
+                        ),
+                        move |res| match res {
+                            Ok(()) => {
+                                Message::SetSmmdbCourseDifficulty(smmdb_id.clone(), difficulty)
+                            }
+                            Err(err) => Message::FetchError(err),
+                        },
+                    )
+                } else {
+                    self.smmdb.set_error(Some(ApiError::Other(
+                        "You have to be signed in to change the difficulty of courses.".to_string(),
+                    )));
+                    Command::none()
+                }
+            }
+            Message::SetSmmdbCourseDifficulty(smmdb_id, difficulty) => {
+                self.smmdb.set_course_difficulty(&smmdb_id, difficulty);
+                Command::none()
+            }
+            Message::InitReplaceSmmdbCourse(smmdb_id) => {
+                self.state = AppState::ReplaceSelect(smmdb_id);
+                Command::none()
+            }
+            Message::ReplaceSmmdbCourse(index, smmdb_id) => {
+                self.upload_course(index, Some(smmdb_id));
+                Command::none()
             }
             Message::TitleChanged(title) => {
                 self.smmdb.set_title(title);
@@ -332,55 +943,275 @@ impl Application for App {
                 self.smmdb.set_sort(sort);
                 Command::none()
             }
+            Message::AddSortKey(val) => {
+                self.smmdb.add_sort_key(val);
+                Command::none()
+            }
+            Message::RemoveSortKey(index) => {
+                self.smmdb.remove_sort_key(index);
+                Command::none()
+            }
+            Message::ToggleSortDirection(index) => {
+                self.smmdb.toggle_sort_direction(index);
+                Command::none()
+            }
+            Message::MoveSortKeyUp(index) => {
+                self.smmdb.move_sort_key_up(index);
+                Command::none()
+            }
+            Message::ToggleAdvancedSearch => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.toggle_advanced_search();
+                }
+                Command::none()
+            }
+            Message::TitleExactChanged(title_exact) => {
+                self.smmdb.set_title_exact(title_exact);
+                Command::none()
+            }
+            Message::TitleCaseSensitiveChanged(title_case_sensitive) => {
+                self.smmdb.set_title_case_sensitive(title_case_sensitive);
+                Command::none()
+            }
+            Message::TitleTrimmedChanged(title_trimmed) => {
+                self.smmdb.set_title_trimmed(title_trimmed);
+                Command::none()
+            }
+            Message::IdsChanged(ids) => {
+                self.smmdb.set_ids_input(ids);
+                Command::none()
+            }
+            Message::OwnerChanged(owner) => {
+                self.smmdb.set_owner_input(owner);
+                Command::none()
+            }
+            Message::GameStyleFilterChanged(game_style) => {
+                self.smmdb.set_game_style(game_style.to_option());
+                Command::none()
+            }
+            Message::ThemeFilterChanged(theme) => {
+                self.smmdb.set_theme(theme.to_option());
+                Command::none()
+            }
+            Message::DayTimeFilterChanged(day_time) => {
+                self.smmdb
+                    .get_meta_filter_mut()
+                    .set_day_time(day_time.to_option());
+                Command::none()
+            }
+            Message::ClearConditionFilterChanged(clear_condition) => {
+                self.smmdb
+                    .get_meta_filter_mut()
+                    .set_clear_condition(clear_condition.to_option());
+                Command::none()
+            }
+            Message::MinTimeChanged(min_time) => {
+                self.smmdb.set_min_time(min_time);
+                Command::none()
+            }
+            Message::MaxTimeChanged(max_time) => {
+                self.smmdb.set_max_time(max_time);
+                Command::none()
+            }
             Message::ApplyFilters => {
                 self.state = AppState::Loading;
                 self.smmdb.reset_pagination();
-                Command::perform(
-                    Smmdb::update(
-                        self.smmdb.get_query_params().clone(),
-                        self.settings.apikey.clone(),
-                    ),
-                    move |res| match res {
-                        Ok(courses) => Message::SetSmmdbCourses(courses),
-                        Err(err) => Message::FetchError(err.to_string()),
-                    },
-                )
+                self.fetch_courses()
+            }
+            Message::FillLimitChanged(fill_limit) => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.set_fill_limit(&fill_limit);
+                }
+                Command::none()
             }
-            Message::PaginateForward => {
+            Message::FillEmptySlots => {
+                let save_page = match self.current_page {
+                    Page::Save(ref save_page) => save_page,
+                    _ => return Command::none(),
+                };
+                let downloads = &self.downloads;
+                let mut empty_slots = save_page
+                    .get_empty_slots()
+                    .into_iter()
+                    .filter(|index| !downloads.is_reserved(*index));
+                let mut jobs = vec![];
+                let mut skipped = vec![];
+                for course in self.smmdb.get_fill_candidates(save_page.get_fill_limit()) {
+                    let title = course.get_course().get_header().get_title().to_string();
+                    if save_page.contains_course(course.get_id(), course.get_course()) {
+                        skipped.push(title);
+                        continue;
+                    }
+                    match empty_slots.next() {
+                        Some(index) => jobs.push((index, course.get_id().clone(), title)),
+                        None => break,
+                    }
+                }
+
                 self.state = AppState::Loading;
-                self.smmdb.paginate_forward();
+                let base_url = self.settings.base_url.clone();
                 Command::perform(
-                    Smmdb::update(
-                        self.smmdb.get_query_params().clone(),
-                        self.settings.apikey.clone(),
-                    ),
-                    move |res| match res {
-                        Ok(courses) => Message::SetSmmdbCourses(courses),
-                        Err(err) => Message::FetchError(err.to_string()),
-                    },
+                    stream::iter(jobs)
+                        .map(move |(index, id, title)| {
+                            let base_url = base_url.clone();
+                            async move { (index, title, Smmdb::fetch_course(base_url, id).await) }
+                        })
+                        .buffer_unordered(MAX_CONCURRENT_DOWNLOADS)
+                        .collect::<Vec<_>>(),
+                    move |courses| Message::InstallFilledCourses(courses, skipped.clone()),
                 )
             }
-            Message::PaginateBackward => {
+            Message::InstallFilledCourses(courses, skipped) => {
+                self.state = AppState::Default;
+                let fetched = courses
+                    .into_iter()
+                    .map(|(index, title, data)| {
+                        let course = data
+                            .and_then(parse_downloaded_course)
+                            .map_err(|err| err.to_string());
+                        (index, title, course)
+                    })
+                    .collect();
+                let (fill_summary, courses) = FillSummary::sort_fetched(fetched, skipped);
+
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    let installed = courses
+                        .iter()
+                        .map(|(index, title, _)| (*index, title.clone()))
+                        .collect();
+                    let courses = courses
+                        .into_iter()
+                        .map(|(index, _, course)| (index as u8, course))
+                        .collect();
+                    let res = save_page.add_courses(courses);
+                    return self.write_save(
+                        SaveSide::Primary,
+                        res,
+                        AfterWrite::Fill(fill_summary, installed),
+                    );
+                }
+                Command::none()
+            }
+            Message::DismissFillSummary => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.set_fill_summary(None);
+                }
+                Command::none()
+            }
+            Message::StashCourse(index) => self.stash_course(index),
+            Message::StashSmmdbCourse(smmdb_id) => {
                 self.state = AppState::Loading;
-                self.smmdb.paginate_backward();
                 Command::perform(
-                    Smmdb::update(
-                        self.smmdb.get_query_params().clone(),
-                        self.settings.apikey.clone(),
-                    ),
-                    move |res| match res {
-                        Ok(courses) => Message::SetSmmdbCourses(courses),
-                        Err(err) => Message::FetchError(err.to_string()),
-                    },
+                    Smmdb::fetch_course(self.settings.base_url.clone(), smmdb_id.clone()),
+                    move |res| Message::AddSmmdbCourseToLibrary(smmdb_id.clone(), res),
                 )
             }
+            Message::AddSmmdbCourseToLibrary(smmdb_id, data) => {
+                self.state = AppState::Default;
+                let result = match data.and_then(parse_downloaded_course) {
+                    Ok(course) => self.library.add(&course, Some(smmdb_id)),
+                    Err(err) => Err(err.into()),
+                };
+                self.set_library_result(result, "Could not stash the course.");
+                Command::none()
+            }
+            Message::LibraryFilterChanged(filter) => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.set_library_filter(filter);
+                }
+                Command::none()
+            }
+            Message::LibraryTagInputChanged(id, tag_input) => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.set_library_tag_input(&id, tag_input);
+                }
+                Command::none()
+            }
+            Message::AddLibraryTags(id) => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    let tags = save_page.take_library_tag_input(&id);
+                    let result = self.library.add_tags(&id, &tags);
+                    self.set_library_result(result, "Could not tag the course.");
+                }
+                Command::none()
+            }
+            Message::RemoveLibraryTag(id, tag) => {
+                let result = self.library.remove_tag(&id, &tag);
+                self.set_library_result(result, "Could not remove the tag.");
+                Command::none()
+            }
+            Message::SelectLibrarySlot(id, slot) => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.select_library_slot(&id, slot);
+                }
+                Command::none()
+            }
+            Message::MoveFromLibrary(id, index) => self.move_from_library(id, index),
+            Message::InitDeleteLibraryCourse(id) => {
+                self.state = AppState::LibraryDeleteSelect(id);
+                Command::none()
+            }
+            Message::DeleteLibraryCourse(id) => {
+                let result = self.library.remove(&id);
+                self.set_library_result(result, "Could not delete the course.");
+                self.state = AppState::Default;
+                Command::none()
+            }
+            Message::DismissLibraryError => {
+                self.library.set_error(None);
+                Command::none()
+            }
+            Message::LoadMoreCourses => {
+                if self.smmdb.is_end_reached()
+                    || self.smmdb.is_loading_more()
+                    || self.smmdb.is_offline()
+                {
+                    return Command::none();
+                }
+                match self.smmdb.take_prefetched_courses() {
+                    Some(courses) => {
+                        self.smmdb.append_courses(courses);
+                        Command::batch(vec![self.fetch_thumbnails(), self.prefetch_courses()])
+                    }
+                    None => {
+                        self.smmdb.set_loading_more(true);
+                        self.prefetch_courses()
+                    }
+                }
+            }
+            Message::SetPrefetchedSmmdbCourses(listing_id, skip, courses) => {
+                if !self.smmdb.set_prefetched_courses(listing_id, skip, courses) {
+                    return Command::none();
+                }
+                if self.smmdb.is_loading_more() {
+                    self.smmdb.set_loading_more(false);
+                    if let Some(courses) = self.smmdb.take_prefetched_courses() {
+                        self.smmdb.append_courses(courses);
+                        return Command::batch(vec![
+                            self.fetch_thumbnails(),
+                            self.prefetch_courses(),
+                        ]);
+                    }
+                }
+                Command::none()
+            }
+            Message::PrefetchError(listing_id, err) => {
+                if listing_id == self.smmdb.get_listing_id() {
+                    self.smmdb.set_prefetching(false);
+                    if self.smmdb.is_loading_more() {
+                        self.smmdb.set_loading_more(false);
+                        self.smmdb.set_error(Some(err));
+                    }
+                }
+                Command::none()
+            }
             Message::UpvoteCourse(course_id) => {
                 if let Some(apikey) = self.settings.apikey.clone() {
                     Command::perform(
-                        Smmdb::vote(course_id.clone(), 1, apikey),
+                        Smmdb::vote(self.settings.base_url.clone(), course_id.clone(), 1, apikey),
                         move |res| match res {
                             Ok(()) => Message::SetVoteCourse(course_id.clone(), 1),
-                            Err(err) => Message::FetchError(err.to_string()),
+                            Err(err) => Message::FetchError(err),
                         },
                     )
                 } else {
@@ -389,12 +1220,18 @@ impl Application for App {
             }
             Message::DownvoteCourse(course_id) => {
                 if let Some(apikey) = self.settings.apikey.clone() {
-                    Command::perform(Smmdb::vote(course_id.clone(), -1, apikey), move |res| {
-                        match res {
+                    Command::perform(
+                        Smmdb::vote(
+                            self.settings.base_url.clone(),
+                            course_id.clone(),
+                            -1,
+                            apikey,
+                        ),
+                        move |res| match res {
                             Ok(()) => Message::SetVoteCourse(course_id.clone(), -1),
-                            Err(err) => Message::FetchError(err.to_string()),
-                        }
-                    })
+                            Err(err) => Message::FetchError(err),
+                        },
+                    )
                 } else {
                     Command::none()
                 }
@@ -402,10 +1239,10 @@ impl Application for App {
             Message::ResetCourseVote(course_id) => {
                 if let Some(apikey) = self.settings.apikey.clone() {
                     Command::perform(
-                        Smmdb::vote(course_id.clone(), 0, apikey),
+                        Smmdb::vote(self.settings.base_url.clone(), course_id.clone(), 0, apikey),
                         move |res| match res {
                             Ok(()) => Message::SetVoteCourse(course_id.clone(), 0),
-                            Err(err) => Message::FetchError(err.to_string()),
+                            Err(err) => Message::FetchError(err),
                         },
                     )
                 } else {
@@ -413,6 +1250,15 @@ impl Application for App {
                 }
             }
             Message::SetVoteCourse(course_id, value) => {
+                if let Some(course_detail_page) = self
+                    .current_page
+                    .get_save_page_mut()
+                    .and_then(SavePage::get_course_detail_mut)
+                {
+                    if *course_detail_page.get_course().get_id() == course_id {
+                        course_detail_page.set_own_vote(value);
+                    }
+                }
                 self.smmdb.set_own_vote(course_id, value);
                 Command::none()
             }
@@ -426,31 +1272,73 @@ impl Application for App {
                 }
                 Command::none()
             }
-            Message::TrySaveSettings(settings) => {
-                settings.save().unwrap();
-                match &settings.apikey {
-                    Some(apikey) => {
-                        Command::perform(Smmdb::try_sign_in(apikey.clone()), move |res| match res {
-                            Ok(_) => Message::SaveSettings(settings.clone()),
-                            Err(err) => Message::RejectSettings(err),
-                        })
+            Message::TrySaveSettings(mut settings) => {
+                settings.base_url = match validate_base_url(&settings.base_url) {
+                    Ok(base_url) => base_url,
+                    Err(err) => {
+                        self.error_state = AppErrorState::Some(err.to_string());
+                        return Command::none();
                     }
-                    None => {
-                        Command::perform(async {}, move |_| Message::SaveSettings(settings.clone()))
+                };
+                // The API key is checked again, if it is kept for another server.
+                let apikey = match &settings.apikey {
+                    Some(apikey) => Some(apikey.clone()),
+                    None if settings.base_url != self.settings.base_url => {
+                        self.settings.apikey.clone()
                     }
+                    None => None,
+                };
+                match apikey {
+                    Some(apikey) => Command::perform(
+                        Smmdb::try_sign_in(settings.base_url.clone(), apikey),
+                        move |res| match res {
+                            Ok(account) => Message::SaveSettings(settings.clone(), Some(account)),
+                            Err(err) => Message::RejectSettings(err),
+                        },
+                    ),
+                    None => Command::perform(async {}, move |_| {
+                        Message::SaveSettings(settings.clone(), None)
+                    }),
                 }
             }
-            Message::SaveSettings(settings) => {
-                settings.save().unwrap();
+            Message::SaveSettings(mut settings, account) => {
+                if settings.apikey.is_none() {
+                    settings.apikey = self.settings.apikey.clone();
+                }
+                if let Err(err) = settings.save() {
+                    self.error_state =
+                        AppErrorState::Some(format!("Could not save the settings.\n{}", err));
+                    return Command::none();
+                }
+                if account.is_some() {
+                    self.smmdb.set_account(account);
+                }
+                let endpoint_changed = settings.base_url != self.settings.base_url;
                 self.settings = settings;
                 if let Page::Settings(ref mut settings_page) = self.current_page {
                     self.current_page = settings_page.get_prev_page()
                 }
+                if let Some(save_page) = self.current_page.get_save_page_mut() {
+                    save_page.set_backup_retention(self.settings.backup_retention);
+                }
                 self.error_state = AppErrorState::None;
+                if endpoint_changed {
+                    self.state = AppState::Loading;
+                    self.smmdb.reset_pagination();
+                    let query_params = self.smmdb.get_query_params().clone();
+                    Command::perform(async {}, move |_| {
+                        Message::FetchCourses(query_params.clone())
+                    })
+                } else {
+                    Command::none()
+                }
+            }
+            Message::SetAccount(account) => {
+                self.smmdb.set_account(Some(account));
                 Command::none()
             }
             Message::RejectSettings(err) => {
-                self.error_state = AppErrorState::Some(err);
+                self.error_state = AppErrorState::Some(err.to_string());
                 Command::none()
             }
             Message::CloseSettings => {
@@ -466,6 +1354,118 @@ impl Application for App {
                 }
                 Command::none()
             }
+            Message::ChangeEndpointProfile(profile) => {
+                if let Page::Settings(ref mut settings_page) = self.current_page {
+                    settings_page.set_base_url(profile.get_url().to_string());
+                }
+                Command::none()
+            }
+            Message::ChangeBaseUrl(base_url) => {
+                if let Page::Settings(ref mut settings_page) = self.current_page {
+                    settings_page.set_base_url(base_url);
+                }
+                Command::none()
+            }
+            Message::ChangeBackupRetention(backup_retention) => {
+                if let Page::Settings(ref mut settings_page) = self.current_page {
+                    settings_page.set_backup_retention(backup_retention);
+                }
+                Command::none()
+            }
+            Message::OpenBackups => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    let backups_page =
+                        BackupsPage::new(save_page.get_backups().list(), save_page.get_save());
+                    save_page.open_overlay(SaveOverlay::Backups(backups_page));
+                }
+                self.state = AppState::Default;
+                self.error_state = AppErrorState::None;
+                Command::none()
+            }
+            Message::PreviewBackup(id) => {
+                let save_page = match self.current_page {
+                    Page::Save(ref mut save_page) => save_page,
+                    _ => return Command::none(),
+                };
+                let save_dir = save_page.get_backups().get_save_dir(&id);
+                let backups_page = match save_page.get_backups_page_mut() {
+                    Some(backups_page) => backups_page,
+                    None => return Command::none(),
+                };
+                backups_page.start_preview(id.clone());
+                match save_dir {
+                    Ok(save_dir) => Command::perform(smmdb_lib::Save::new(save_dir), move |res| {
+                        Message::SetBackupPreview(id.clone(), res.map_err(|err| err.into()))
+                    }),
+                    Err(err) => {
+                        backups_page.set_preview(id, Err(err.to_string()));
+                        Command::none()
+                    }
+                }
+            }
+            Message::SetBackupPreview(id, save) => {
+                if let Some(backups_page) = self
+                    .current_page
+                    .get_save_page_mut()
+                    .and_then(SavePage::get_backups_page_mut)
+                {
+                    backups_page.set_preview(id, save);
+                }
+                Command::none()
+            }
+            Message::RestoreBackup(id) => {
+                let save_page = match self.current_page {
+                    Page::Save(ref mut save_page) => save_page,
+                    _ => return Command::none(),
+                };
+                let backup = match save_page
+                    .get_backups_page_mut()
+                    .and_then(|backups_page| backups_page.get_backup(&id))
+                {
+                    Some(backup) => backup.clone(),
+                    None => return Command::none(),
+                };
+                if save_page.has_pending_changes() {
+                    self.error_state = AppErrorState::Some(
+                        "Apply or discard the staged changes before restoring a backup."
+                            .to_string(),
+                    );
+                    return Command::none();
+                }
+                if !self.downloads.is_empty() {
+                    self.error_state = AppErrorState::Some(
+                        "Cancel or wait for the downloads before restoring a backup.".to_string(),
+                    );
+                    return Command::none();
+                }
+                if let Err(err) = save_page.restore_backup(&backup) {
+                    log::error!("{:?}", err);
+                    self.error_state =
+                        AppErrorState::Some(format!("Could not restore the backup.\n{}", err));
+                    return Command::none();
+                }
+
+                self.state = AppState::Loading;
+                self.error_state = AppErrorState::None;
+                let display_name = save_page.get_display_name().clone();
+                let location = save_page.get_location().clone();
+                Command::perform(
+                    smmdb_lib::Save::new(location.clone()),
+                    move |res| match res {
+                        Ok(smmdb_save) => {
+                            Message::LoadSave(smmdb_save, display_name.clone(), location.clone())
+                        }
+                        Err(err) => Message::LoadSaveError(err.into()),
+                    },
+                )
+            }
+            Message::CloseBackups => {
+                if let Page::Save(ref mut save_page) = self.current_page {
+                    save_page.close_overlay();
+                }
+                self.error_state = AppErrorState::None;
+                Command::none()
+            }
             Message::ResetState => {
                 self.state = AppState::Default;
                 self.error_state = AppErrorState::None;
@@ -475,21 +1475,73 @@ impl Application for App {
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        match &self.state {
-            AppState::SwapSelect(_) | AppState::DownloadSelect(_) | AppState::DeleteSelect(_) => {
-                subscription::events().map(|event| match event {
-                    Event::Keyboard(keyboard::Event::KeyReleased {
-                        key_code: keyboard::KeyCode::Escape,
-                        modifiers: _,
-                    }) => Message::ResetState,
-                    _ => Message::Empty,
-                })
-            }
-            AppState::Downloading { smmdb_id, .. } => {
-                Smmdb::download_course(smmdb_id.clone()).map(Message::DownloadProgressed)
-            }
-            AppState::Default | AppState::Loading => Subscription::none(),
+        let state_subscription = match &self.state {
+            AppState::SwapSelect(_)
+            | AppState::DownloadSelect(_)
+            | AppState::DeleteSelect(_)
+            | AppState::SmmdbDeleteSelect(_)
+            | AppState::ReplaceSelect(_)
+            | AppState::LibraryDeleteSelect(_)
+            | AppState::TransferSelect(_)
+            | AppState::TransferConflict(..) => subscription::events().map(|event| match event {
+                Event::Keyboard(keyboard::Event::KeyReleased {
+                    key_code: keyboard::KeyCode::Escape,
+                    modifiers: _,
+                }) => Message::ResetState,
+                _ => Message::Empty,
+            }),
+            AppState::Uploading {
+                id,
+                save_index,
+                smmdb_id,
+                data,
+                ..
+            } => match &self.settings.apikey {
+                Some(apikey) => Smmdb::upload_course(
+                    &self.settings.base_url,
+                    *id,
+                    *save_index,
+                    apikey.clone(),
+                    data.clone(),
+                    smmdb_id.clone(),
+                )
+                .map(Message::UploadProgressed),
+                None => Subscription::none(),
+            },
+            AppState::Default => match &self.current_page {
+                Page::Save(save_page) if !save_page.has_overlay() => {
+                    subscription::events().map(|event| match event {
+                        Event::Keyboard(keyboard::Event::KeyPressed {
+                            key_code: keyboard::KeyCode::Z,
+                            modifiers,
+                        }) if modifiers.control => {
+                            if modifiers.shift {
+                                Message::Redo
+                            } else {
+                                Message::Undo
+                            }
+                        }
+                        _ => Message::Empty,
+                    })
+                }
+                _ => Subscription::none(),
+            },
+            AppState::Loading => Subscription::none(),
+        };
+        let mut subscriptions = vec![state_subscription];
+        subscriptions.extend(self.downloads.get_running().map(|job| {
+            Smmdb::download_course(
+                &self.settings.base_url,
+                job.get_id(),
+                job.get_smmdb_id().clone(),
+            )
+            .map(|(job_id, progress)| Message::DownloadProgressed(job_id, progress))
+        }));
+        if self.smmdb.is_offline() {
+            subscriptions
+                .push(time::every(CONNECTIVITY_CHECK_INTERVAL).map(|_| Message::CheckConnectivity));
         }
+        Subscription::batch(subscriptions)
     }
 
     fn view(&mut self) -> Element<Self::Message> {
@@ -513,7 +1565,14 @@ impl Application for App {
                 )
                 .push(match &mut self.current_page {
                     Page::Init(init_page) => init_page.view(&self.state, &self.error_state),
-                    Page::Save(save_page) => save_page.view(&self.state, &mut self.smmdb),
+                    Page::Save(save_page) => save_page.view(
+                        &self.state,
+                        &self.error_state,
+                        &mut self.smmdb,
+                        &self.library,
+                        &self.downloads,
+                        self.settings.apikey.is_some(),
+                    ),
                     Page::Settings(settings_page) => settings_page.view(&self.error_state),
                 }),
         )
@@ -524,6 +1583,361 @@ impl Application for App {
     }
 }
 
+impl App {
+    fn upload_course(&mut self, index: usize, smmdb_id: Option<String>) {
+        if let Page::Save(ref save_page) = self.current_page {
+            if let Some(data) = save_page.get_course_data(index) {
+                self.smmdb.set_error(None);
+                self.state = AppState::Uploading {
+                    id: self.next_upload_id,
+                    save_index: index,
+                    smmdb_id,
+                    data: Arc::new(data),
+                    progress: 0.,
+                };
+                self.next_upload_id += 1;
+            }
+        }
+    }
+
+    fn add_downloaded_course(
+        &mut self,
+        job_id: u64,
+        save_index: usize,
+        data: Vec<u8>,
+    ) -> Command<Message> {
+        let course = match parse_downloaded_course(data) {
+            Ok(course) => course,
+            Err(err) => {
+                log::error!("{:?}", err);
+                self.downloads.fail(job_id, err);
+                return Command::none();
+            }
+        };
+        let save_page = match self.current_page.get_save_page_mut() {
+            Some(save_page) => save_page,
+            None => {
+                self.downloads
+                    .fail(job_id, ApiError::Other("No save is open.".to_string()));
+                return Command::none();
+            }
+        };
+        let res = save_page.add_course(save_index as u8, course);
+        self.write_save(SaveSide::Primary, res, AfterWrite::FinishDownload(job_id))
+    }
+
+    fn transfer_course(
+        &mut self,
+        transfer: Transfer,
+        to: usize,
+        resolution: Option<ConflictResolution>,
+    ) -> Command<Message> {
+        if transfer.from == SaveSide::Primary
+            && transfer.mode == TransferMode::Move
+            && self.has_reserved_slot(vec![transfer.index])
+        {
+            return Command::none();
+        }
+        let save_page = match self.current_page {
+            Page::Save(ref mut save_page) => save_page,
+            _ => return Command::none(),
+        };
+        self.state = AppState::Loading;
+        let (side, res) = match save_page.transfer_course(transfer, to, resolution, &self.downloads)
+        {
+            Ok((side, write)) => (side, Ok(write)),
+            Err(err) => (transfer.from, Err(err)),
+        };
+        self.write_save(side, res, AfterWrite::Transfer(transfer))
+    }
+
+    fn stash_course(&mut self, index: usize) -> Command<Message> {
+        self.state = AppState::Default;
+        if self.has_reserved_slot(vec![index]) {
+            return Command::none();
+        }
+        let save_page = match self.current_page {
+            Page::Save(ref mut save_page) => save_page,
+            _ => return Command::none(),
+        };
+        if save_page.is_staged() {
+            self.set_library_result::<()>(
+                Err(anyhow::anyhow!(
+                    "Apply or discard the staged changes first."
+                )),
+                "Could not stash the course.",
+            );
+            return Command::none();
+        }
+        let course = match save_page.get_course(index) {
+            Some(course) => course,
+            None => return Command::none(),
+        };
+        let id = match self
+            .library
+            .add(course, save_page.get_smmdb_id(index).cloned())
+        {
+            Ok(id) => id,
+            Err(err) => {
+                self.set_library_result::<()>(Err(err), "Could not stash the course.");
+                return Command::none();
+            }
+        };
+        let res = save_page.delete_course(index as u8);
+        self.write_save(SaveSide::Primary, res, AfterWrite::StashCourse(id))
+    }
+
+    /// A course already in that slot is stashed in exchange, once the save has been written.
+    fn move_from_library(&mut self, id: String, index: usize) -> Command<Message> {
+        if self.has_reserved_slot(vec![index]) {
+            return Command::none();
+        }
+        let save_page = match self.current_page {
+            Page::Save(ref mut save_page) => save_page,
+            _ => return Command::none(),
+        };
+        let replaced_course = save_page
+            .get_course(index)
+            .cloned()
+            .map(|replaced_course| (replaced_course, save_page.get_smmdb_id(index).cloned()));
+        let res = if save_page.is_staged() {
+            Err(anyhow::anyhow!(
+                "Apply or discard the staged changes first."
+            ))
+        } else {
+            self.library
+                .get_course(&id)
+                .and_then(|course| save_page.add_course(index as u8, course))
+        };
+        self.write_save(
+            SaveSide::Primary,
+            res,
+            AfterWrite::MoveFromLibrary(id, replaced_course),
+        )
+    }
+
+    fn has_reserved_slot(&mut self, slots: Vec<usize>) -> bool {
+        let is_reserved = slots
+            .into_iter()
+            .any(|index| self.downloads.is_reserved(index));
+        if is_reserved {
+            self.state = AppState::Default;
+            self.error_state =
+                AppErrorState::Some("Slots reserved by a download cannot be changed.".to_string());
+        }
+        is_reserved
+    }
+
+    fn write_save(
+        &mut self,
+        side: SaveSide,
+        res: anyhow::Result<Option<SaveWrite>>,
+        after: AfterWrite,
+    ) -> Command<Message> {
+        match res {
+            Ok(Some(write)) => {
+                self.is_writing = true;
+                Command::perform(write.write(), move |res| {
+                    Message::SaveWritten(side, res, after.clone())
+                })
+            }
+            Ok(None) => self.after_write(Ok(()), after),
+            Err(err) => self.after_write(Err(err.to_string()), after),
+        }
+    }
+
+    fn after_write(&mut self, res: Result<(), String>, after: AfterWrite) -> Command<Message> {
+        match after {
+            AfterWrite::Report(action) => {
+                self.state = AppState::Default;
+                if let Err(err) = res {
+                    log::error!("{}", err);
+                    self.error_state = AppErrorState::Some(format!("{}\n{}", action, err));
+                }
+            }
+            AfterWrite::FinishDownload(job_id) => match res {
+                Ok(()) => {
+                    self.downloads.finish(job_id);
+                }
+                Err(err) => {
+                    log::error!("{}", err);
+                    self.downloads.fail(
+                        job_id,
+                        ApiError::Other(format!("Could not save the course.\n{}", err)),
+                    );
+                }
+            },
+            AfterWrite::StashCourse(id) => {
+                self.state = AppState::Default;
+                let result = res.map_err(anyhow::Error::msg);
+                if result.is_err() {
+                    if let Err(err) = self.library.remove(&id) {
+                        log::error!("{:?}", err);
+                    }
+                }
+                self.set_library_result(result, "Could not stash the course.");
+            }
+            AfterWrite::MoveFromLibrary(id, replaced_course) => {
+                self.state = AppState::Default;
+                let result = res.map_err(anyhow::Error::msg).and_then(|()| {
+                    if let Some((replaced_course, smmdb_id)) = replaced_course {
+                        self.library.add(&replaced_course, smmdb_id)?;
+                    }
+                    self.library.remove(&id)
+                });
+                self.set_library_result(result, "Could not move the course to your save.");
+            }
+            AfterWrite::Transfer(transfer) => {
+                let after = AfterWrite::Report("Could not transfer the course.");
+                if res.is_err() || transfer.mode == TransferMode::Copy {
+                    return self.after_write(res, after);
+                }
+                let res = match self.current_page.get_save_page_mut() {
+                    Some(save_page) => save_page
+                        .get_side_mut(transfer.from)
+                        .ok_or_else(|| anyhow::anyhow!("The save has been closed."))
+                        .and_then(|source| source.delete_course(transfer.index as u8)),
+                    None => Ok(None),
+                };
+                return self.write_save(transfer.from, res, after);
+            }
+            AfterWrite::Fill(mut fill_summary, installed) => {
+                if let Err(err) = &res {
+                    log::error!("{}", err);
+                }
+                fill_summary.finish(installed, res);
+                if let Some(save_page) = self.current_page.get_save_page_mut() {
+                    save_page.set_fill_summary(Some(fill_summary));
+                }
+            }
+        }
+        Command::none()
+    }
+
+    fn run_queued_messages(&mut self, command: Command<Message>) -> Command<Message> {
+        let mut commands = vec![command];
+        while !self.is_writing {
+            match self.queued_messages.pop_front() {
+                Some(message) => commands.push(self.update(message)),
+                None => break,
+            }
+        }
+        Command::batch(commands)
+    }
+
+    fn set_library_result<T>(&mut self, result: anyhow::Result<T>, action: &str) {
+        match result {
+            Ok(_) => self.library.set_error(None),
+            Err(err) => {
+                log::error!("{:?}", err);
+                self.library.set_error(Some(format!("{}\n{}", action, err)));
+            }
+        }
+    }
+
+    fn fetch_courses(&self) -> Command<Message> {
+        self.fetch_courses_with(self.smmdb.get_query_params().clone(), false)
+    }
+
+    /// Background fetches silently keep the current courses on failure.
+    fn fetch_courses_with(&self, query_params: QueryParams, background: bool) -> Command<Message> {
+        let base_url = self.settings.base_url.clone();
+        let apikey = self.settings.apikey.clone();
+        Command::perform(
+            async move {
+                match Smmdb::update(base_url.clone(), query_params.clone(), apikey.clone()).await {
+                    Ok(courses) => {
+                        if let Err(err) = ListingCache::store(
+                            &base_url,
+                            apikey.as_deref(),
+                            &query_params,
+                            &courses,
+                        ) {
+                            log::warn!("Could not cache courses: {}", err);
+                        }
+                        Message::SetSmmdbCourses(courses)
+                    }
+                    Err(_) if background => Message::Empty,
+                    Err(err) => {
+                        match ListingCache::load(&base_url, apikey.as_deref(), &query_params) {
+                            Some(listing) if err.is_connection_error() => {
+                                Message::SetCachedSmmdbCourses(listing.courses, listing.fetched_at)
+                            }
+                            _ => Message::FetchError(err),
+                        }
+                    }
+                }
+            },
+            |message| message,
+        )
+    }
+
+    fn prefetch_courses(&mut self) -> Command<Message> {
+        if self.smmdb.is_end_reached() || self.smmdb.is_prefetching() {
+            return Command::none();
+        }
+        self.smmdb.set_prefetching(true);
+        let listing_id = self.smmdb.get_listing_id();
+        let query_params = self.smmdb.get_next_page_query_params();
+        let skip = query_params.skip;
+        let base_url = self.settings.base_url.clone();
+        let apikey = self.settings.apikey.clone();
+        Command::perform(
+            Smmdb::update(base_url, query_params, apikey),
+            move |res| match res {
+                Ok(courses) => Message::SetPrefetchedSmmdbCourses(listing_id, skip, courses),
+                Err(err) => Message::PrefetchError(listing_id, err),
+            },
+        )
+    }
+
+    fn fetch_thumbnails(&mut self) -> Command<Message> {
+        let courses: Vec<(String, i64)> = self
+            .smmdb
+            .get_course_panels()
+            .values()
+            .filter(|panel| !panel.has_thumbnail())
+            .map(|panel| {
+                (
+                    panel.get_id().clone(),
+                    panel.get_course().get_last_modified(),
+                )
+            })
+            .collect();
+
+        let mut commands = Vec::<Command<Message>>::new();
+        for (id, last_modified) in courses {
+            let base_url = self.settings.base_url.clone();
+            commands.push(Command::perform(
+                async move {
+                    futures::join!(
+                        Smmdb::fetch_thumbnail(base_url, id.clone(), last_modified, "m"),
+                        futures::future::ok::<String, String>(id)
+                    )
+                },
+                |(thumbnail, id)| {
+                    if let (Ok(thumbnail), Ok(id)) = (thumbnail, id) {
+                        Message::SetSmmdbCourseThumbnail(thumbnail, id)
+                    } else {
+                        // TODO handle error
+                        Message::Empty
+                    }
+                },
+            ));
+        }
+        Command::batch(commands)
+    }
+}
+
+fn parse_downloaded_course(data: Vec<u8>) -> Result<smmdb_lib::Course2, ApiError> {
+    data.try_into().map_err(|err| {
+        ApiError::Other(format!(
+            "The downloaded file is not a valid course.\n{:?}",
+            err
+        ))
+    })
+}
+
 struct AppStyle;
 
 impl container::StyleSheet for AppStyle {
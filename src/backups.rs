@@ -0,0 +1,378 @@
+use crate::settings::get_config_dir;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs::{copy, create_dir_all, read, read_dir, remove_dir_all, remove_file, write},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+const BACKUP_FILE: &str = "backup.json";
+const SAVE_DIR: &str = "save";
+
+/// Only the newest snapshots of a save folder are kept, as many as the retention allows.
+#[derive(Clone, Debug)]
+pub struct BackupStore {
+    /// The config directory, if unset.
+    root: Option<PathBuf>,
+    location: PathBuf,
+    retention: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Backup {
+    id: String,
+    created_at: i64,
+    operation: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlotContent {
+    title: String,
+    hash: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct SlotChange {
+    pub index: usize,
+    pub current: Option<String>,
+    pub backup: Option<String>,
+}
+
+impl BackupStore {
+    pub fn new(location: PathBuf, retention: usize) -> BackupStore {
+        BackupStore {
+            root: None,
+            location,
+            retention,
+        }
+    }
+
+    pub fn new_in(root: PathBuf, location: PathBuf, retention: usize) -> BackupStore {
+        BackupStore {
+            root: Some(root),
+            location,
+            retention,
+        }
+    }
+
+    pub fn set_retention(&mut self, retention: usize) {
+        self.retention = retention;
+    }
+
+    pub fn create(&self, operation: &str) -> Result<Backup> {
+        let backup = self.copy_save(operation)?;
+        self.prune()?;
+        Ok(backup)
+    }
+
+    fn copy_save(&self, operation: &str) -> Result<Backup> {
+        let dir = self.get_dir()?;
+        // Ids have to keep increasing, even when backups are taken within the same millisecond
+        // and the ids of pruned ones become free again.
+        let newest = self
+            .list()
+            .first()
+            .and_then(|backup| backup.id.parse::<i64>().ok());
+        let mut id = Utc::now().timestamp_millis();
+        if let Some(newest) = newest {
+            id = id.max(newest + 1);
+        }
+        while dir.join(id.to_string()).exists() {
+            id += 1;
+        }
+        let backup = Backup {
+            id: id.to_string(),
+            created_at: Utc::now().timestamp(),
+            operation: operation.to_string(),
+        };
+        let backup_dir = dir.join(&backup.id);
+        copy_dir(&self.location, &backup_dir.join(SAVE_DIR))?;
+        write(
+            backup_dir.join(BACKUP_FILE),
+            serde_json::to_string(&backup)?,
+        )?;
+        Ok(backup)
+    }
+
+    pub fn list(&self) -> Vec<Backup> {
+        let dir = match self.get_dir() {
+            Ok(dir) => dir,
+            Err(_) => return vec![],
+        };
+        let mut backups: Vec<Backup> = match read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| read(entry.path().join(BACKUP_FILE)).ok())
+                .filter_map(|backup| serde_json::from_slice(&backup).ok())
+                .collect(),
+            Err(_) => vec![],
+        };
+        // Ids are timestamps in milliseconds.
+        backups.sort_by(|a, b| b.id.cmp(&a.id));
+        backups
+    }
+
+    pub fn get_save_dir(&self, id: &str) -> Result<PathBuf> {
+        Ok(self.get_dir()?.join(id).join(SAVE_DIR))
+    }
+
+    /// Course files, which did not exist at the time of the backup, are removed.
+    /// Backups exceeding the retention are only removed afterwards,
+    /// since the one being restored might be among them.
+    pub fn restore(&self, id: &str, operation: &str) -> Result<()> {
+        let save_dir = self.get_save_dir(id)?;
+        if !save_dir.exists() {
+            bail!("The backup does not exist anymore.");
+        }
+        self.copy_save(operation).map_err(|err| {
+            anyhow!(
+                "Could not back up the save, it was left unchanged.\n{}",
+                err
+            )
+        })?;
+        for entry in read_dir(&self.location)? {
+            let path = entry?.path();
+            let is_course_file = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .map_or(false, |file_name| file_name.starts_with("course_"));
+            if path.is_file()
+                && is_course_file
+                && !save_dir.join(path.file_name().unwrap()).exists()
+            {
+                remove_file(path)?;
+            }
+        }
+        copy_dir(&save_dir, &self.location)?;
+        self.prune()
+    }
+
+    fn prune(&self) -> Result<()> {
+        let dir = self.get_dir()?;
+        for backup in self.list().into_iter().skip(self.retention.max(1)) {
+            remove_dir_all(dir.join(backup.id))?;
+        }
+        Ok(())
+    }
+
+    fn get_dir(&self) -> Result<PathBuf> {
+        let key: String = self
+            .location
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let mut dir = match &self.root {
+            Some(root) => root.clone(),
+            None => get_config_dir()?.join("backups"),
+        };
+        dir.push(key);
+        Ok(dir)
+    }
+}
+
+impl Backup {
+    pub fn get_id(&self) -> &String {
+        &self.id
+    }
+
+    pub fn get_created_at(&self) -> i64 {
+        self.created_at
+    }
+
+    pub fn get_operation(&self) -> &String {
+        &self.operation
+    }
+}
+
+pub fn get_slot_contents(save: &smmdb_lib::Save) -> Vec<Option<SlotContent>> {
+    save.get_own_courses()
+        .iter()
+        .map(|course| {
+            course.as_ref().map(|course| {
+                let course = course.get_course();
+                let mut hasher = DefaultHasher::new();
+                course.get_course_data().hash(&mut hasher);
+                SlotContent {
+                    title: course.get_course().get_header().get_title().to_string(),
+                    hash: hasher.finish(),
+                }
+            })
+        })
+        .collect()
+}
+
+pub fn diff_slots(
+    backup: &[Option<SlotContent>],
+    current: &[Option<SlotContent>],
+) -> Vec<SlotChange> {
+    (0..backup.len().max(current.len()))
+        .filter_map(|index| {
+            let backup = backup.get(index).cloned().flatten();
+            let current = current.get(index).cloned().flatten();
+            if backup == current {
+                None
+            } else {
+                Some(SlotChange {
+                    index,
+                    current: current.map(|slot| slot.title),
+                    backup: backup.map(|slot| slot.title),
+                })
+            }
+        })
+        .collect()
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    create_dir_all(to)?;
+    for entry in read_dir(from)? {
+        let path = entry?.path();
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn create_store(test_dir: &TestDir, retention: usize) -> BackupStore {
+        let location = test_dir.path().join("save");
+        create_dir_all(&location).unwrap();
+        BackupStore::new_in(test_dir.path().join("backups"), location, retention)
+    }
+
+    fn slot(title: &str, hash: u64) -> Option<SlotContent> {
+        Some(SlotContent {
+            title: title.to_string(),
+            hash,
+        })
+    }
+
+    #[test]
+    fn creates_backups_of_the_save_folder() {
+        let test_dir = TestDir::new("backups-create");
+        let store = create_store(&test_dir, 10);
+        write(store.location.join("course_data_000.bcd"), b"first").unwrap();
+
+        let backup = store.create("Swap slots 1 and 2").unwrap();
+
+        let backups = store.list();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].get_id(), backup.get_id());
+        assert_eq!(backups[0].get_operation(), "Swap slots 1 and 2");
+        let save_dir = store.get_save_dir(backup.get_id()).unwrap();
+        assert_eq!(
+            read(save_dir.join("course_data_000.bcd")).unwrap(),
+            b"first"
+        );
+    }
+
+    #[test]
+    fn prunes_backups_beyond_retention() {
+        let test_dir = TestDir::new("backups-prune");
+        let store = create_store(&test_dir, 2);
+
+        for index in 0..4 {
+            store.create(&format!("Operation {}", index)).unwrap();
+        }
+
+        let operations: Vec<String> = store
+            .list()
+            .iter()
+            .map(|backup| backup.get_operation().clone())
+            .collect();
+        assert_eq!(operations, vec!["Operation 3", "Operation 2"]);
+    }
+
+    #[test]
+    fn restores_backups_and_removes_newer_course_files() {
+        let test_dir = TestDir::new("backups-restore");
+        let store = create_store(&test_dir, 10);
+        write(store.location.join("course_data_000.bcd"), b"first").unwrap();
+        let backup = store.create("Delete slot 1").unwrap();
+        write(store.location.join("course_data_000.bcd"), b"changed").unwrap();
+        write(store.location.join("course_data_001.bcd"), b"second").unwrap();
+
+        store.restore(backup.get_id(), "Restore").unwrap();
+
+        assert_eq!(
+            read(store.location.join("course_data_000.bcd")).unwrap(),
+            b"first"
+        );
+        assert!(!store.location.join("course_data_001.bcd").exists());
+        let backups = store.list();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].get_operation(), "Restore");
+        let save_dir = store.get_save_dir(backups[0].get_id()).unwrap();
+        assert_eq!(
+            read(save_dir.join("course_data_001.bcd")).unwrap(),
+            b"second"
+        );
+    }
+
+    #[test]
+    fn restores_backups_beyond_retention() {
+        let test_dir = TestDir::new("backups-restore-retention");
+        let store = create_store(&test_dir, 1);
+        write(store.location.join("course_data_000.bcd"), b"first").unwrap();
+        let backup = store.create("Delete slot 1").unwrap();
+        write(store.location.join("course_data_000.bcd"), b"changed").unwrap();
+
+        store.restore(backup.get_id(), "Restore").unwrap();
+
+        assert_eq!(
+            read(store.location.join("course_data_000.bcd")).unwrap(),
+            b"first"
+        );
+        let backups = store.list();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].get_operation(), "Restore");
+    }
+
+    #[test]
+    fn fails_to_restore_missing_backups() {
+        let test_dir = TestDir::new("backups-missing");
+        let store = create_store(&test_dir, 10);
+
+        assert!(store.restore("0", "Restore").is_err());
+        assert!(store.list().is_empty());
+    }
+
+    #[test]
+    fn diffs_changed_slots() {
+        let backup = vec![slot("A", 1), None, slot("C", 3), slot("D", 4)];
+        let current = vec![slot("A", 1), slot("B", 2), slot("C", 30)];
+
+        let changes = diff_slots(&backup, &current);
+
+        let changes: Vec<(usize, Option<&str>, Option<&str>)> = changes
+            .iter()
+            .map(|change| {
+                (
+                    change.index,
+                    change.current.as_deref(),
+                    change.backup.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (1, Some("B"), None),
+                (2, Some("C"), Some("C")),
+                (3, None, Some("D")),
+            ]
+        );
+    }
+}
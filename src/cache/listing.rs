@@ -0,0 +1,150 @@
+use super::get_cache_dir;
+use crate::smmdb::{Course2Response, QueryParams};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{metadata, read, read_dir, remove_file, write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const MAX_CACHE_SIZE: u64 = 20 * 1024 * 1024;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CachedListing {
+    pub fetched_at: i64,
+    pub courses: Vec<Course2Response>,
+}
+
+/// Listings are stored per server, API key and query, since each of them changes the response.
+pub struct ListingCache;
+
+impl ListingCache {
+    pub fn store(
+        base_url: &str,
+        apikey: Option<&str>,
+        query_params: &QueryParams,
+        courses: &[Course2Response],
+    ) -> Result<()> {
+        let fetched_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let listing = CachedListing {
+            fetched_at,
+            courses: courses.to_vec(),
+        };
+        let dir = get_cache_dir("listings")?;
+        write(
+            dir.join(get_file_name(base_url, apikey, query_params)?),
+            serde_json::to_string(&listing)?,
+        )?;
+        prune(&dir, MAX_CACHE_SIZE)
+    }
+
+    pub fn load(
+        base_url: &str,
+        apikey: Option<&str>,
+        query_params: &QueryParams,
+    ) -> Option<CachedListing> {
+        let path = get_cache_dir("listings")
+            .ok()?
+            .join(get_file_name(base_url, apikey, query_params).ok()?);
+        let listing = read(path).ok()?;
+        serde_json::from_slice(&listing).ok()
+    }
+}
+
+fn get_file_name(
+    base_url: &str,
+    apikey: Option<&str>,
+    query_params: &QueryParams,
+) -> Result<String> {
+    let query = serde_qs::to_string(query_params).map_err(|err| anyhow::anyhow!("{}", err))?;
+    let key = format!(
+        "{}\n{}\n{}",
+        base_url.trim_end_matches('/'),
+        apikey.unwrap_or_default(),
+        query
+    );
+    Ok(format!("{:016x}.json", fnv1a(key.as_bytes())))
+}
+
+/// A hash, which stays the same across Rust releases, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn prune(dir: &Path, max_size: u64) -> Result<()> {
+    let mut listings: Vec<(PathBuf, u64, SystemTime)> = vec![];
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        let metadata = metadata(&path)?;
+        if metadata.is_file() {
+            listings.push((path, metadata.len(), metadata.modified()?));
+        }
+    }
+    let mut total: u64 = listings.iter().map(|(_, size, _)| size).sum();
+    listings.sort_by_key(|(_, _, modified)| *modified);
+    for (path, size, _) in listings {
+        if total <= max_size {
+            break;
+        }
+        remove_file(path)?;
+        total -= size;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    use std::{thread::sleep, time::Duration};
+
+    fn query_params() -> QueryParams {
+        serde_json::from_str("{}").unwrap()
+    }
+
+    #[test]
+    fn hashes_stably() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn keeps_listings_apart_per_server_and_apikey() {
+        let query_params = query_params();
+        let production = get_file_name("https://api.smmdb.net", None, &query_params).unwrap();
+
+        assert_eq!(
+            get_file_name("https://api.smmdb.net/", None, &query_params).unwrap(),
+            production
+        );
+        assert_ne!(
+            get_file_name("http://localhost:3030", None, &query_params).unwrap(),
+            production
+        );
+        assert_ne!(
+            get_file_name("https://api.smmdb.net", Some("apikey"), &query_params).unwrap(),
+            production
+        );
+    }
+
+    #[test]
+    fn prunes_oldest_listings() {
+        let dir = TestDir::new("listings");
+        for name in ["a", "b", "c"].iter() {
+            write(dir.path().join(name), [0; 4]).unwrap();
+            sleep(Duration::from_millis(20));
+        }
+
+        prune(dir.path(), 8).unwrap();
+
+        assert!(!dir.path().join("a").exists());
+        assert!(dir.path().join("b").exists());
+        assert!(dir.path().join("c").exists());
+    }
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use std::{fs::create_dir_all, path::PathBuf};
+
+mod listing;
+mod thumbnail;
+
+pub use listing::*;
+pub use thumbnail::*;
+
+pub fn get_cache_dir(name: &str) -> Result<PathBuf> {
+    let mut cache_dir = if let Some(cache_dir) = dirs::cache_dir() {
+        cache_dir
+    } else {
+        dirs::data_dir().expect("Could not initialize app directory")
+    };
+    cache_dir.push("smmdb-client");
+    cache_dir.push(name);
+    if !cache_dir.exists() {
+        create_dir_all(cache_dir.clone())?;
+    }
+    Ok(cache_dir)
+}
@@ -0,0 +1,258 @@
+use super::get_cache_dir;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{read, remove_file, write},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+const MAX_CACHE_SIZE: u64 = 50 * 1024 * 1024;
+const INDEX_FILE: &str = "index.json";
+
+lazy_static! {
+    pub static ref THUMBNAIL_CACHE: Mutex<ThumbnailCache> = Mutex::new(
+        get_cache_dir("thumbnails")
+            .map(|dir| ThumbnailCache::new(dir, MAX_CACHE_SIZE))
+            .unwrap_or_else(|_| ThumbnailCache::disabled())
+    );
+}
+
+#[derive(Debug)]
+pub struct ThumbnailCache {
+    dir: Option<PathBuf>,
+    max_size: u64,
+    index: CacheIndex,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct CacheIndex {
+    tick: u64,
+    entries: HashMap<String, CacheEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CacheEntry {
+    size: u64,
+    last_access: u64,
+}
+
+impl ThumbnailCache {
+    pub fn new(dir: PathBuf, max_size: u64) -> ThumbnailCache {
+        let mut index_path = dir.clone();
+        index_path.push(INDEX_FILE);
+        let index = read(index_path)
+            .ok()
+            .and_then(|index| serde_json::from_slice(&index).ok())
+            .unwrap_or_default();
+        ThumbnailCache {
+            dir: Some(dir),
+            max_size,
+            index,
+        }
+    }
+
+    pub fn disabled() -> ThumbnailCache {
+        ThumbnailCache {
+            dir: None,
+            max_size: 0,
+            index: CacheIndex::default(),
+        }
+    }
+
+    /// The access only counts in memory and is written with the index on the next insert.
+    pub fn get(
+        &mut self,
+        base_url: &str,
+        id: &str,
+        size: &str,
+        last_modified: i64,
+    ) -> Option<Vec<u8>> {
+        let key = get_key(base_url, id, size, last_modified);
+        let path = self.get_entry_path(&key)?;
+        if !self.index.entries.contains_key(&key) {
+            return None;
+        }
+        match read(path) {
+            Ok(thumbnail) => {
+                self.index.tick += 1;
+                if let Some(entry) = self.index.entries.get_mut(&key) {
+                    entry.last_access = self.index.tick;
+                }
+                Some(thumbnail)
+            }
+            Err(_) => {
+                self.index.entries.remove(&key);
+                None
+            }
+        }
+    }
+
+    pub fn insert(
+        &mut self,
+        base_url: &str,
+        id: &str,
+        size: &str,
+        last_modified: i64,
+        thumbnail: &[u8],
+    ) -> Result<()> {
+        let key = get_key(base_url, id, size, last_modified);
+        let path = match self.get_entry_path(&key) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        // Older versions of the same thumbnail will never be requested again.
+        let prefix = get_key_prefix(base_url, id, size);
+        let outdated: Vec<String> = self
+            .index
+            .entries
+            .keys()
+            .filter(|other| other.starts_with(&prefix) && **other != key)
+            .cloned()
+            .collect();
+        for key in outdated {
+            self.remove(&key);
+        }
+
+        write(path, thumbnail)?;
+        self.index.tick += 1;
+        self.index.entries.insert(
+            key,
+            CacheEntry {
+                size: thumbnail.len() as u64,
+                last_access: self.index.tick,
+            },
+        );
+        self.evict();
+        self.save_index()
+    }
+
+    fn evict(&mut self) {
+        let mut total: u64 = self.index.entries.values().map(|entry| entry.size).sum();
+        if total <= self.max_size {
+            return;
+        }
+        let mut entries: Vec<(String, u64, u64)> = self
+            .index
+            .entries
+            .iter()
+            .map(|(key, entry)| (key.clone(), entry.size, entry.last_access))
+            .collect();
+        entries.sort_by_key(|(_, _, last_access)| *last_access);
+        for (key, size, _) in entries {
+            if total <= self.max_size {
+                break;
+            }
+            self.remove(&key);
+            total -= size;
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(path) = self.get_entry_path(key) {
+            remove_file(path).ok();
+        }
+        self.index.entries.remove(key);
+    }
+
+    fn get_entry_path(&self, key: &str) -> Option<PathBuf> {
+        let mut path = self.dir.clone()?;
+        path.push(format!("{}.jpg", key));
+        Some(path)
+    }
+
+    fn save_index(&self) -> Result<()> {
+        if let Some(dir) = &self.dir {
+            let mut index_path = dir.clone();
+            index_path.push(INDEX_FILE);
+            write(index_path, serde_json::to_string(&self.index)?)?;
+        }
+        Ok(())
+    }
+}
+
+fn get_key(base_url: &str, id: &str, size: &str, last_modified: i64) -> String {
+    format!("{}{}", get_key_prefix(base_url, id, size), last_modified)
+}
+
+/// Thumbnails are kept apart per server, since course ids are only unique on one server.
+fn get_key_prefix(base_url: &str, id: &str, size: &str) -> String {
+    let server: String = base_url
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}_{}_{}_", server, id, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    const SERVER: &str = "http://localhost:3030";
+
+    #[test]
+    fn evicts_least_recently_used_thumbnails() {
+        let dir = TestDir::new("thumbnails");
+        let mut cache = ThumbnailCache::new(dir.path().to_path_buf(), 8);
+        cache.insert(SERVER, "a", "m", 1, &[1; 4]).unwrap();
+        cache.insert(SERVER, "b", "m", 1, &[2; 4]).unwrap();
+        assert_eq!(cache.get(SERVER, "a", "m", 1), Some(vec![1; 4]));
+
+        cache.insert(SERVER, "c", "m", 1, &[3; 4]).unwrap();
+
+        assert_eq!(cache.get(SERVER, "a", "m", 1), Some(vec![1; 4]));
+        assert_eq!(cache.get(SERVER, "b", "m", 1), None);
+        assert_eq!(cache.get(SERVER, "c", "m", 1), Some(vec![3; 4]));
+    }
+
+    #[test]
+    fn writes_access_order_only_on_insert() {
+        let dir = TestDir::new("thumbnails");
+        let index_path = dir.path().join(INDEX_FILE);
+        let mut cache = ThumbnailCache::new(dir.path().to_path_buf(), 8);
+        cache.insert(SERVER, "a", "m", 1, &[1; 4]).unwrap();
+        cache.insert(SERVER, "b", "m", 1, &[2; 4]).unwrap();
+        let index = read(&index_path).unwrap();
+
+        cache.get(SERVER, "a", "m", 1);
+        assert_eq!(read(&index_path).unwrap(), index);
+
+        cache.insert(SERVER, "c", "m", 1, &[3; 4]).unwrap();
+        let mut cache = ThumbnailCache::new(dir.path().to_path_buf(), 8);
+        assert_eq!(cache.get(SERVER, "a", "m", 1), Some(vec![1; 4]));
+        assert_eq!(cache.get(SERVER, "b", "m", 1), None);
+    }
+
+    #[test]
+    fn replaces_outdated_thumbnails() {
+        let dir = TestDir::new("thumbnails");
+        let mut cache = ThumbnailCache::new(dir.path().to_path_buf(), 64);
+        cache.insert(SERVER, "a", "m", 1, &[1; 4]).unwrap();
+        cache.insert(SERVER, "a", "l", 1, &[2; 4]).unwrap();
+        cache.insert(SERVER, "a", "m", 2, &[3; 4]).unwrap();
+
+        assert_eq!(cache.get(SERVER, "a", "m", 1), None);
+        assert_eq!(cache.get(SERVER, "a", "m", 2), Some(vec![3; 4]));
+        assert_eq!(cache.get(SERVER, "a", "l", 1), Some(vec![2; 4]));
+    }
+
+    #[test]
+    fn keeps_thumbnails_apart_per_server() {
+        let dir = TestDir::new("thumbnails");
+        let mut cache = ThumbnailCache::new(dir.path().to_path_buf(), 64);
+        cache.insert(SERVER, "a", "m", 1, &[1; 4]).unwrap();
+        cache
+            .insert("https://api.smmdb.net", "a", "m", 1, &[2; 4])
+            .unwrap();
+
+        assert_eq!(cache.get(SERVER, "a", "m", 1), Some(vec![1; 4]));
+        assert_eq!(
+            cache.get("https://api.smmdb.net", "a", "m", 1),
+            Some(vec![2; 4])
+        );
+    }
+}
@@ -0,0 +1,478 @@
+use crate::{
+    backups::BackupStore,
+    course_file::{export_course, import_course},
+    emu::guess_emu_dir,
+    settings::validate_base_url,
+    smmdb::{Course2Response, Difficulty},
+    upload_registry::UploadRegistry,
+    Settings, Smmdb,
+};
+
+use anyhow::{anyhow, bail, Result};
+use std::{
+    convert::TryInto,
+    path::{Path, PathBuf},
+};
+
+const USAGE: &str = "\
+Usage: smmdb [--server <url>] <command> [options]
+
+Runs the graphical client if no command is given.
+
+Commands:
+    detect-saves                            List all automatically detected save folders
+    list --save <dir>                       List all course slots of a save
+    search [--title <title>] [--uploader <name>] [--difficulty <difficulty>]
+           [--limit <n>] [--skip <n>]       Search courses on SMMDB
+    download <smmdb-id> --slot <n> --save <dir>
+                                            Download a course from SMMDB into an empty slot
+    swap <slot> <slot> --save <dir>         Swap two course slots
+    delete <slot> --save <dir>              Delete the course in a slot
+    export <slot> <file> --save <dir>       Export the course in a slot to a course data file
+                                            and a thumbnail file next to it
+    import <file> --slot <n> --save <dir>   Import a course file into an empty slot
+    help                                    Print this message
+
+Slots are numbered starting at 1, as printed by the list command.";
+
+const COMMANDS: [&str; 11] = [
+    "detect-saves",
+    "list",
+    "search",
+    "download",
+    "swap",
+    "delete",
+    "export",
+    "import",
+    "help",
+    "--help",
+    "-h",
+];
+
+struct Args {
+    positional: Vec<String>,
+    server: Option<String>,
+    save: Option<PathBuf>,
+    slot: Option<String>,
+    title: Option<String>,
+    uploader: Option<String>,
+    difficulty: Option<String>,
+    limit: Option<String>,
+    skip: Option<String>,
+}
+
+impl Args {
+    fn parse(args: Vec<String>) -> Result<Args> {
+        let mut parsed = Args {
+            positional: vec![],
+            server: None,
+            save: None,
+            slot: None,
+            title: None,
+            uploader: None,
+            difficulty: None,
+            limit: None,
+            skip: None,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let target = match arg.as_ref() {
+                "--server" => &mut parsed.server,
+                "--save" => {
+                    parsed.save = Some(Args::value(&arg, args.next())?.into());
+                    continue;
+                }
+                "--slot" => &mut parsed.slot,
+                "--title" => &mut parsed.title,
+                "--uploader" => &mut parsed.uploader,
+                "--difficulty" => &mut parsed.difficulty,
+                "--limit" => &mut parsed.limit,
+                "--skip" => &mut parsed.skip,
+                "--help" | "-h" => {
+                    parsed.positional.push("help".to_string());
+                    continue;
+                }
+                _ if arg.starts_with("--") => bail!("Unknown option {}\n\n{}", arg, USAGE),
+                _ => {
+                    parsed.positional.push(arg);
+                    continue;
+                }
+            };
+            *target = Some(Args::value(&arg, args.next())?);
+        }
+        if let Some(server) = &parsed.server {
+            parsed.server =
+                Some(validate_base_url(server).map_err(|err| anyhow!("{}\n\n{}", err, USAGE))?);
+        }
+        Ok(parsed)
+    }
+
+    fn value(option: &str, value: Option<String>) -> Result<String> {
+        value.ok_or_else(|| anyhow!("Missing value for option {}", option))
+    }
+
+    fn get_save(&self) -> Result<PathBuf> {
+        self.save
+            .clone()
+            .ok_or_else(|| anyhow!("Missing --save <dir> option\n\n{}", USAGE))
+    }
+
+    fn get_positional(&self, index: usize, name: &str) -> Result<&String> {
+        self.positional
+            .get(index)
+            .ok_or_else(|| anyhow!("Missing argument <{}>\n\n{}", name, USAGE))
+    }
+}
+
+/// Unknown arguments, like the ones some platforms pass on launch, are ignored.
+pub fn is_command(args: &[String]) -> bool {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--server" {
+            args.next();
+            continue;
+        }
+        return COMMANDS.contains(&arg.as_str());
+    }
+    false
+}
+
+pub fn run(args: Vec<String>) -> Result<()> {
+    let args = Args::parse(args)?;
+    let command = match args.positional.first() {
+        Some(command) => command.clone(),
+        None => bail!("Missing command\n\n{}", USAGE),
+    };
+
+    let mut settings = Settings::load()?;
+    if let Some(server) = args.server.clone() {
+        settings.base_url = server;
+    }
+
+    let mut runtime = tokio::runtime::Runtime::new()?;
+    match command.as_ref() {
+        "detect-saves" => detect_saves(),
+        "list" => runtime.block_on(list(args.get_save()?)),
+        "search" => runtime.block_on(search(&args, settings)),
+        "download" => {
+            let smmdb_id = args.get_positional(1, "smmdb-id")?.clone();
+            let slot = parse_slot(
+                args.slot
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("Missing --slot <n> option\n\n{}", USAGE))?,
+            )?;
+            runtime.block_on(download(args.get_save()?, smmdb_id, slot, settings))
+        }
+        "export" => {
+            let slot = parse_slot(args.get_positional(1, "slot")?)?;
+            let file = PathBuf::from(args.get_positional(2, "file")?);
+            runtime.block_on(export(args.get_save()?, slot, file))
+        }
+        "import" => {
+            let file = PathBuf::from(args.get_positional(1, "file")?);
+            let slot = parse_slot(
+                args.slot
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("Missing --slot <n> option\n\n{}", USAGE))?,
+            )?;
+            runtime.block_on(import(args.get_save()?, file, slot, settings))
+        }
+        "swap" => {
+            let first = parse_slot(args.get_positional(1, "slot")?)?;
+            let second = parse_slot(args.get_positional(2, "slot")?)?;
+            runtime.block_on(swap(args.get_save()?, first, second, settings))
+        }
+        "delete" => {
+            let slot = parse_slot(args.get_positional(1, "slot")?)?;
+            runtime.block_on(delete(args.get_save()?, slot, settings))
+        }
+        "help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => bail!("Unknown command {}\n\n{}", command, USAGE),
+    }
+}
+
+fn parse_slot(slot: &str) -> Result<u8> {
+    match slot.parse::<u8>() {
+        Ok(slot) if slot >= 1 && slot <= 60 => Ok(slot - 1),
+        _ => bail!("Invalid slot {}. Slots are numbered from 1 to 60.", slot),
+    }
+}
+
+fn parse_difficulty(difficulty: &str) -> Result<Difficulty> {
+    Difficulty::ALL
+        .iter()
+        .find(|d| d.to_string().eq_ignore_ascii_case(difficulty))
+        .cloned()
+        .ok_or_else(|| {
+            anyhow!(
+                "Invalid difficulty {}. Valid values are easy, normal, expert and superexpert.",
+                difficulty
+            )
+        })
+}
+
+async fn load_save(location: PathBuf) -> Result<smmdb_lib::Save> {
+    smmdb_lib::Save::new(location).await.map_err(|err| {
+        let err: String = err.into();
+        anyhow!("Could not load save file. Full error:\n{}", err)
+    })
+}
+
+async fn write_save(
+    save: &mut smmdb_lib::Save,
+    location: &Path,
+    operation: String,
+    settings: &Settings,
+) -> Result<()> {
+    BackupStore::new(location.to_path_buf(), settings.backup_retention)
+        .create(&operation)
+        .map_err(|err| {
+            anyhow!(
+                "Could not back up the save, it was left unchanged.\n{}",
+                err
+            )
+        })?;
+    save.save()
+        .await
+        .map_err(|err| -> anyhow::Error { err.into() })
+}
+
+fn update_uploads<F: FnOnce(&mut UploadRegistry)>(update: F) -> Result<()> {
+    let mut uploads = UploadRegistry::load();
+    update(&mut uploads);
+    uploads.save()
+}
+
+fn detect_saves() -> Result<()> {
+    let saves = guess_emu_dir()?;
+    if saves.is_empty() {
+        println!("No save folders found.");
+    }
+    for save in saves {
+        println!(
+            "{}\t{}",
+            save.get_display_name(),
+            save.get_location().display()
+        );
+    }
+    Ok(())
+}
+
+async fn list(location: PathBuf) -> Result<()> {
+    let save = load_save(location).await?;
+    for (index, course) in save.get_own_courses().iter().enumerate() {
+        match course {
+            Some(course) => println!(
+                "{:>2}  {}",
+                index + 1,
+                course.get_course().get_course().get_header().get_title()
+            ),
+            None => println!("{:>2}  (empty)", index + 1),
+        }
+    }
+    Ok(())
+}
+
+async fn search(args: &Args, settings: Settings) -> Result<()> {
+    let mut smmdb = Smmdb::new(settings.apikey.clone());
+    if let Some(title) = args.title.clone() {
+        smmdb.set_title(title);
+    }
+    if let Some(uploader) = args.uploader.clone() {
+        smmdb.set_uploader(uploader);
+    }
+    if let Some(difficulty) = &args.difficulty {
+        smmdb.set_difficulty(parse_difficulty(difficulty)?);
+    }
+    let mut query_params = smmdb.get_query_params().clone();
+    if let Some(limit) = &args.limit {
+        query_params.limit = limit.parse()?;
+    }
+    if let Some(skip) = &args.skip {
+        query_params.skip = skip.parse()?;
+    }
+
+    let courses = Smmdb::update(settings.base_url, query_params, settings.apikey).await?;
+    for course in courses.iter() {
+        print_course(course);
+    }
+    Ok(())
+}
+
+fn print_course(course: &Course2Response) {
+    let difficulty = match course.get_difficulty() {
+        Some(difficulty) => difficulty.to_string(),
+        None => "".to_string(),
+    };
+    println!(
+        "{}\t{}\t{}\t{:+}",
+        course.get_id(),
+        course.get_course().get_header().get_title(),
+        difficulty,
+        course.get_votes()
+    );
+}
+
+async fn download(location: PathBuf, smmdb_id: String, slot: u8, settings: Settings) -> Result<()> {
+    let mut save = load_save(location.clone()).await?;
+    if save.get_own_courses()[slot as usize].is_some() {
+        bail!("Slot {} is not empty.", slot + 1);
+    }
+
+    let data = Smmdb::fetch_course(settings.base_url.clone(), smmdb_id.clone()).await?;
+    let course: smmdb_lib::Course2 = data
+        .try_into()
+        .map_err(|err| anyhow!("Could not read course {}: {:?}", smmdb_id, err))?;
+    let operation = format!(
+        "Add {} to slot {}",
+        course.get_course().get_header().get_title(),
+        slot + 1
+    );
+    save.add_course(slot, course)?;
+    write_save(&mut save, &location, operation, &settings).await?;
+    update_uploads(|uploads| uploads.remove(&location, slot as usize))?;
+    println!("Downloaded course {} into slot {}.", smmdb_id, slot + 1);
+    Ok(())
+}
+
+async fn export(location: PathBuf, slot: u8, file: PathBuf) -> Result<()> {
+    let save = load_save(location).await?;
+    let course = match &save.get_own_courses()[slot as usize] {
+        Some(course) => course.get_course(),
+        None => bail!("Slot {} is empty.", slot + 1),
+    };
+    let data_path = export_course(course, &file)?;
+    println!(
+        "Exported course in slot {} to {}.",
+        slot + 1,
+        data_path.display()
+    );
+    Ok(())
+}
+
+async fn import(location: PathBuf, file: PathBuf, slot: u8, settings: Settings) -> Result<()> {
+    let mut save = load_save(location.clone()).await?;
+    if save.get_own_courses()[slot as usize].is_some() {
+        bail!("Slot {} is not empty.", slot + 1);
+    }
+
+    let course = import_course(&file)?;
+    let operation = format!(
+        "Add {} to slot {}",
+        course.get_course().get_header().get_title(),
+        slot + 1
+    );
+    save.add_course(slot, course)?;
+    write_save(&mut save, &location, operation, &settings).await?;
+    update_uploads(|uploads| uploads.remove(&location, slot as usize))?;
+    println!("Imported {} into slot {}.", file.display(), slot + 1);
+    Ok(())
+}
+
+async fn swap(location: PathBuf, first: u8, second: u8, settings: Settings) -> Result<()> {
+    let mut save = load_save(location.clone()).await?;
+    save.swap_course(first, second)?;
+    let operation = format!("Swap slots {} and {}", first + 1, second + 1);
+    write_save(&mut save, &location, operation, &settings).await?;
+    update_uploads(|uploads| uploads.swap(&location, first as usize, second as usize))?;
+    println!("Swapped slots {} and {}.", first + 1, second + 1);
+    Ok(())
+}
+
+async fn delete(location: PathBuf, slot: u8, settings: Settings) -> Result<()> {
+    let mut save = load_save(location.clone()).await?;
+    let operation = match &save.get_own_courses()[slot as usize] {
+        Some(course) => format!(
+            "Delete {} from slot {}",
+            course.get_course().get_course().get_header().get_title(),
+            slot + 1
+        ),
+        None => bail!("Slot {} is already empty.", slot + 1),
+    };
+    save.remove_course(slot)?;
+    write_save(&mut save, &location, operation, &settings).await?;
+    update_uploads(|uploads| uploads.remove(&location, slot as usize))?;
+    println!("Deleted course in slot {}.", slot + 1);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn detects_commands() {
+        assert!(is_command(&args(&["list", "--save", "save"])));
+        assert!(is_command(&args(&[
+            "--server",
+            "http://localhost",
+            "search"
+        ])));
+        assert!(is_command(&args(&["--help"])));
+        assert!(!is_command(&args(&[])));
+        assert!(!is_command(&args(&["-psn_0_12345"])));
+        assert!(!is_command(&args(&["/path/to/save"])));
+        assert!(!is_command(&args(&["--server", "http://localhost"])));
+    }
+
+    #[test]
+    fn parses_options_and_positional_arguments() {
+        let parsed = Args::parse(args(&[
+            "--server",
+            "http://localhost",
+            "download",
+            "abc",
+            "--slot",
+            "3",
+            "--save",
+            "save",
+        ]))
+        .unwrap();
+
+        assert_eq!(parsed.positional, args(&["download", "abc"]));
+        assert_eq!(parsed.server, Some("http://localhost".to_string()));
+        assert_eq!(
+            Args::parse(args(&["--server", "https://smmdb.example/ ", "search"]))
+                .unwrap()
+                .server,
+            Some("https://smmdb.example".to_string())
+        );
+        assert_eq!(parsed.slot, Some("3".to_string()));
+        assert_eq!(parsed.get_save().unwrap(), PathBuf::from("save"));
+        assert_eq!(parsed.get_positional(1, "smmdb-id").unwrap(), "abc");
+        assert!(parsed.get_positional(2, "slot").is_err());
+    }
+
+    #[test]
+    fn parses_help_flag_as_command() {
+        let parsed = Args::parse(args(&["-h"])).unwrap();
+
+        assert_eq!(parsed.positional, args(&["help"]));
+    }
+
+    #[test]
+    fn rejects_invalid_options() {
+        assert!(Args::parse(args(&["list", "--save"])).is_err());
+        assert!(Args::parse(args(&["list", "--unknown", "value"])).is_err());
+        assert!(Args::parse(args(&["--server", "localhost", "search"])).is_err());
+        assert!(Args::parse(args(&["--server", "ftp://localhost", "search"])).is_err());
+        assert!(Args::parse(args(&["list"])).unwrap().get_save().is_err());
+    }
+
+    #[test]
+    fn parses_slots_starting_at_one() {
+        assert_eq!(parse_slot("1").unwrap(), 0);
+        assert_eq!(parse_slot("60").unwrap(), 59);
+        assert!(parse_slot("0").is_err());
+        assert!(parse_slot("61").is_err());
+        assert!(parse_slot("-1").is_err());
+        assert!(parse_slot("first").is_err());
+    }
+}
@@ -1,4 +1,11 @@
-use crate::{font::*, icon, styles::*, AppState, Message};
+use crate::{
+    download_queue::{DownloadJob, JobStatus},
+    font::*,
+    icon,
+    pages::{ConflictResolution, PendingChange, SaveSide, Transfer, TransferMode},
+    styles::*,
+    AppState, Message,
+};
 
 use iced::{
     button, container, image, Align, Button, Color, Column, Container, Element, Image, Length,
@@ -13,28 +20,77 @@ pub struct CoursePanel {
     delete_state: button::State,
     delete_confirm_state: button::State,
     delete_cancel_state: button::State,
+    upload_state: button::State,
+    stash_state: button::State,
+    export_state: button::State,
+    import_state: button::State,
+    download_cancel_state: button::State,
+    download_retry_state: button::State,
+    copy_state: button::State,
+    move_state: button::State,
+    conflict_cancel_state: button::State,
+    conflict_next_state: button::State,
+    conflict_replace_state: button::State,
     course: Option<SavedCourse>,
+    smmdb_id: Option<String>,
+    pending_change: Option<PendingChange>,
 }
 
 impl CoursePanel {
-    pub fn new(course: Option<SavedCourse>) -> CoursePanel {
+    pub fn new(
+        course: Option<SavedCourse>,
+        smmdb_id: Option<String>,
+        pending_change: Option<PendingChange>,
+    ) -> CoursePanel {
         CoursePanel {
             panel_state: button::State::new(),
             add_state: button::State::new(),
             delete_state: button::State::new(),
             delete_confirm_state: button::State::new(),
             delete_cancel_state: button::State::new(),
+            upload_state: button::State::new(),
+            stash_state: button::State::new(),
+            export_state: button::State::new(),
+            import_state: button::State::new(),
+            download_cancel_state: button::State::new(),
+            download_retry_state: button::State::new(),
+            copy_state: button::State::new(),
+            move_state: button::State::new(),
+            conflict_cancel_state: button::State::new(),
+            conflict_next_state: button::State::new(),
+            conflict_replace_state: button::State::new(),
             course,
+            smmdb_id,
+            pending_change,
         }
     }
 
-    pub fn view(&mut self, state: &AppState, index: usize) -> Element<Message> {
+    pub fn view(
+        &mut self,
+        state: &AppState,
+        index: usize,
+        has_apikey: bool,
+        download: Option<&DownloadJob>,
+        side: SaveSide,
+        has_other_save: bool,
+        is_staged: bool,
+    ) -> Element<Message> {
+        let is_primary = side == SaveSide::Primary;
         let content: Element<Message> = if let Some(course) = &self.course {
             let course = course.get_course();
             let course_header = course.get_course().get_header();
 
+            let mut title = Row::new()
+                .align_items(Align::Center)
+                .push(Text::new(format!("{}", course_header.get_title())).size(24));
+            if let Some(pending_change) = self.pending_change {
+                title = title
+                    .push(Space::with_width(Length::Units(10)))
+                    .push(view_pending_change(pending_change));
+            }
+
             let mut content = Column::new()
-                .push(Text::new(format!("{}", course_header.get_title())).size(24))
+                .push(title)
                 .push(Space::with_height(Length::Units(10)))
                 .push(
                     Row::new()
@@ -54,8 +110,85 @@ impl CoursePanel {
                 )
                 .width(Length::Shrink);
 
+            if let Some(smmdb_id) = &self.smmdb_id {
+                content = content
+                    .push(Space::with_height(Length::Units(10)))
+                    .push(Text::new(format!("SMMDB ID: {}", smmdb_id)).size(14));
+            }
+
+            if let AppState::Uploading {
+                save_index,
+                progress,
+                ..
+            } = state
+            {
+                if *save_index == index && is_primary {
+                    content = content
+                        .push(Space::with_height(Length::Units(10)))
+                        .push(Text::new("Uploading to SMMDB...").size(14))
+                        .push(ProgressBar::new(0.0..=100.0, *progress));
+                }
+            }
+
+            if let AppState::TransferConflict(transfer, to) = state {
+                if transfer.from != side && *to == index {
+                    content = content
+                        .push(Space::with_height(Length::Units(18)))
+                        .push(
+                            Text::new("This slot is not empty. Where should the course go?")
+                                .size(16)
+                                .font(HELVETICA_BOLD),
+                        )
+                        .push(
+                            Row::new()
+                                .push(Space::with_width(Length::Fill))
+                                .push(
+                                    Button::new(
+                                        &mut self.conflict_cancel_state,
+                                        Text::new("Cancel"),
+                                    )
+                                    .padding(BUTTON_PADDING)
+                                    .style(DefaultButtonStyle)
+                                    .on_press(Message::ResetState),
+                                )
+                                .push(Space::with_width(Length::Units(16)))
+                                .push(
+                                    Button::new(
+                                        &mut self.conflict_next_state,
+                                        Text::new("Next empty slot"),
+                                    )
+                                    .padding(BUTTON_PADDING)
+                                    .style(DefaultButtonStyle)
+                                    .on_press(
+                                        Message::ResolveTransferConflict(
+                                            *transfer,
+                                            index,
+                                            ConflictResolution::NextEmptySlot,
+                                        ),
+                                    ),
+                                )
+                                .push(Space::with_width(Length::Units(16)))
+                                .push(
+                                    Button::new(
+                                        &mut self.conflict_replace_state,
+                                        Text::new("Replace"),
+                                    )
+                                    .padding(BUTTON_PADDING)
+                                    .style(DeleteButtonStyle)
+                                    .on_press(
+                                        Message::ResolveTransferConflict(
+                                            *transfer,
+                                            index,
+                                            ConflictResolution::Replace,
+                                        ),
+                                    ),
+                                ),
+                        );
+                }
+            }
+
             content = if let AppState::DeleteSelect(idx) = state {
-                if *idx == index {
+                if *idx == index && is_primary {
                     content
                         .push(Space::with_height(Length::Units(18)))
                         .push(
@@ -95,20 +228,71 @@ impl CoursePanel {
 
             content.into()
         } else {
-            let empty_text = Text::new("empty").size(18).width(Length::Shrink);
-            let content: Element<Message> = if let AppState::Downloading {
-                save_index,
-                progress,
-                ..
-            } = state
-            {
-                if *save_index == index {
-                    ProgressBar::new(0.0..=100.0, *progress).into()
-                } else {
-                    empty_text.into()
+            let content: Element<Message> = match download {
+                Some(job) => {
+                    let cancel_button = Button::new(
+                        &mut self.download_cancel_state,
+                        Text::new(match job.get_status() {
+                            JobStatus::Failed(_) => "Dismiss",
+                            _ => "Cancel",
+                        }),
+                    )
+                    .style(DeleteButtonStyle)
+                    .on_press(Message::CancelDownload(job.get_id()));
+                    match job.get_status() {
+                        JobStatus::Failed(err) => Column::new()
+                            .push(
+                                Text::new(format!("Download failed: {}", err))
+                                    .size(15)
+                                    .color(TEXT_DANGER_COLOR),
+                            )
+                            .push(Space::with_height(Length::Units(8)))
+                            .push(
+                                Row::new()
+                                    .push(Space::with_width(Length::Fill))
+                                    .push(
+                                        Button::new(
+                                            &mut self.download_retry_state,
+                                            Text::new("Retry"),
+                                        )
+                                        .style(DefaultButtonStyle)
+                                        .on_press(Message::RetryDownload(job.get_id())),
+                                    )
+                                    .push(Space::with_width(Length::Units(8)))
+                                    .push(cancel_button),
+                            )
+                            .into(),
+                        status => {
+                            let progress: Element<Message> = match status {
+                                JobStatus::Running(Some(progress)) => {
+                                    ProgressBar::new(0.0..=100.0, *progress).into()
+                                }
+                                JobStatus::Running(None) => Text::new("Downloading...")
+                                    .size(18)
+                                    .width(Length::Fill)
+                                    .into(),
+                                _ => Text::new("Download queued")
+                                    .size(18)
+                                    .width(Length::Fill)
+                                    .into(),
+                            };
+                            Row::new()
+                                .align_items(Align::Center)
+                                .push(progress)
+                                .push(Space::with_width(Length::Units(10)))
+                                .push(cancel_button)
+                                .into()
+                        }
+                    }
                 }
-            } else {
-                empty_text.into()
+                None => match self.pending_change {
+                    Some(pending_change) => Column::new()
+                        .align_items(Align::Center)
+                        .push(Text::new("empty").size(18))
+                        .push(view_pending_change(pending_change))
+                        .into(),
+                    None => Text::new("empty").size(18).width(Length::Shrink).into(),
+                },
             };
 
             Container::new(content)
@@ -120,12 +304,31 @@ impl CoursePanel {
         };
 
         let panel: Element<Message> = match state {
-            AppState::SwapSelect(idx) => Button::new(&mut self.panel_state, content)
-                .style(CoursePanelButtonStyle(state.clone(), index))
-                .padding(12)
-                .width(Length::Fill)
-                .on_press(Message::SwapCourse(*idx, index))
-                .into(),
+            // Slots reserved by a download cannot be swapped into.
+            AppState::SwapSelect(idx) if is_primary && download.is_none() => {
+                Button::new(&mut self.panel_state, content)
+                    .style(CoursePanelButtonStyle(state.clone(), index))
+                    .padding(12)
+                    .width(Length::Fill)
+                    .on_press(Message::SwapCourse(*idx, index))
+                    .into()
+            }
+            AppState::ReplaceSelect(smmdb_id) if is_primary && self.course.is_some() => {
+                Button::new(&mut self.panel_state, content)
+                    .style(CoursePanelButtonStyle(state.clone(), index))
+                    .padding(12)
+                    .width(Length::Fill)
+                    .on_press(Message::ReplaceSmmdbCourse(index, smmdb_id.clone()))
+                    .into()
+            }
+            AppState::TransferSelect(transfer) if transfer.from != side && download.is_none() => {
+                Button::new(&mut self.panel_state, content)
+                    .style(CoursePanelButtonStyle(state.clone(), index))
+                    .padding(12)
+                    .width(Length::Fill)
+                    .on_press(Message::TransferCourse(*transfer, index))
+                    .into()
+            }
             _ => Container::new(content)
                 .style(CoursePanelStyle(state.clone(), index))
                 .padding(12)
@@ -134,7 +337,17 @@ impl CoursePanel {
         };
 
         let mut actions = Column::new();
-        if self.course.is_some() {
+        if !is_primary {
+            if self.course.is_some() {
+                actions = actions.push(view_transfer_buttons(
+                    &mut self.copy_state,
+                    &mut self.move_state,
+                    state,
+                    index,
+                    side,
+                ));
+            }
+        } else if self.course.is_some() {
             let mut swap_button = Button::new(
                 &mut self.add_state,
                 icon::SORT
@@ -151,7 +364,7 @@ impl CoursePanel {
                         swap_button.on_press(Message::InitSwapCourse(index))
                     }
                 }
-                AppState::Loading | AppState::Downloading { .. } => swap_button,
+                AppState::Loading | AppState::Uploading { .. } => swap_button,
                 _ => swap_button.on_press(Message::InitSwapCourse(index)),
             };
 
@@ -171,14 +384,64 @@ impl CoursePanel {
                         delete_button.on_press(Message::InitDeleteCourse(index))
                     }
                 }
-                AppState::Loading | AppState::Downloading { .. } => delete_button,
+                AppState::Loading | AppState::Uploading { .. } => delete_button,
                 _ => delete_button.on_press(Message::InitDeleteCourse(index)),
             };
 
+            let mut stash_button = Button::new(&mut self.stash_state, Text::new("Stash").size(14))
+                .style(UploadButtonStyle);
+            let mut export_button =
+                Button::new(&mut self.export_state, Text::new("Export").size(14))
+                    .style(UploadButtonStyle);
+            match state {
+                AppState::Loading | AppState::Uploading { .. } => {}
+                _ => {
+                    if !is_staged {
+                        stash_button = stash_button.on_press(Message::StashCourse(index));
+                    }
+                    export_button = export_button.on_press(Message::ExportCourse(index));
+                }
+            }
+
             actions = actions
                 .push(swap_button)
                 .push(Space::with_height(Length::Units(10)))
-                .push(delete_button);
+                .push(delete_button)
+                .push(Space::with_height(Length::Units(10)))
+                .push(stash_button)
+                .push(Space::with_height(Length::Units(10)))
+                .push(export_button);
+
+            if has_apikey {
+                let mut upload_button = Button::new(
+                    &mut self.upload_state,
+                    icon::UPLOAD
+                        .clone()
+                        .width(Length::Units(24))
+                        .height(Length::Units(24)),
+                )
+                .style(UploadButtonStyle);
+                upload_button = match state {
+                    AppState::Loading | AppState::Uploading { .. } => upload_button,
+                    _ => upload_button.on_press(Message::UploadCourse(index)),
+                };
+
+                actions = actions
+                    .push(Space::with_height(Length::Units(10)))
+                    .push(upload_button);
+            }
+
+            if has_other_save {
+                actions = actions.push(Space::with_height(Length::Units(10))).push(
+                    view_transfer_buttons(
+                        &mut self.copy_state,
+                        &mut self.move_state,
+                        state,
+                        index,
+                        side,
+                    ),
+                );
+            }
         } else {
             let mut download_button = Button::new(
                 &mut self.add_state,
@@ -189,6 +452,7 @@ impl CoursePanel {
             )
             .style(DownloadButtonStyle(state.clone(), index));
             download_button = match state {
+                _ if download.is_some() => download_button,
                 AppState::DownloadSelect(idx) => {
                     if *idx == index {
                         download_button.on_press(Message::ResetState)
@@ -196,11 +460,25 @@ impl CoursePanel {
                         download_button.on_press(Message::InitDownloadCourse(index))
                     }
                 }
-                AppState::Loading | AppState::Downloading { .. } => download_button,
+                AppState::Loading | AppState::Uploading { .. } => download_button,
                 _ => download_button.on_press(Message::InitDownloadCourse(index)),
             };
 
-            actions = actions.push(download_button);
+            let mut import_button =
+                Button::new(&mut self.import_state, Text::new("Import").size(14))
+                    .style(UploadButtonStyle);
+            match state {
+                _ if download.is_some() => {}
+                AppState::Loading | AppState::Uploading { .. } => {}
+                _ => {
+                    import_button = import_button.on_press(Message::ImportCourse(index));
+                }
+            }
+
+            actions = actions
+                .push(download_button)
+                .push(Space::with_height(Length::Units(10)))
+                .push(import_button);
         }
 
         Row::new()
@@ -212,6 +490,58 @@ impl CoursePanel {
     }
 }
 
+fn view_transfer_buttons<'a>(
+    copy_state: &'a mut button::State,
+    move_state: &'a mut button::State,
+    state: &AppState,
+    index: usize,
+    side: SaveSide,
+) -> Column<'a, Message> {
+    let arrow = match side {
+        SaveSide::Primary => "→",
+        SaveSide::Secondary => "←",
+    };
+    let mut copy_button = Button::new(copy_state, Text::new(format!("Copy {}", arrow)).size(14))
+        .style(UploadButtonStyle);
+    let mut move_button = Button::new(move_state, Text::new(format!("Move {}", arrow)).size(14))
+        .style(UploadButtonStyle);
+    let on_press = |mode| {
+        let transfer = Transfer {
+            from: side,
+            index,
+            mode,
+        };
+        match state {
+            AppState::TransferSelect(selected) if *selected == transfer => Message::ResetState,
+            _ => Message::InitTransferCourse(transfer),
+        }
+    };
+    match state {
+        AppState::Loading | AppState::Uploading { .. } => {}
+        _ => {
+            copy_button = copy_button.on_press(on_press(TransferMode::Copy));
+            move_button = move_button.on_press(on_press(TransferMode::Move));
+        }
+    }
+    Column::new()
+        .push(copy_button)
+        .push(Space::with_height(Length::Units(10)))
+        .push(move_button)
+}
+
+fn view_pending_change(pending_change: PendingChange) -> Text {
+    let (label, color) = match pending_change {
+        PendingChange::Added => ("added".to_string(), TEXT_HIGHLIGHT_COLOR),
+        PendingChange::Replaced => ("replaced".to_string(), TEXT_HIGHLIGHT_COLOR),
+        PendingChange::Moved(from) => (
+            format!("moved from slot {}", from + 1),
+            TEXT_HIGHLIGHT_COLOR,
+        ),
+        PendingChange::Removed => ("removed".to_string(), TEXT_DANGER_COLOR),
+    };
+    Text::new(label).size(14).font(HELVETICA_BOLD).color(color)
+}
+
 struct CoursePanelButtonStyle(AppState, usize);
 
 impl button::StyleSheet for CoursePanelButtonStyle {
@@ -226,6 +556,9 @@ impl button::StyleSheet for CoursePanelButtonStyle {
                         Some(PANEL_ACTIVE)
                     }
                 }
+                AppState::ReplaceSelect(_) | AppState::TransferSelect(_) => {
+                    Some(PANEL_SELECT_ACTIVE)
+                }
                 _ => Some(PANEL_ACTIVE),
             },
             border_radius: 8,
@@ -252,6 +585,9 @@ impl button::StyleSheet for CoursePanelButtonStyle {
                         Some(PANEL_ACTIVE)
                     }
                 }
+                AppState::ReplaceSelect(_) | AppState::TransferSelect(_) => {
+                    Some(PANEL_SELECT_HOVER)
+                }
                 _ => Some(PANEL_ACTIVE),
             },
             border_radius: 8,
@@ -398,3 +734,33 @@ impl button::StyleSheet for DeleteButtonStyle {
         }
     }
 }
+
+struct UploadButtonStyle;
+
+impl button::StyleSheet for UploadButtonStyle {
+    fn active(&self) -> button::Style {
+        button::Style {
+            background: Some(BUTTON_ACTIVE),
+            border_radius: 4,
+            border_width: 0,
+            ..button::Style::default()
+        }
+    }
+
+    fn hovered(&self) -> button::Style {
+        button::Style {
+            text_color: Color::WHITE,
+            background: Some(BUTTON_HOVER),
+            border_radius: 4,
+            ..button::Style::default()
+        }
+    }
+
+    fn disabled(&self) -> button::Style {
+        button::Style {
+            background: Some(BUTTON_DISABLED),
+            border_radius: 4,
+            ..button::Style::default()
+        }
+    }
+}
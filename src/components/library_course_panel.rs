@@ -0,0 +1,231 @@
+use crate::{
+    font::*, library::LibraryEntry, pages::Slot, smmdb::format_timestamp, styles::*, AppState,
+    Message,
+};
+
+use iced::{
+    button, container, pick_list, text_input, Align, Button, Column, Container, Element, Image,
+    Length, PickList, Row, Space, Text, TextInput,
+};
+use iced_native::widget::image::Handle;
+
+#[derive(Clone, Debug, Default)]
+pub struct LibraryCoursePanel {
+    tag_input: String,
+    tag_input_state: text_input::State,
+    add_tag_state: button::State,
+    tag_states: Vec<button::State>,
+    selected_slot: Option<Slot>,
+    slot_state: pick_list::State<Slot>,
+    move_state: button::State,
+    delete_state: button::State,
+    delete_confirm_state: button::State,
+    delete_cancel_state: button::State,
+}
+
+impl LibraryCoursePanel {
+    pub fn new() -> LibraryCoursePanel {
+        LibraryCoursePanel::default()
+    }
+
+    pub fn set_tag_input(&mut self, tag_input: String) {
+        self.tag_input = tag_input;
+    }
+
+    pub fn take_tag_input(&mut self) -> String {
+        std::mem::take(&mut self.tag_input)
+    }
+
+    pub fn select_slot(&mut self, slot: Slot) {
+        self.selected_slot = Some(slot);
+    }
+
+    pub fn has_focus(&self) -> bool {
+        self.tag_input_state.is_focused()
+    }
+
+    /// Shows a stashed course. Occupied slots are offered as well, their course gets stashed
+    /// in exchange.
+    pub fn view(
+        &mut self,
+        state: &AppState,
+        entry: &LibraryEntry,
+        slots: &[Slot],
+        is_staged: bool,
+    ) -> Element<Message> {
+        let id = entry.get_id().clone();
+        let is_busy = match state {
+            AppState::Loading | AppState::Uploading { .. } => true,
+            _ => false,
+        };
+        if let Some(slot) = self.selected_slot {
+            if !slots.contains(&slot) {
+                self.selected_slot = None;
+            }
+        }
+
+        let thumbnail: Element<Message> = match entry.get_thumbnail() {
+            Some(thumbnail) => Image::new(Handle::from_memory(thumbnail.clone()))
+                .width(Length::Units(160))
+                .height(Length::Units(90))
+                .into(),
+            None => Space::new(Length::Units(160), Length::Units(90)).into(),
+        };
+
+        let mut meta = Column::new().spacing(2);
+        if let Some(game_style) = entry.get_game_style() {
+            meta = meta.push(Text::new(game_style).size(14));
+        }
+        if let Some(theme) = entry.get_theme() {
+            meta = meta.push(Text::new(theme).size(14));
+        }
+        meta = meta.push(Text::new(format!("Time limit: {} seconds", entry.get_time())).size(14));
+        if let Some(clear_condition) = entry.get_clear_condition() {
+            meta = meta.push(Text::new(clear_condition).size(14));
+        }
+        if let Some(smmdb_id) = entry.get_smmdb_id() {
+            meta = meta.push(Text::new(format!("SMMDB ID: {}", smmdb_id)).size(14));
+        }
+        meta = meta.push(
+            Text::new(format!(
+                "Stashed {}",
+                format_timestamp(entry.get_added_at())
+            ))
+            .size(14)
+            .color(TEXT_HELP_COLOR),
+        );
+
+        self.tag_states
+            .resize_with(entry.get_tags().len(), button::State::default);
+        let mut tags = Row::new().spacing(4).align_items(Align::Center);
+        for (tag, tag_state) in entry.get_tags().iter().zip(self.tag_states.iter_mut()) {
+            tags = tags.push(
+                Button::new(tag_state, Text::new(format!("{} ×", tag)).size(14))
+                    .style(DefaultButtonStyle)
+                    .on_press(Message::RemoveLibraryTag(id.clone(), tag.clone())),
+            );
+        }
+        let tag_id = id.clone();
+        let mut add_tag_button =
+            Button::new(&mut self.add_tag_state, Text::new("Add tag")).style(DefaultButtonStyle);
+        if !self.tag_input.trim().is_empty() {
+            add_tag_button = add_tag_button.on_press(Message::AddLibraryTags(id.clone()));
+        }
+        let tag_input = Row::new()
+            .align_items(Align::Center)
+            .push(
+                TextInput::new(
+                    &mut self.tag_input_state,
+                    "Tags, separated by commas",
+                    &self.tag_input,
+                    move |tag_input| Message::LibraryTagInputChanged(tag_id.clone(), tag_input),
+                )
+                .style(DefaultTextInputStyle)
+                .padding(4)
+                .on_submit(Message::AddLibraryTags(id.clone())),
+            )
+            .push(Space::with_width(Length::Units(8)))
+            .push(add_tag_button);
+
+        let actions: Element<Message> = match state {
+            AppState::LibraryDeleteSelect(delete_id) if *delete_id == id => Column::new()
+                .push(
+                    Text::new("Do you really want to delete this course from your library?")
+                        .size(16)
+                        .font(HELVETICA_BOLD),
+                )
+                .push(
+                    Row::new()
+                        .push(Space::with_width(Length::Fill))
+                        .push(
+                            Button::new(
+                                &mut self.delete_cancel_state,
+                                Text::new("No").size(20).font(HELVETICA_BOLD),
+                            )
+                            .padding(BUTTON_PADDING)
+                            .style(DefaultButtonStyle)
+                            .on_press(Message::ResetState),
+                        )
+                        .push(Space::with_width(Length::Units(16)))
+                        .push(
+                            Button::new(
+                                &mut self.delete_confirm_state,
+                                Text::new("Yes").size(20).font(HELVETICA_BOLD),
+                            )
+                            .padding(BUTTON_PADDING)
+                            .style(DefaultButtonDangerStyle)
+                            .on_press(Message::DeleteLibraryCourse(id.clone())),
+                        ),
+                )
+                .into(),
+            _ => {
+                let slot_id = id.clone();
+                let mut move_button = Button::new(&mut self.move_state, Text::new("Move to save"))
+                    .style(DefaultButtonStyle);
+                let mut delete_button = Button::new(&mut self.delete_state, Text::new("Delete"))
+                    .style(DefaultButtonDangerStyle);
+                if !is_busy {
+                    if let (Some(slot), false) = (self.selected_slot, is_staged) {
+                        move_button =
+                            move_button.on_press(Message::MoveFromLibrary(id.clone(), slot.0));
+                    }
+                    delete_button =
+                        delete_button.on_press(Message::InitDeleteLibraryCourse(id.clone()));
+                }
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(
+                        PickList::new(
+                            &mut self.slot_state,
+                            slots.to_vec(),
+                            self.selected_slot,
+                            move |slot| Message::SelectLibrarySlot(slot_id.clone(), slot),
+                        )
+                        .style(DefaultPickListStyle)
+                        .padding(4),
+                    )
+                    .push(Space::with_width(Length::Units(8)))
+                    .push(move_button)
+                    .push(Space::with_width(Length::Fill))
+                    .push(delete_button)
+                    .into()
+            }
+        };
+
+        let content = Column::new()
+            .spacing(8)
+            .push(Text::new(entry.get_title()).size(20))
+            .push(
+                Row::new()
+                    .push(thumbnail)
+                    .push(Space::with_width(Length::Units(10)))
+                    .push(
+                        Column::new()
+                            .push(Text::new(entry.get_description()).size(15))
+                            .push(Space::with_height(Length::Units(8)))
+                            .push(meta),
+                    ),
+            )
+            .push(tags)
+            .push(tag_input)
+            .push(actions);
+
+        Container::new(content)
+            .style(LibraryCoursePanelStyle)
+            .padding(12)
+            .width(Length::Fill)
+            .into()
+    }
+}
+
+struct LibraryCoursePanelStyle;
+
+impl container::StyleSheet for LibraryCoursePanelStyle {
+    fn style(&self) -> container::Style {
+        container::Style {
+            background: Some(PANEL_ACTIVE),
+            border_radius: 8,
+            ..container::Style::default()
+        }
+    }
+}
@@ -1,7 +1,9 @@
 mod course_panel;
+mod library_course_panel;
 mod save_button;
 mod smmdb_course_panel;
 
 pub use course_panel::*;
+pub use library_course_panel::*;
 pub use save_button::*;
 pub use smmdb_course_panel::*;
@@ -1,7 +1,6 @@
-use crate::{styles::*, AppState, EmuSave, EmuType, Message};
+use crate::{styles::*, AppState, EmuSave, Message};
 
 use iced::{button, Button, Element, Text};
-use std::path::PathBuf;
 
 #[derive(Clone, Debug)]
 pub struct SaveButton {
@@ -11,11 +10,11 @@ pub struct SaveButton {
 }
 
 impl SaveButton {
-    pub fn new(display_name: String, location: PathBuf, emu_type: EmuType) -> SaveButton {
+    pub fn new(save: EmuSave) -> SaveButton {
         SaveButton {
-            display_name: display_name.clone(),
+            display_name: save.get_display_name().clone(),
             state: button::State::new(),
-            save: EmuSave::new(display_name, location, emu_type),
+            save,
         }
     }
 
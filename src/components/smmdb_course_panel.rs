@@ -1,16 +1,21 @@
-use crate::{icon, smmdb::Course2Response, smmdb::Difficulty, styles::*, AppState, Message};
+use crate::{
+    font::*, icon, smmdb::Course2Response, smmdb::Difficulty, styles::*, AppState, Message,
+};
 
 use iced::{
-    button, container, Align, Background, Button, Color, Column, Container, Element, Image, Length,
-    Row, Space, Text,
+    button, container, pick_list, Align, Background, Button, Color, Column, Container, Element,
+    Image, Length, PickList, Row, Space, Text,
 };
 use iced_native::widget::image::Handle;
 
 #[derive(Debug)]
 pub struct SmmdbCoursePanel {
     panel_state: button::State,
+    details_state: button::State,
+    stash_state: button::State,
     upvote_state: button::State,
     downvote_state: button::State,
+    owner_actions: OwnerActions,
     course: Course2Response,
     thumbnail: Option<Vec<u8>>,
 }
@@ -19,8 +24,11 @@ impl SmmdbCoursePanel {
     pub fn new(course: Course2Response) -> SmmdbCoursePanel {
         SmmdbCoursePanel {
             panel_state: button::State::new(),
+            details_state: button::State::new(),
+            stash_state: button::State::new(),
             upvote_state: button::State::new(),
             downvote_state: button::State::new(),
+            owner_actions: OwnerActions::new(),
             course,
             thumbnail: None,
         }
@@ -30,7 +38,12 @@ impl SmmdbCoursePanel {
         self.course.set_own_vote(value);
     }
 
-    pub fn view(&mut self, state: &AppState) -> Element<Message> {
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.course.set_difficulty(difficulty);
+    }
+
+    pub fn view(&mut self, state: &AppState, account_id: Option<&String>) -> Element<Message> {
+        let is_own = account_id == Some(self.course.get_owner());
         let course = self.course.get_course();
         let course_header = course.get_header();
 
@@ -119,6 +132,16 @@ impl SmmdbCoursePanel {
             .push(Space::with_height(Length::Units(16)))
             .push(downvote);
 
+        let mut stash_button =
+            Button::new(&mut self.stash_state, Text::new("Stash")).style(DefaultButtonStyle);
+        match state {
+            AppState::Loading | AppState::Uploading { .. } => {}
+            _ => {
+                stash_button =
+                    stash_button.on_press(Message::StashSmmdbCourse(self.course.get_id().clone()));
+            }
+        }
+
         let inner_content = Row::new()
             .push(voting_content)
             .push(Space::with_width(Length::Units(10)))
@@ -132,11 +155,32 @@ impl SmmdbCoursePanel {
             )
             .align_items(Align::Center);
 
-        let content = Column::new()
-            .push(Text::new(format!("{}", course_header.get_title())).size(24))
+        let mut content = Column::new()
+            .push(
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(
+                        Text::new(format!("{}", course_header.get_title()))
+                            .size(24)
+                            .width(Length::Fill),
+                    )
+                    .push(stash_button)
+                    .push(Space::with_width(Length::Units(8)))
+                    .push(
+                        Button::new(&mut self.details_state, Text::new("Details"))
+                            .style(DefaultButtonStyle)
+                            .on_press(Message::OpenCourseDetail(self.course.get_id().clone())),
+                    ),
+            )
             .push(Space::with_height(Length::Units(10)))
             .push(inner_content);
 
+        if is_own {
+            content = content
+                .push(Space::with_height(Length::Units(10)))
+                .push(self.owner_actions.view(state, &self.course));
+        }
+
         match state {
             AppState::DownloadSelect(index) => Button::new(&mut self.panel_state, content)
                 .style(SmmdbCoursePanelButtonStyle(state.clone()))
@@ -159,11 +203,141 @@ impl SmmdbCoursePanel {
         self.course.get_id()
     }
 
+    pub fn get_course(&self) -> &Course2Response {
+        &self.course
+    }
+
+    pub fn get_thumbnail(&self) -> Option<&Vec<u8>> {
+        self.thumbnail.as_ref()
+    }
+
+    pub fn has_thumbnail(&self) -> bool {
+        self.thumbnail.is_some()
+    }
+
     pub fn set_thumbnail(&mut self, thumbnail: Vec<u8>) {
         self.thumbnail = Some(thumbnail);
     }
 }
 
+#[derive(Debug)]
+struct OwnerActions {
+    difficulty_state: pick_list::State<Difficulty>,
+    replace_state: button::State,
+    delete_state: button::State,
+    delete_confirm_state: button::State,
+    delete_cancel_state: button::State,
+}
+
+impl OwnerActions {
+    fn new() -> OwnerActions {
+        OwnerActions {
+            difficulty_state: pick_list::State::default(),
+            replace_state: button::State::new(),
+            delete_state: button::State::new(),
+            delete_confirm_state: button::State::new(),
+            delete_cancel_state: button::State::new(),
+        }
+    }
+
+    fn view(&mut self, state: &AppState, course: &Course2Response) -> Element<Message> {
+        let id = course.get_id().clone();
+        let is_busy = match state {
+            AppState::Loading | AppState::Uploading { .. } => true,
+            _ => false,
+        };
+
+        if let AppState::SmmdbDeleteSelect(smmdb_id) = state {
+            if *smmdb_id == id {
+                return Column::new()
+                    .push(
+                        Text::new("Do you really want to delete this course from SMMDB?")
+                            .size(16)
+                            .font(HELVETICA_BOLD),
+                    )
+                    .push(
+                        Row::new()
+                            .push(Space::with_width(Length::Fill))
+                            .push(
+                                Button::new(
+                                    &mut self.delete_cancel_state,
+                                    Text::new("No").size(20).font(HELVETICA_BOLD),
+                                )
+                                .padding(BUTTON_PADDING)
+                                .style(DefaultButtonStyle)
+                                .on_press(Message::ResetState),
+                            )
+                            .push(Space::with_width(Length::Units(16)))
+                            .push(
+                                Button::new(
+                                    &mut self.delete_confirm_state,
+                                    Text::new("Yes").size(20).font(HELVETICA_BOLD),
+                                )
+                                .padding(BUTTON_PADDING)
+                                .style(DefaultButtonDangerStyle)
+                                .on_press(Message::DeleteSmmdbCourse(id)),
+                            ),
+                    )
+                    .into();
+            }
+        }
+
+        let selected_id = id.clone();
+        let difficulty_pick_list = PickList::new(
+            &mut self.difficulty_state,
+            &Difficulty::TAGS[..],
+            course.get_difficulty().cloned(),
+            move |difficulty| Message::ChangeSmmdbCourseDifficulty(selected_id.clone(), difficulty),
+        )
+        .style(DefaultPickListStyle)
+        .padding(4);
+
+        let is_replacing = match state {
+            AppState::ReplaceSelect(smmdb_id) => *smmdb_id == id,
+            _ => false,
+        };
+        let mut replace_button = Button::new(
+            &mut self.replace_state,
+            Text::new(if is_replacing {
+                "Cancel replace"
+            } else {
+                "Replace from save"
+            }),
+        )
+        .style(DefaultButtonStyle);
+        let mut delete_button = Button::new(&mut self.delete_state, Text::new("Delete"))
+            .style(DefaultButtonDangerStyle);
+        if !is_busy {
+            replace_button = replace_button.on_press(if is_replacing {
+                Message::ResetState
+            } else {
+                Message::InitReplaceSmmdbCourse(id.clone())
+            });
+            delete_button = delete_button.on_press(Message::InitDeleteSmmdbCourse(id));
+        }
+
+        let mut actions = Column::new().push(
+            Row::new()
+                .align_items(Align::Center)
+                .push(Text::new("Your course:").size(15))
+                .push(Space::with_width(Length::Units(8)))
+                .push(difficulty_pick_list)
+                .push(Space::with_width(Length::Fill))
+                .push(replace_button)
+                .push(Space::with_width(Length::Units(8)))
+                .push(delete_button),
+        );
+        if is_replacing {
+            actions = actions.push(
+                Text::new("Select a course from your save to replace this course.")
+                    .size(15)
+                    .color(TEXT_HELP_COLOR),
+            );
+        }
+        actions.into()
+    }
+}
+
 struct SmmdbCoursePanelButtonStyle(AppState);
 
 impl button::StyleSheet for SmmdbCoursePanelButtonStyle {
@@ -0,0 +1,168 @@
+use anyhow::{anyhow, bail, Result};
+use smmdb_lib::Course2;
+use std::{
+    convert::TryInto,
+    fs::{read, write},
+    path::{Path, PathBuf},
+};
+
+pub fn get_encrypted_course_data(course: &Course2) -> Vec<u8> {
+    let mut data = course.get_course_data().to_vec();
+    Course2::encrypt(&mut data);
+    data
+}
+
+pub fn get_encrypted_thumbnail(course: &Course2) -> Option<Vec<u8>> {
+    course
+        .get_course_thumb()
+        .map(|thumb| thumb.get_encrypted().to_vec())
+}
+
+pub fn read_course(data: Vec<u8>, thumb: Option<Vec<u8>>) -> Result<Course2> {
+    Course2::from_switch_files(data, thumb, true).map_err(|err| -> anyhow::Error { err.into() })
+}
+
+/// Writes a course data file and a thumbnail file next to it, like other SMM2 tools do.
+pub fn export_course(course: &Course2, path: &Path) -> Result<PathBuf> {
+    write_course_files(
+        &get_encrypted_course_data(course),
+        get_encrypted_thumbnail(course).as_deref(),
+        path,
+    )
+}
+
+fn write_course_files(data: &[u8], thumb: Option<&[u8]>, path: &Path) -> Result<PathBuf> {
+    let data_path = path.with_extension("bcd");
+    write(&data_path, data)?;
+    if let Some(thumb) = thumb {
+        write(data_path.with_extension("btl"), thumb)?;
+    }
+    Ok(data_path)
+}
+
+/// Course data files (`.bcd`) are read together with the thumbnail next to them, either
+/// exported by this client or taken from a save folder. Any other file is expected to be
+/// a course as downloaded from SMMDB.
+pub fn import_course(path: &Path) -> Result<Course2> {
+    let course: Course2 = if path
+        .extension()
+        .map_or(false, |extension| extension == "bcd")
+    {
+        let (data, thumb) = read_course_files(path)?;
+        read_course(data, thumb)?
+    } else {
+        read(path)?
+            .try_into()
+            .map_err(|err| anyhow!("The file is not a valid course.\n{:?}", err))?
+    };
+    if course.get_course_thumb().is_none() {
+        bail!("No thumbnail was found for the course.");
+    }
+    Ok(course)
+}
+
+fn read_course_files(data_path: &Path) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+    let data = read(data_path)?;
+    let thumb = get_thumbnail_paths(data_path)
+        .into_iter()
+        .find_map(|thumb_path| read(thumb_path).ok());
+    Ok((data, thumb))
+}
+
+/// Save folders name the thumbnail of `course_data_000.bcd` `course_thumb_000.btl`.
+fn get_thumbnail_paths(data_path: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![data_path.with_extension("btl")];
+    if let Some(file_name) = data_path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+    {
+        if file_name.starts_with("course_data_") {
+            paths.push(
+                data_path
+                    .with_file_name(file_name.replacen("course_data_", "course_thumb_", 1))
+                    .with_extension("btl"),
+            );
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn exported_files_round_trip() {
+        let test_dir = TestDir::new("course-file-round-trip");
+
+        let data_path =
+            write_course_files(b"data", Some(b"thumb"), &test_dir.path().join("course.txt"))
+                .unwrap();
+
+        assert_eq!(data_path, test_dir.path().join("course.bcd"));
+        assert!(test_dir.path().join("course.btl").exists());
+        assert_eq!(
+            read_course_files(&data_path).unwrap(),
+            (b"data".to_vec(), Some(b"thumb".to_vec()))
+        );
+    }
+
+    #[test]
+    fn reads_course_data_without_thumbnail() {
+        let test_dir = TestDir::new("course-file-no-thumb");
+
+        let data_path =
+            write_course_files(b"data", None, &test_dir.path().join("course.bcd")).unwrap();
+
+        assert_eq!(
+            read_course_files(&data_path).unwrap(),
+            (b"data".to_vec(), None)
+        );
+    }
+
+    #[test]
+    fn finds_thumbnails_of_save_folders() {
+        let test_dir = TestDir::new("course-file-save-folder");
+        let data_path = test_dir.path().join("course_data_012.bcd");
+        write(&data_path, b"data").unwrap();
+        write(test_dir.path().join("course_thumb_012.btl"), b"thumb").unwrap();
+        write(test_dir.path().join("course_thumb_013.btl"), b"other").unwrap();
+
+        assert_eq!(
+            get_thumbnail_paths(&data_path),
+            vec![
+                test_dir.path().join("course_data_012.btl"),
+                test_dir.path().join("course_thumb_012.btl"),
+            ]
+        );
+        assert_eq!(
+            read_course_files(&data_path).unwrap(),
+            (b"data".to_vec(), Some(b"thumb".to_vec()))
+        );
+    }
+
+    #[test]
+    fn prefers_thumbnails_next_to_exported_files() {
+        let test_dir = TestDir::new("course-file-exported");
+        let data_path = test_dir.path().join("course_data_000.bcd");
+        write(&data_path, b"data").unwrap();
+        write(test_dir.path().join("course_data_000.btl"), b"exported").unwrap();
+        write(test_dir.path().join("course_thumb_000.btl"), b"save").unwrap();
+
+        assert_eq!(
+            read_course_files(&data_path).unwrap(),
+            (b"data".to_vec(), Some(b"exported".to_vec()))
+        );
+    }
+
+    #[test]
+    fn looks_only_next_to_other_course_data_files() {
+        let path = Path::new("courses").join("level.bcd");
+
+        assert_eq!(
+            get_thumbnail_paths(&path),
+            vec![Path::new("courses").join("level.btl")]
+        );
+    }
+}
@@ -0,0 +1,373 @@
+use protobuf::ProtobufEnum;
+use serde::{Deserialize, Serialize};
+use smmdb_lib::proto::SMM2Course::SMM2Course;
+use std::fmt;
+
+/// The proto enums are mapped by their value, which follows the order of the course file format.
+/// SMMDB filters by the names of the proto enums.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum GameStyle {
+    #[serde(rename = "M1")]
+    Smb1,
+    #[serde(rename = "M3")]
+    Smb3,
+    #[serde(rename = "MW")]
+    Smw,
+    #[serde(rename = "WU")]
+    Nsmbu,
+    #[serde(rename = "W3")]
+    Sm3dw,
+}
+
+impl GameStyle {
+    pub const ALL: [GameStyle; 5] = [
+        GameStyle::Smb1,
+        GameStyle::Smb3,
+        GameStyle::Smw,
+        GameStyle::Nsmbu,
+        GameStyle::Sm3dw,
+    ];
+
+    pub fn from_course(course: &SMM2Course) -> Option<GameStyle> {
+        GameStyle::ALL
+            .get(course.get_header().get_game_style().value() as usize)
+            .cloned()
+    }
+}
+
+impl fmt::Display for GameStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameStyle::Smb1 => write!(f, "Super Mario Bros."),
+            GameStyle::Smb3 => write!(f, "Super Mario Bros. 3"),
+            GameStyle::Smw => write!(f, "Super Mario World"),
+            GameStyle::Nsmbu => write!(f, "New Super Mario Bros. U"),
+            GameStyle::Sm3dw => write!(f, "Super Mario 3D World"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CourseTheme {
+    Ground,
+    Underground,
+    Castle,
+    Airship,
+    Underwater,
+    GhostHouse,
+    Snow,
+    Desert,
+    Sky,
+    Forest,
+}
+
+impl CourseTheme {
+    pub const ALL: [CourseTheme; 10] = [
+        CourseTheme::Ground,
+        CourseTheme::Underground,
+        CourseTheme::Castle,
+        CourseTheme::Airship,
+        CourseTheme::Underwater,
+        CourseTheme::GhostHouse,
+        CourseTheme::Snow,
+        CourseTheme::Desert,
+        CourseTheme::Sky,
+        CourseTheme::Forest,
+    ];
+
+    pub fn from_course(course: &SMM2Course) -> Option<CourseTheme> {
+        CourseTheme::ALL
+            .get(course.get_course_area().get_course_theme().value() as usize)
+            .cloned()
+    }
+}
+
+impl fmt::Display for CourseTheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CourseTheme::Ground => write!(f, "Ground"),
+            CourseTheme::Underground => write!(f, "Underground"),
+            CourseTheme::Castle => write!(f, "Castle"),
+            CourseTheme::Airship => write!(f, "Airship"),
+            CourseTheme::Underwater => write!(f, "Underwater"),
+            CourseTheme::GhostHouse => write!(f, "Ghost House"),
+            CourseTheme::Snow => write!(f, "Snow"),
+            CourseTheme::Desert => write!(f, "Desert"),
+            CourseTheme::Sky => write!(f, "Sky"),
+            CourseTheme::Forest => write!(f, "Forest"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AutoScroll {
+    None,
+    Slow,
+    Normal,
+    Fast,
+    Custom,
+}
+
+impl AutoScroll {
+    pub const ALL: [AutoScroll; 5] = [
+        AutoScroll::None,
+        AutoScroll::Slow,
+        AutoScroll::Normal,
+        AutoScroll::Fast,
+        AutoScroll::Custom,
+    ];
+
+    pub fn from_course(course: &SMM2Course) -> Option<AutoScroll> {
+        AutoScroll::ALL
+            .get(course.get_course_area().get_auto_scroll().value() as usize)
+            .cloned()
+    }
+}
+
+impl fmt::Display for AutoScroll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AutoScroll::None => write!(f, "None"),
+            AutoScroll::Slow => write!(f, "Slow"),
+            AutoScroll::Normal => write!(f, "Normal"),
+            AutoScroll::Fast => write!(f, "Fast"),
+            AutoScroll::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+pub fn get_clear_condition(course: &SMM2Course) -> Option<String> {
+    let header = course.get_header();
+    if header.get_clear_condition() == 0 {
+        return None;
+    }
+    Some(format!(
+        "{:?} #{} (amount: {})",
+        header.get_clear_condition_category(),
+        header.get_clear_condition(),
+        header.get_clear_condition_amount()
+    ))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DayTime {
+    Day,
+    Night,
+}
+
+impl DayTime {
+    pub const ALL: [DayTime; 2] = [DayTime::Day, DayTime::Night];
+
+    pub fn from_course(course: &SMM2Course) -> DayTime {
+        match course.get_course_area().get_day_time().value() {
+            0 => DayTime::Day,
+            _ => DayTime::Night,
+        }
+    }
+}
+
+impl fmt::Display for DayTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DayTime::Day => write!(f, "Day"),
+            DayTime::Night => write!(f, "Night"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClearConditionPresence {
+    With,
+    Without,
+}
+
+impl ClearConditionPresence {
+    pub const ALL: [ClearConditionPresence; 2] = [
+        ClearConditionPresence::With,
+        ClearConditionPresence::Without,
+    ];
+}
+
+impl fmt::Display for ClearConditionPresence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClearConditionPresence::With => write!(f, "With clear condition"),
+            ClearConditionPresence::Without => write!(f, "Without clear condition"),
+        }
+    }
+}
+
+/// A pick list option, which either matches any value or only the given one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Choice<T> {
+    Any,
+    Only(T),
+}
+
+impl<T: Copy> Choice<T> {
+    pub fn all(values: &[T]) -> Vec<Choice<T>> {
+        std::iter::once(Choice::Any)
+            .chain(values.iter().cloned().map(Choice::Only))
+            .collect()
+    }
+}
+
+impl<T> From<Option<T>> for Choice<T> {
+    fn from(value: Option<T>) -> Choice<T> {
+        match value {
+            Some(value) => Choice::Only(value),
+            None => Choice::Any,
+        }
+    }
+}
+
+impl<T> Choice<T> {
+    pub fn to_option(self) -> Option<T> {
+        match self {
+            Choice::Any => None,
+            Choice::Only(value) => Some(value),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Choice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Choice::Any => write!(f, "Any"),
+            Choice::Only(value) => value.fmt(f),
+        }
+    }
+}
+
+/// Filters on course metadata, which are applied to the fetched courses.
+///
+/// Game style, theme and time are also sent to SMMDB as query parameters,
+/// in case the server filters by them as well.
+#[derive(Clone, Debug, Default)]
+pub struct MetaFilter {
+    game_style: Option<GameStyle>,
+    theme: Option<CourseTheme>,
+    day_time: Option<DayTime>,
+    clear_condition: Option<ClearConditionPresence>,
+    min_time: Option<u32>,
+    max_time: Option<u32>,
+    min_time_input: String,
+    max_time_input: String,
+}
+
+impl MetaFilter {
+    pub fn matches(&self, course: &SMM2Course) -> bool {
+        let time = course.get_header().get_time();
+        let has_clear_condition = course.get_header().get_clear_condition() != 0;
+        self.game_style.map_or(true, |game_style| {
+            GameStyle::from_course(course) == Some(game_style)
+        }) && self.theme.map_or(true, |theme| {
+            CourseTheme::from_course(course) == Some(theme)
+        }) && self
+            .day_time
+            .map_or(true, |day_time| DayTime::from_course(course) == day_time)
+            && self
+                .clear_condition
+                .map_or(true, |clear_condition| match clear_condition {
+                    ClearConditionPresence::With => has_clear_condition,
+                    ClearConditionPresence::Without => !has_clear_condition,
+                })
+            && self.min_time.map_or(true, |min_time| time >= min_time)
+            && self.max_time.map_or(true, |max_time| time <= max_time)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.game_style.is_some()
+            || self.theme.is_some()
+            || self.day_time.is_some()
+            || self.clear_condition.is_some()
+            || self.min_time.is_some()
+            || self.max_time.is_some()
+    }
+
+    pub fn get_game_style(&self) -> Option<GameStyle> {
+        self.game_style
+    }
+
+    pub fn set_game_style(&mut self, game_style: Option<GameStyle>) {
+        self.game_style = game_style;
+    }
+
+    pub fn get_theme(&self) -> Option<CourseTheme> {
+        self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Option<CourseTheme>) {
+        self.theme = theme;
+    }
+
+    pub fn get_day_time(&self) -> Option<DayTime> {
+        self.day_time
+    }
+
+    pub fn set_day_time(&mut self, day_time: Option<DayTime>) {
+        self.day_time = day_time;
+    }
+
+    pub fn get_clear_condition(&self) -> Option<ClearConditionPresence> {
+        self.clear_condition
+    }
+
+    pub fn set_clear_condition(&mut self, clear_condition: Option<ClearConditionPresence>) {
+        self.clear_condition = clear_condition;
+    }
+
+    pub fn get_min_time(&self) -> Option<u32> {
+        self.min_time
+    }
+
+    pub fn get_min_time_input(&self) -> &str {
+        &self.min_time_input
+    }
+
+    /// Invalid input keeps the previous limit and is reported by `get_time_error`.
+    pub fn set_min_time(&mut self, min_time: String) {
+        if let Some(time) = parse_time(&min_time) {
+            self.min_time = time;
+        }
+        self.min_time_input = min_time;
+    }
+
+    pub fn get_max_time(&self) -> Option<u32> {
+        self.max_time
+    }
+
+    pub fn get_max_time_input(&self) -> &str {
+        &self.max_time_input
+    }
+
+    pub fn set_max_time(&mut self, max_time: String) {
+        if let Some(time) = parse_time(&max_time) {
+            self.max_time = time;
+        }
+        self.max_time_input = max_time;
+    }
+
+    pub fn get_time_error(&self) -> Option<String> {
+        [&self.min_time_input, &self.max_time_input]
+            .iter()
+            .find(|input| parse_time(input).is_none())
+            .map(|input| {
+                format!(
+                    "\"{}\" is not a time limit. Enter whole seconds.",
+                    input.trim()
+                )
+            })
+    }
+}
+
+/// Returns `None` for invalid input and `Some(None)` for empty input, which removes the limit.
+fn parse_time(input: &str) -> Option<Option<u32>> {
+    let input = input.trim();
+    if input.is_empty() {
+        Some(None)
+    } else {
+        input.parse().ok().map(Some)
+    }
+}
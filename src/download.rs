@@ -1,4 +1,11 @@
+use crate::{http, smmdb::ApiError};
+
+use reqwest::{header, StatusCode};
+
+const MAX_RESUMES: u32 = 3;
+
 pub struct Download {
+    pub id: u64,
     pub url: String,
 }
 
@@ -6,12 +13,13 @@ impl<H, I> iced_native::subscription::Recipe<H, I> for Download
 where
     H: std::hash::Hasher,
 {
-    type Output = Progress;
+    type Output = (u64, Progress);
 
     fn hash(&self, state: &mut H) {
         use std::hash::Hash;
 
         std::any::TypeId::of::<Self>().hash(state);
+        self.id.hash(state);
         self.url.hash(state);
     }
 
@@ -19,68 +27,129 @@ where
         self: Box<Self>,
         _input: futures::stream::BoxStream<'static, I>,
     ) -> futures::stream::BoxStream<'static, Self::Output> {
-        Box::pin(futures::stream::unfold(
-            State::Ready(self.url),
-            |state| async move {
-                match state {
-                    State::Ready(url) => {
-                        let response = reqwest::get(&url).await;
+        use futures::StreamExt;
+
+        let id = self.id;
+        Box::pin(
+            futures::stream::unfold(
+                State::Ready {
+                    url: self.url,
+                    data: vec![],
+                    resumes: 0,
+                },
+                |state| async move {
+                    match state {
+                        State::Ready { url, data, resumes } => {
+                            let mut request = http::client().get(&url);
+                            if !data.is_empty() {
+                                request =
+                                    request.header(header::RANGE, format!("bytes={}-", data.len()));
+                            }
 
-                        match response {
-                            Ok(response) => {
-                                if let Some(total) = response.content_length() {
+                            match http::send_idempotent(request).await {
+                                Ok(response) => {
+                                    if let Some(err) = ApiError::from_response(&response) {
+                                        return Some((Progress::Errored(err), State::Finished));
+                                    }
+                                    // Servers may ignore the range and resend everything.
+                                    let data = if response.status() == StatusCode::PARTIAL_CONTENT {
+                                        data
+                                    } else {
+                                        vec![]
+                                    };
+                                    let total = response
+                                        .content_length()
+                                        .map(|length| length + data.len() as u64);
+                                    let progress = if resumes == 0 {
+                                        Progress::Started
+                                    } else {
+                                        get_progress(data.len() as u64, total)
+                                    };
                                     Some((
-                                        Progress::Started,
+                                        progress,
                                         State::Downloading {
+                                            url,
                                             response,
                                             total,
-                                            downloaded: 0,
-                                            data: vec![],
+                                            data,
+                                            resumes,
                                         },
                                     ))
-                                } else {
-                                    Some((Progress::Errored, State::Finished))
                                 }
+                                Err(err) => Some((Progress::Errored(err), State::Finished)),
                             }
-                            Err(_) => Some((Progress::Errored, State::Finished)),
                         }
-                    }
-                    State::Downloading {
-                        mut response,
-                        total,
-                        downloaded,
-                        mut data,
-                    } => match response.chunk().await {
-                        Ok(Some(chunk)) => {
-                            let downloaded = downloaded + chunk.len() as u64;
-                            data.extend(chunk.iter().cloned());
-
-                            let percentage = (downloaded as f32 / total as f32) * 100.0;
+                        State::Downloading {
+                            url,
+                            mut response,
+                            total,
+                            mut data,
+                            resumes,
+                        } => match response.chunk().await {
+                            Ok(Some(chunk)) => {
+                                data.extend(chunk.iter().cloned());
 
-                            Some((
-                                Progress::Advanced(percentage),
-                                State::Downloading {
-                                    response,
-                                    total,
-                                    downloaded,
+                                Some((
+                                    get_progress(data.len() as u64, total),
+                                    State::Downloading {
+                                        url,
+                                        response,
+                                        total,
+                                        data,
+                                        resumes,
+                                    },
+                                ))
+                            }
+                            Ok(None) => match total {
+                                Some(total) if (data.len() as u64) < total => resume(
+                                    url,
                                     data,
-                                },
-                            ))
-                        }
-                        Ok(None) => Some((Progress::Finished(data), State::Finished)),
-                        Err(_) => Some((Progress::Errored, State::Finished)),
-                    },
-                    State::Finished => {
-                        // We do not let the stream die, as it would start a
-                        // new download repeatedly if the user is not careful
-                        // in case of errors.
-                        let _: () = iced::futures::future::pending().await;
-
-                        None
+                                    Some(total),
+                                    resumes,
+                                    ApiError::Other(
+                                        "The download ended before the course was complete."
+                                            .to_string(),
+                                    ),
+                                ),
+                                _ => Some((Progress::Finished(data), State::Finished)),
+                            },
+                            Err(err) => resume(url, data, total, resumes, err.into()),
+                        },
+                        // The job stops running once it finished or failed, so the
+                        // subscription is dropped and cannot start the download again.
+                        State::Finished => None,
                     }
-                }
-            },
-        ))
+                },
+            )
+            .map(move |progress| (id, progress)),
+        )
+    }
+}
+
+fn resume(
+    url: String,
+    data: Vec<u8>,
+    total: Option<u64>,
+    resumes: u32,
+    err: ApiError,
+) -> Option<(Progress, State)> {
+    if resumes >= MAX_RESUMES {
+        return Some((Progress::Errored(err), State::Finished));
+    }
+    Some((
+        get_progress(data.len() as u64, total),
+        State::Ready {
+            url,
+            data,
+            resumes: resumes + 1,
+        },
+    ))
+}
+
+fn get_progress(downloaded: u64, total: Option<u64>) -> Progress {
+    match total {
+        Some(total) if total > 0 => Progress::Advanced((downloaded as f32 / total as f32) * 100.0),
+        _ => Progress::Received(downloaded),
     }
 }
 
@@ -88,17 +157,24 @@ where
 pub enum Progress {
     Started,
     Advanced(f32),
+    /// Bytes received so far, if the server did not tell the size of the course.
+    Received(u64),
     Finished(Vec<u8>),
-    Errored,
+    Errored(ApiError),
 }
 
 pub enum State {
-    Ready(String),
+    Ready {
+        url: String,
+        data: Vec<u8>,
+        resumes: u32,
+    },
     Downloading {
+        url: String,
         response: reqwest::Response,
-        total: u64,
-        downloaded: u64,
+        total: Option<u64>,
         data: Vec<u8>,
+        resumes: u32,
     },
     Finished,
 }
@@ -0,0 +1,237 @@
+use crate::smmdb::ApiError;
+
+pub const MAX_CONCURRENT_DOWNLOADS: usize = 2;
+
+/// A slot stays reserved as long as a job targets it, so no two jobs can fill the same slot.
+#[derive(Clone, Debug, Default)]
+pub struct DownloadQueue {
+    jobs: Vec<DownloadJob>,
+    next_id: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct DownloadJob {
+    id: u64,
+    smmdb_id: String,
+    title: String,
+    save_index: usize,
+    status: JobStatus,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JobStatus {
+    Pending,
+    /// The progress in percent, if the size of the course is known.
+    Running(Option<f32>),
+    Failed(ApiError),
+}
+
+impl DownloadQueue {
+    pub fn new() -> DownloadQueue {
+        DownloadQueue::default()
+    }
+
+    pub fn push(&mut self, smmdb_id: String, title: String, save_index: usize) -> Option<u64> {
+        if self.is_reserved(save_index) {
+            return None;
+        }
+        let id = self.next_job_id();
+        self.jobs.push(DownloadJob {
+            id,
+            smmdb_id,
+            title,
+            save_index,
+            status: JobStatus::Pending,
+        });
+        self.start_pending();
+        Some(id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    pub fn is_reserved(&self, save_index: usize) -> bool {
+        self.get_job_for_slot(save_index).is_some()
+    }
+
+    pub fn get_jobs(&self) -> &[DownloadJob] {
+        &self.jobs
+    }
+
+    pub fn get_job_for_slot(&self, save_index: usize) -> Option<&DownloadJob> {
+        self.jobs.iter().find(|job| job.save_index == save_index)
+    }
+
+    pub fn get_running(&self) -> impl Iterator<Item = &DownloadJob> {
+        self.jobs.iter().filter(|job| job.is_running())
+    }
+
+    pub fn set_progress(&mut self, id: u64, progress: Option<f32>) {
+        if let Some(job) = self.get_job_mut(id) {
+            if job.is_running() {
+                job.status = JobStatus::Running(progress);
+            }
+        }
+    }
+
+    pub fn finish(&mut self, id: u64) -> Option<DownloadJob> {
+        let job = self.remove(id);
+        self.start_pending();
+        job
+    }
+
+    /// Marks a job as failed. It keeps its slot reserved until it is retried or cancelled.
+    pub fn fail(&mut self, id: u64, err: ApiError) {
+        if let Some(job) = self.get_job_mut(id) {
+            job.status = JobStatus::Failed(err);
+        }
+        self.start_pending();
+    }
+
+    pub fn cancel(&mut self, id: u64) {
+        self.remove(id);
+        self.start_pending();
+    }
+
+    /// The job gets a new id, so that its download starts over.
+    pub fn retry(&mut self, id: u64) {
+        let new_id = self.next_job_id();
+        if let Some(job) = self.get_job_mut(id) {
+            if let JobStatus::Failed(_) = job.status {
+                job.id = new_id;
+                job.status = JobStatus::Pending;
+            }
+        }
+        self.start_pending();
+    }
+
+    /// Job ids keep increasing, so that no download is mistaken for a new one.
+    pub fn clear(&mut self) {
+        self.jobs.clear();
+    }
+
+    fn start_pending(&mut self) {
+        let mut running = self.get_running().count();
+        for job in self.jobs.iter_mut() {
+            if running >= MAX_CONCURRENT_DOWNLOADS {
+                break;
+            }
+            if job.status == JobStatus::Pending {
+                job.status = JobStatus::Running(Some(0.));
+                running += 1;
+            }
+        }
+    }
+
+    fn next_job_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn get_job_mut(&mut self, id: u64) -> Option<&mut DownloadJob> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    fn remove(&mut self, id: u64) -> Option<DownloadJob> {
+        let index = self.jobs.iter().position(|job| job.id == id)?;
+        Some(self.jobs.remove(index))
+    }
+}
+
+impl DownloadJob {
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn get_smmdb_id(&self) -> &String {
+        &self.smmdb_id
+    }
+
+    pub fn get_title(&self) -> &String {
+        &self.title
+    }
+
+    pub fn get_save_index(&self) -> usize {
+        self.save_index
+    }
+
+    pub fn get_status(&self) -> &JobStatus {
+        &self.status
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.status, JobStatus::Running(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserves_slots_and_limits_concurrency() {
+        let mut downloads = DownloadQueue::new();
+
+        let first = downloads
+            .push("0".to_string(), "Course 0".to_string(), 0)
+            .unwrap();
+        let second = downloads
+            .push("1".to_string(), "Course 1".to_string(), 1)
+            .unwrap();
+        let third = downloads
+            .push("2".to_string(), "Course 2".to_string(), 2)
+            .unwrap();
+        assert_eq!(
+            downloads.push("3".to_string(), "Course 3".to_string(), 1),
+            None
+        );
+        assert_eq!(downloads.get_running().count(), MAX_CONCURRENT_DOWNLOADS);
+        assert_eq!(
+            downloads.get_job_for_slot(2).unwrap().get_status(),
+            &JobStatus::Pending
+        );
+
+        downloads.set_progress(first, Some(50.));
+        assert_eq!(
+            downloads.get_job_for_slot(0).unwrap().get_status(),
+            &JobStatus::Running(Some(50.))
+        );
+        downloads.fail(second, ApiError::Timeout);
+        assert!(downloads.is_reserved(1));
+        assert_eq!(
+            downloads.get_job_for_slot(2).unwrap().get_status(),
+            &JobStatus::Running(Some(0.))
+        );
+
+        downloads.retry(second);
+        let retried = downloads.get_job_for_slot(1).unwrap();
+        assert_ne!(retried.get_id(), second);
+        assert_eq!(retried.get_status(), &JobStatus::Pending);
+
+        assert_eq!(downloads.finish(first).unwrap().get_save_index(), 0);
+        assert!(!downloads.is_reserved(0));
+        assert!(downloads.get_job_for_slot(1).unwrap().is_running());
+
+        downloads.cancel(third);
+        assert!(!downloads.is_reserved(2));
+        assert_eq!(downloads.get_jobs().len(), 1);
+    }
+
+    #[test]
+    fn keeps_increasing_job_ids_after_clearing() {
+        let mut downloads = DownloadQueue::new();
+        let first = downloads
+            .push("0".to_string(), "Course 0".to_string(), 0)
+            .unwrap();
+
+        downloads.clear();
+
+        assert!(downloads.is_empty());
+        assert!(!downloads.is_reserved(0));
+        let second = downloads
+            .push("1".to_string(), "Course 1".to_string(), 0)
+            .unwrap();
+        assert_ne!(first, second);
+    }
+}
@@ -1,5 +1,3 @@
-use crate::components::SaveButton;
-
 use anyhow::Result;
 use std::{collections::HashSet, fs::read_dir, path::PathBuf};
 
@@ -7,7 +5,7 @@ mod save;
 
 pub use save::*;
 
-pub fn guess_emu_dir() -> Result<Vec<SaveButton>> {
+pub fn guess_emu_dir() -> Result<Vec<EmuSave>> {
     let mut dirs = vec![];
     let mut found_paths: HashSet<PathBuf> = HashSet::new();
     let yuzu_guesses = ["yuzu", "yuzu-emu"];
@@ -70,7 +68,7 @@ pub fn guess_emu_dir() -> Result<Vec<SaveButton>> {
 }
 
 fn guess_dir(
-    dirs: &mut Vec<SaveButton>,
+    dirs: &mut Vec<EmuSave>,
     found_paths: &mut HashSet<PathBuf>,
     dir: PathBuf,
     guesses: &[&str],
@@ -93,7 +91,7 @@ fn guess_dir(
                                 found_paths.insert(path.clone());
                                 let display_name =
                                     format!("[{:?}] {}", &emu_type, dir.to_string_lossy());
-                                dirs.push(SaveButton::new(display_name, path, emu_type.clone()));
+                                dirs.push(EmuSave::new(display_name, path, emu_type.clone()));
                             }
                         }
                     }
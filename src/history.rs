@@ -0,0 +1,306 @@
+use smmdb_lib::SavedCourse;
+
+const HISTORY_LIMIT: usize = 50;
+
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Operation {
+    description: String,
+    change: SlotChange,
+}
+
+#[derive(Clone, Debug)]
+pub enum SlotChange {
+    Swap(u8, u8),
+    /// A course moved from one slot to another, shifting the courses in between.
+    Move(u8, u8),
+    /// Slots, whose course has been added or deleted, with their content before and after.
+    Replace(Vec<(u8, SlotState, SlotState)>),
+}
+
+#[derive(Clone, Debug)]
+pub struct SlotState {
+    pub course: Option<SavedCourse>,
+    pub smmdb_id: Option<String>,
+}
+
+impl History {
+    pub fn push(&mut self, description: String, change: SlotChange) {
+        self.redo.clear();
+        self.undo.push(Operation {
+            description,
+            change,
+        });
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Returns the operation to undo, with its change already inverted.
+    pub fn take_undo(&mut self) -> Option<Operation> {
+        let operation = self.undo.pop()?;
+        self.redo.push(operation.clone());
+        Some(operation.invert())
+    }
+
+    pub fn take_redo(&mut self) -> Option<Operation> {
+        let operation = self.redo.pop()?;
+        self.undo.push(operation.clone());
+        Some(operation)
+    }
+
+    pub fn get_undo_slots(&self) -> Vec<usize> {
+        self.undo
+            .last()
+            .map_or_else(Vec::new, |operation| operation.change.get_slots())
+    }
+
+    pub fn get_redo_slots(&self) -> Vec<usize> {
+        self.redo
+            .last()
+            .map_or_else(Vec::new, |operation| operation.change.get_slots())
+    }
+
+    pub fn revert_undo(&mut self) {
+        if let Some(operation) = self.redo.pop() {
+            self.undo.push(operation);
+        }
+    }
+
+    pub fn revert_redo(&mut self) {
+        if let Some(operation) = self.undo.pop() {
+            self.redo.push(operation);
+        }
+    }
+}
+
+impl SlotChange {
+    pub fn get_slots(&self) -> Vec<usize> {
+        match self {
+            SlotChange::Swap(first, second) => vec![*first as usize, *second as usize],
+            SlotChange::Move(from, to) => {
+                (*from.min(to) as usize..=*from.max(to) as usize).collect()
+            }
+            SlotChange::Replace(slots) => {
+                slots.iter().map(|(index, _, _)| *index as usize).collect()
+            }
+        }
+    }
+}
+
+impl Operation {
+    pub fn get_description(&self) -> &String {
+        &self.description
+    }
+
+    pub fn get_change(&self) -> &SlotChange {
+        &self.change
+    }
+
+    fn invert(self) -> Operation {
+        let change = match self.change {
+            SlotChange::Swap(first, second) => SlotChange::Swap(first, second),
+            SlotChange::Move(from, to) => SlotChange::Move(to, from),
+            SlotChange::Replace(slots) => SlotChange::Replace(
+                slots
+                    .into_iter()
+                    .rev()
+                    .map(|(index, before, after)| (index, after, before))
+                    .collect(),
+            ),
+        };
+        Operation {
+            description: self.description,
+            change,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(smmdb_id: &str) -> SlotState {
+        SlotState {
+            course: None,
+            smmdb_id: Some(smmdb_id.to_string()),
+        }
+    }
+
+    fn push_swap(history: &mut History, first: u8, second: u8) {
+        history.push(
+            format!("Swap slots {} and {}", first + 1, second + 1),
+            SlotChange::Swap(first, second),
+        );
+    }
+
+    fn get_description(operation: Option<Operation>) -> Option<String> {
+        operation.map(|operation| operation.get_description().clone())
+    }
+
+    #[test]
+    fn undoes_and_redoes_in_order() {
+        let mut history = History::default();
+        assert!(!history.can_undo());
+        assert!(!history.can_redo());
+        push_swap(&mut history, 0, 1);
+        push_swap(&mut history, 2, 3);
+
+        assert_eq!(
+            get_description(history.take_undo()).as_deref(),
+            Some("Swap slots 3 and 4")
+        );
+        assert_eq!(
+            get_description(history.take_undo()).as_deref(),
+            Some("Swap slots 1 and 2")
+        );
+        assert!(history.take_undo().is_none());
+        assert!(history.can_redo());
+
+        assert_eq!(
+            get_description(history.take_redo()).as_deref(),
+            Some("Swap slots 1 and 2")
+        );
+        assert!(history.can_undo());
+        assert!(history.can_redo());
+        assert_eq!(
+            get_description(history.take_redo()).as_deref(),
+            Some("Swap slots 3 and 4")
+        );
+        assert!(history.take_redo().is_none());
+    }
+
+    #[test]
+    fn push_clears_redo() {
+        let mut history = History::default();
+        push_swap(&mut history, 0, 1);
+        history.take_undo();
+        assert!(history.can_redo());
+
+        push_swap(&mut history, 2, 3);
+
+        assert!(!history.can_redo());
+        assert_eq!(
+            get_description(history.take_undo()).as_deref(),
+            Some("Swap slots 3 and 4")
+        );
+        assert!(history.take_undo().is_none());
+    }
+
+    #[test]
+    fn drops_oldest_operations_beyond_limit() {
+        let mut history = History::default();
+        for index in 0..HISTORY_LIMIT + 5 {
+            history.push(format!("Operation {}", index), SlotChange::Swap(0, 1));
+        }
+
+        let mut count = 0;
+        let mut oldest = None;
+        while let Some(operation) = history.take_undo() {
+            count += 1;
+            oldest = Some(operation);
+        }
+        assert_eq!(count, HISTORY_LIMIT);
+        assert_eq!(get_description(oldest).as_deref(), Some("Operation 5"));
+    }
+
+    #[test]
+    fn reverts_failed_undo_and_redo() {
+        let mut history = History::default();
+        push_swap(&mut history, 0, 1);
+
+        history.take_undo();
+        history.revert_undo();
+        assert!(history.can_undo());
+        assert!(!history.can_redo());
+
+        history.take_undo();
+        history.take_redo();
+        history.revert_redo();
+        assert!(!history.can_undo());
+        assert!(history.can_redo());
+    }
+
+    #[test]
+    fn returns_slots_changed_by_undo_and_redo() {
+        let mut history = History::default();
+        assert!(history.get_undo_slots().is_empty());
+        history.push("Move".to_string(), SlotChange::Move(5, 2));
+        history.push(
+            "Replace".to_string(),
+            SlotChange::Replace(vec![(0, slot("a"), slot("b")), (4, slot("c"), slot("d"))]),
+        );
+
+        assert_eq!(history.get_undo_slots(), vec![0, 4]);
+        history.take_undo();
+        assert_eq!(history.get_undo_slots(), vec![2, 3, 4, 5]);
+        assert_eq!(history.get_redo_slots(), vec![0, 4]);
+        history.take_undo();
+        assert!(history.get_undo_slots().is_empty());
+        assert_eq!(history.get_redo_slots(), vec![2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn inverts_changes_on_undo() {
+        let mut history = History::default();
+        history.push("Swap".to_string(), SlotChange::Swap(1, 2));
+        history.push("Move".to_string(), SlotChange::Move(3, 7));
+        history.push(
+            "Replace".to_string(),
+            SlotChange::Replace(vec![(0, slot("a"), slot("b")), (4, slot("c"), slot("d"))]),
+        );
+
+        match history.take_undo().unwrap().get_change() {
+            SlotChange::Replace(slots) => {
+                let slots: Vec<(u8, Option<&str>, Option<&str>)> = slots
+                    .iter()
+                    .map(|(index, before, after)| {
+                        (
+                            *index,
+                            before.smmdb_id.as_deref(),
+                            after.smmdb_id.as_deref(),
+                        )
+                    })
+                    .collect();
+                assert_eq!(
+                    slots,
+                    vec![(4, Some("d"), Some("c")), (0, Some("b"), Some("a"))]
+                );
+            }
+            change => panic!("Unexpected change {:?}", change),
+        }
+        match history.take_undo().unwrap().get_change() {
+            SlotChange::Move(from, to) => assert_eq!((*from, *to), (7, 3)),
+            change => panic!("Unexpected change {:?}", change),
+        }
+        match history.take_undo().unwrap().get_change() {
+            SlotChange::Swap(first, second) => assert_eq!((*first, *second), (1, 2)),
+            change => panic!("Unexpected change {:?}", change),
+        }
+    }
+
+    #[test]
+    fn redoes_changes_as_recorded() {
+        let mut history = History::default();
+        history.push("Move".to_string(), SlotChange::Move(3, 7));
+        history.take_undo();
+
+        match history.take_redo().unwrap().get_change() {
+            SlotChange::Move(from, to) => assert_eq!((*from, *to), (3, 7)),
+            change => panic!("Unexpected change {:?}", change),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::smmdb::ApiError;
+
+use reqwest::{header, Client, Method, RequestBuilder, Response, StatusCode};
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a request to the API may take as a whole. Course transfers are not limited.
+const API_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_RETRIES: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+lazy_static! {
+    static ref CLIENT: Client = Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .expect("Could not initialize HTTP client");
+}
+
+/// Requests built from the shared client directly only time out while connecting,
+/// which is what course downloads and uploads need.
+pub fn client() -> &'static Client {
+    &CLIENT
+}
+
+pub fn api_request(method: Method, url: &str) -> RequestBuilder {
+    CLIENT.request(method, url).timeout(API_TIMEOUT)
+}
+
+/// Retries with exponential backoff, if the connection failed or the server is temporarily
+/// unavailable. Responses with an error status are returned as is, once no retries are left.
+pub async fn send_idempotent(request: RequestBuilder) -> Result<Response, ApiError> {
+    let mut attempt = 0;
+    loop {
+        let current = request
+            .try_clone()
+            .ok_or_else(|| ApiError::Other("Request cannot be retried".to_string()))?;
+        match current.send().await {
+            Ok(response) => {
+                if attempt >= MAX_RETRIES || !is_retryable(response.status()) {
+                    return Ok(response);
+                }
+                let delay = match get_retry_after(&response) {
+                    Some(retry_after) if retry_after > MAX_RETRY_AFTER => return Ok(response),
+                    Some(retry_after) => retry_after,
+                    None => get_backoff(attempt),
+                };
+                tokio::time::delay_for(delay).await;
+            }
+            Err(err) => {
+                if attempt >= MAX_RETRIES || !(err.is_timeout() || err.is_connect()) {
+                    return Err(err.into());
+                }
+                tokio::time::delay_for(get_backoff(attempt)).await;
+            }
+        }
+        attempt += 1;
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::SERVICE_UNAVAILABLE
+        | StatusCode::GATEWAY_TIMEOUT => true,
+        _ => false,
+    }
+}
+
+fn get_retry_after(response: &Response) -> Option<Duration> {
+    match response.status() {
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs),
+        _ => None,
+    }
+}
+
+fn get_backoff(attempt: u32) -> Duration {
+    INITIAL_BACKOFF * 2u32.pow(attempt)
+}
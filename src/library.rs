@@ -0,0 +1,371 @@
+use crate::{
+    course_file::{get_encrypted_course_data, get_encrypted_thumbnail, read_course},
+    course_meta::{get_clear_condition, CourseTheme, GameStyle},
+    settings::get_config_dir,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use smmdb_lib::Course2;
+use std::{
+    fs::{create_dir_all, read, remove_dir_all, write},
+    path::PathBuf,
+};
+
+const INDEX_FILE: &str = "library.json";
+const COURSE_DATA_FILE: &str = "course_data.bcd";
+const COURSE_THUMB_FILE: &str = "course_thumb.btl";
+const THUMBNAIL_FILE: &str = "thumbnail.jpg";
+
+/// Every course is kept in its own folder in the same format as in a save folder,
+/// next to a decrypted thumbnail for the UI. Metadata and tags are kept in an index file.
+#[derive(Clone, Debug)]
+pub struct Library {
+    dir: PathBuf,
+    entries: Vec<LibraryEntry>,
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LibraryEntry {
+    id: String,
+    title: String,
+    description: String,
+    smmdb_id: Option<String>,
+    game_style: Option<String>,
+    theme: Option<String>,
+    time: u32,
+    clear_condition: Option<String>,
+    added_at: i64,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(skip)]
+    thumbnail: Option<Vec<u8>>,
+}
+
+impl Library {
+    pub fn load() -> Result<Library> {
+        let mut dir = get_config_dir()?;
+        dir.push("library");
+        Ok(Library::load_from(dir))
+    }
+
+    pub fn load_from(dir: PathBuf) -> Library {
+        let mut entries: Vec<LibraryEntry> = read(dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|index| serde_json::from_slice(&index).ok())
+            .unwrap_or_default();
+        for entry in entries.iter_mut() {
+            entry.thumbnail = read(dir.join(&entry.id).join(THUMBNAIL_FILE)).ok();
+        }
+        Library {
+            dir,
+            entries,
+            error: None,
+        }
+    }
+
+    pub fn get_entries(&self) -> &[LibraryEntry] {
+        &self.entries
+    }
+
+    pub fn get_entry(&self, id: &str) -> Option<&LibraryEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    pub fn get_error(&self) -> Option<&String> {
+        self.error.as_ref()
+    }
+
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+
+    pub fn add(&mut self, course: &Course2, smmdb_id: Option<String>) -> Result<String> {
+        let meta = course.get_course();
+        let header = meta.get_header();
+        let entry = LibraryEntry {
+            id: String::new(),
+            title: header.get_title().to_string(),
+            description: header.get_description().to_string(),
+            smmdb_id,
+            game_style: GameStyle::from_course(meta).map(|game_style| game_style.to_string()),
+            theme: CourseTheme::from_course(meta).map(|theme| theme.to_string()),
+            time: header.get_time(),
+            clear_condition: get_clear_condition(meta),
+            added_at: Utc::now().timestamp(),
+            tags: vec![],
+            thumbnail: course
+                .get_course_thumb()
+                .map(|thumb| thumb.clone().take_jpeg()),
+        };
+        self.add_files(
+            entry,
+            get_encrypted_course_data(course),
+            get_encrypted_thumbnail(course),
+        )
+    }
+
+    fn add_files(
+        &mut self,
+        mut entry: LibraryEntry,
+        data: Vec<u8>,
+        thumb: Option<Vec<u8>>,
+    ) -> Result<String> {
+        entry.id = self.next_entry_id();
+        let entry_dir = self.dir.join(&entry.id);
+        create_dir_all(&entry_dir)?;
+        write(entry_dir.join(COURSE_DATA_FILE), data)?;
+        if let Some(thumb) = thumb {
+            write(entry_dir.join(COURSE_THUMB_FILE), thumb)?;
+        }
+        if let Some(thumbnail) = &entry.thumbnail {
+            write(entry_dir.join(THUMBNAIL_FILE), thumbnail)?;
+        }
+        let id = entry.id.clone();
+        self.entries.insert(0, entry);
+        self.save_index()?;
+        Ok(id)
+    }
+
+    pub fn get_course(&self, id: &str) -> Result<Course2> {
+        let (data, thumb) = self.get_files(id)?;
+        read_course(data, thumb)
+    }
+
+    fn get_files(&self, id: &str) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        if self.get_entry(id).is_none() {
+            return Err(anyhow!("The course is not in the library."));
+        }
+        let entry_dir = self.dir.join(id);
+        let data = read(entry_dir.join(COURSE_DATA_FILE))?;
+        let thumb = read(entry_dir.join(COURSE_THUMB_FILE)).ok();
+        Ok((data, thumb))
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<()> {
+        self.entries.retain(|entry| entry.id != id);
+        self.save_index()?;
+        let entry_dir = self.dir.join(id);
+        if entry_dir.exists() {
+            remove_dir_all(entry_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Adds comma separated tags to an entry, ignoring tags it already has.
+    pub fn add_tags(&mut self, id: &str, tags: &str) -> Result<()> {
+        if let Some(entry) = self.get_entry_mut(id) {
+            for tag in tags.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+                if !entry.tags.iter().any(|existing| existing == tag) {
+                    entry.tags.push(tag.to_string());
+                }
+            }
+        }
+        self.save_index()
+    }
+
+    pub fn remove_tag(&mut self, id: &str, tag: &str) -> Result<()> {
+        if let Some(entry) = self.get_entry_mut(id) {
+            entry.tags.retain(|existing| existing != tag);
+        }
+        self.save_index()
+    }
+
+    fn get_entry_mut(&mut self, id: &str) -> Option<&mut LibraryEntry> {
+        self.entries.iter_mut().find(|entry| entry.id == id)
+    }
+
+    fn next_entry_id(&self) -> String {
+        let mut id = Utc::now().timestamp_millis();
+        while self.dir.join(id.to_string()).exists() || self.get_entry(&id.to_string()).is_some() {
+            id += 1;
+        }
+        id.to_string()
+    }
+
+    fn save_index(&self) -> Result<()> {
+        create_dir_all(&self.dir)?;
+        write(
+            self.dir.join(INDEX_FILE),
+            serde_json::to_string(&self.entries)?,
+        )?;
+        Ok(())
+    }
+}
+
+impl LibraryEntry {
+    pub fn get_id(&self) -> &String {
+        &self.id
+    }
+
+    pub fn get_title(&self) -> &String {
+        &self.title
+    }
+
+    pub fn get_description(&self) -> &String {
+        &self.description
+    }
+
+    pub fn get_smmdb_id(&self) -> Option<&String> {
+        self.smmdb_id.as_ref()
+    }
+
+    pub fn get_game_style(&self) -> Option<&String> {
+        self.game_style.as_ref()
+    }
+
+    pub fn get_theme(&self) -> Option<&String> {
+        self.theme.as_ref()
+    }
+
+    pub fn get_time(&self) -> u32 {
+        self.time
+    }
+
+    pub fn get_clear_condition(&self) -> Option<&String> {
+        self.clear_condition.as_ref()
+    }
+
+    pub fn get_added_at(&self) -> i64 {
+        self.added_at
+    }
+
+    pub fn get_tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn get_thumbnail(&self) -> Option<&Vec<u8>> {
+        self.thumbnail.as_ref()
+    }
+
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        filter.is_empty()
+            || self.title.to_lowercase().contains(&filter)
+            || self
+                .tags
+                .iter()
+                .any(|tag| tag.to_lowercase().contains(&filter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn entry(title: &str, tags: &[&str]) -> LibraryEntry {
+        LibraryEntry {
+            id: String::new(),
+            title: title.to_string(),
+            description: format!("Description of {}", title),
+            smmdb_id: None,
+            game_style: None,
+            theme: None,
+            time: 300,
+            clear_condition: None,
+            added_at: 1_600_000_000,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            thumbnail: Some(b"jpeg".to_vec()),
+        }
+    }
+
+    #[test]
+    fn adds_courses_and_loads_them_again() {
+        let test_dir = TestDir::new("library-add");
+        let mut library = Library::load_from(test_dir.path().to_path_buf());
+        let first = library
+            .add_files(
+                entry("First", &[]),
+                b"first".to_vec(),
+                Some(b"thumb".to_vec()),
+            )
+            .unwrap();
+        let second = library
+            .add_files(entry("Second", &[]), b"second".to_vec(), None)
+            .unwrap();
+        assert_ne!(first, second);
+
+        let library = Library::load_from(test_dir.path().to_path_buf());
+        let titles: Vec<&String> = library
+            .get_entries()
+            .iter()
+            .map(|entry| entry.get_title())
+            .collect();
+        assert_eq!(titles, vec!["Second", "First"]);
+        assert_eq!(
+            library.get_entry(&first).unwrap().get_thumbnail(),
+            Some(&b"jpeg".to_vec())
+        );
+        assert_eq!(
+            library.get_files(&first).unwrap(),
+            (b"first".to_vec(), Some(b"thumb".to_vec()))
+        );
+        assert_eq!(
+            library.get_files(&second).unwrap(),
+            (b"second".to_vec(), None)
+        );
+    }
+
+    #[test]
+    fn fails_to_get_courses_not_in_the_library() {
+        let test_dir = TestDir::new("library-get");
+        let library = Library::load_from(test_dir.path().to_path_buf());
+
+        assert!(library.get_files("0").is_err());
+        assert!(library.get_course("0").is_err());
+    }
+
+    #[test]
+    fn removes_courses_with_their_files() {
+        let test_dir = TestDir::new("library-remove");
+        let mut library = Library::load_from(test_dir.path().to_path_buf());
+        let id = library
+            .add_files(entry("Course", &[]), b"data".to_vec(), None)
+            .unwrap();
+
+        library.remove(&id).unwrap();
+
+        assert!(library.get_entry(&id).is_none());
+        assert!(!test_dir.path().join(&id).exists());
+        let library = Library::load_from(test_dir.path().to_path_buf());
+        assert!(library.get_entries().is_empty());
+    }
+
+    #[test]
+    fn adds_and_removes_tags() {
+        let test_dir = TestDir::new("library-tags");
+        let mut library = Library::load_from(test_dir.path().to_path_buf());
+        let id = library
+            .add_files(entry("Course", &["speedrun"]), b"data".to_vec(), None)
+            .unwrap();
+
+        library.add_tags(&id, " puzzle, speedrun,, kaizo ").unwrap();
+        assert_eq!(
+            library.get_entry(&id).unwrap().get_tags(),
+            ["speedrun", "puzzle", "kaizo"]
+        );
+
+        library.remove_tag(&id, "speedrun").unwrap();
+        let library = Library::load_from(test_dir.path().to_path_buf());
+        assert_eq!(
+            library.get_entry(&id).unwrap().get_tags(),
+            ["puzzle", "kaizo"]
+        );
+    }
+
+    #[test]
+    fn matches_title_and_tags_ignoring_case() {
+        let entry = entry("Airship Assault", &["Auto Scroll"]);
+
+        assert!(entry.matches(""));
+        assert!(entry.matches("  "));
+        assert!(entry.matches("airship"));
+        assert!(entry.matches(" ASSAULT "));
+        assert!(entry.matches("scroll"));
+        assert!(!entry.matches("castle"));
+        assert!(!entry.matches("Description"));
+    }
+}
@@ -2,15 +2,28 @@
 extern crate lazy_static;
 
 mod app;
+mod backups;
+mod cache;
+mod cli;
 mod components;
+mod course_file;
+mod course_meta;
 mod download;
+mod download_queue;
 mod emu;
 mod font;
+mod history;
+mod http;
 mod icon;
+mod library;
 mod pages;
 mod settings;
 mod smmdb;
 mod styles;
+#[cfg(test)]
+mod test_dir;
+mod upload;
+mod upload_registry;
 mod widgets;
 
 pub use app::{AppErrorState, AppState, Message};
@@ -19,6 +32,7 @@ pub use emu::{EmuSave, EmuType};
 pub use pages::Page;
 pub use settings::Settings;
 pub use smmdb::Smmdb;
+pub use upload::{Upload, UploadProgress};
 
 use anyhow::Result;
 
@@ -26,6 +40,13 @@ fn main() -> Result<()> {
     use app::*;
     use iced::{window, Application};
 
+    env_logger::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if cli::is_command(&args) {
+        return cli::run(args);
+    }
+
     let icon = match image::load_from_memory(include_bytes!("../assets/icons/icon.png")) {
         Ok(buffer) => {
             let buffer = buffer.to_rgba();
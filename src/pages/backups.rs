@@ -0,0 +1,193 @@
+use crate::{
+    backups::{diff_slots, get_slot_contents, Backup, SlotChange, SlotContent},
+    font::*,
+    smmdb::format_timestamp,
+    styles::*,
+    AppErrorState, AppState, Message,
+};
+
+use iced::{
+    button, container, scrollable, Align, Button, Column, Container, Element, Length, Row,
+    Scrollable, Space, Text,
+};
+
+#[derive(Clone, Debug)]
+pub struct BackupsPage {
+    backups: Vec<Backup>,
+    current: Vec<Option<SlotContent>>,
+    preview: Option<(String, Option<Result<Vec<SlotChange>, String>>)>,
+    state: scrollable::State,
+    close_state: button::State,
+    backup_states: Vec<BackupState>,
+}
+
+impl BackupsPage {
+    pub fn new(backups: Vec<Backup>, save: &smmdb_lib::Save) -> BackupsPage {
+        BackupsPage {
+            backup_states: vec![BackupState::default(); backups.len()],
+            backups,
+            current: get_slot_contents(save),
+            preview: None,
+            state: scrollable::State::new(),
+            close_state: button::State::new(),
+        }
+    }
+
+    pub fn get_backup(&self, id: &str) -> Option<&Backup> {
+        self.backups.iter().find(|backup| backup.get_id() == id)
+    }
+
+    pub fn start_preview(&mut self, id: String) {
+        self.preview = Some((id, None));
+    }
+
+    pub fn set_preview(&mut self, id: String, save: Result<smmdb_lib::Save, String>) {
+        if let Some((preview_id, _)) = &self.preview {
+            if *preview_id != id {
+                return;
+            }
+        }
+        let changes = save.map(|save| diff_slots(&get_slot_contents(&save), &self.current));
+        self.preview = Some((id, Some(changes)));
+    }
+
+    pub fn view(&mut self, state: &AppState, error_state: &AppErrorState) -> Element<Message> {
+        let is_busy = matches!(state, AppState::Loading);
+
+        let mut content = Scrollable::new(&mut self.state)
+            .padding(CONTAINER_PADDING)
+            .spacing(LIST_SPACING)
+            .push(
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(Text::new("Backups").size(36).width(Length::Fill))
+                    .push(
+                        Button::new(&mut self.close_state, Text::new("Close"))
+                            .style(DefaultButtonDangerStyle)
+                            .on_press(Message::CloseBackups),
+                    ),
+            )
+            .push(
+                Text::new(
+                    "A backup of your save is taken before every change. \
+                    Restoring a backup takes a backup of your current save as well.",
+                )
+                .size(14)
+                .color(TEXT_HELP_COLOR),
+            );
+
+        if let AppErrorState::Some(err) = error_state {
+            content = content.push(
+                Text::new(err)
+                    .font(HELVETICA_BOLD)
+                    .size(18)
+                    .color(COLOR_DARK_RED),
+            );
+        }
+        if self.backups.is_empty() {
+            content = content.push(Text::new("There are no backups of this save yet."));
+        }
+
+        for (backup, backup_state) in self.backups.iter().zip(self.backup_states.iter_mut()) {
+            let id = backup.get_id().clone();
+            let mut preview_button =
+                Button::new(&mut backup_state.preview_state, Text::new("Preview"))
+                    .style(DefaultButtonStyle);
+            let mut restore_button =
+                Button::new(&mut backup_state.restore_state, Text::new("Restore"))
+                    .style(DefaultButtonStyle);
+            if !is_busy {
+                preview_button = preview_button.on_press(Message::PreviewBackup(id.clone()));
+                restore_button = restore_button.on_press(Message::RestoreBackup(id.clone()));
+            }
+
+            let mut panel = Column::new().spacing(8).push(
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(
+                        Column::new()
+                            .width(Length::Fill)
+                            .push(
+                                Text::new(format_timestamp(backup.get_created_at()))
+                                    .font(HELVETICA_BOLD)
+                                    .size(18),
+                            )
+                            .push(
+                                Text::new(format!("Before: {}", backup.get_operation())).size(15),
+                            ),
+                    )
+                    .push(preview_button)
+                    .push(Space::with_width(Length::Units(8)))
+                    .push(restore_button),
+            );
+
+            if let Some((preview_id, preview)) = &self.preview {
+                if *preview_id == id {
+                    let preview: Element<Message> = match preview {
+                        None => Text::new("Loading preview...")
+                            .size(15)
+                            .color(TEXT_HELP_COLOR)
+                            .into(),
+                        Some(Err(err)) => Text::new(format!("Could not read the backup.\n{}", err))
+                            .size(15)
+                            .color(TEXT_DANGER_COLOR)
+                            .into(),
+                        Some(Ok(changes)) if changes.is_empty() => {
+                            Text::new("The courses in this backup are the same as in your save.")
+                                .size(15)
+                                .color(TEXT_HELP_COLOR)
+                                .into()
+                        }
+                        Some(Ok(changes)) => changes
+                            .iter()
+                            .fold(
+                                Column::new()
+                                    .spacing(2)
+                                    .push(Text::new("Restoring changes these slots:").size(15)),
+                                |changes, change| {
+                                    changes.push(
+                                        Text::new(format!(
+                                            "Slot {}: {} → {}",
+                                            change.index + 1,
+                                            change.current.as_deref().unwrap_or("empty"),
+                                            change.backup.as_deref().unwrap_or("empty"),
+                                        ))
+                                        .size(15),
+                                    )
+                                },
+                            )
+                            .into(),
+                    };
+                    panel = panel.push(preview);
+                }
+            }
+
+            content = content.push(
+                Container::new(panel)
+                    .style(BackupPanelStyle)
+                    .padding(12)
+                    .width(Length::Fill),
+            );
+        }
+
+        content.into()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct BackupState {
+    preview_state: button::State,
+    restore_state: button::State,
+}
+
+struct BackupPanelStyle;
+
+impl container::StyleSheet for BackupPanelStyle {
+    fn style(&self) -> container::Style {
+        container::Style {
+            background: Some(PANEL_ACTIVE),
+            border_radius: 8,
+            ..container::Style::default()
+        }
+    }
+}
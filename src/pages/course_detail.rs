@@ -0,0 +1,242 @@
+use crate::{
+    course_meta::{get_clear_condition, AutoScroll, CourseTheme, GameStyle},
+    font::*,
+    icon,
+    smmdb::{format_timestamp, Course2Response},
+    styles::*,
+    AppState, Message,
+};
+
+use iced::{
+    button, pick_list, scrollable, Align, Button, Column, Element, Image, Length, PickList, Row,
+    Scrollable, Space, Text,
+};
+use iced_native::widget::image::Handle;
+use std::fmt;
+
+#[derive(Clone, Debug)]
+pub struct CourseDetailPage {
+    course: Course2Response,
+    thumbnail: Option<Vec<u8>>,
+    empty_slots: Vec<Slot>,
+    selected_slot: Option<Slot>,
+    state: scrollable::State,
+    back_state: button::State,
+    upvote_state: button::State,
+    downvote_state: button::State,
+    slot_state: pick_list::State<Slot>,
+    download_state: button::State,
+}
+
+/// A course slot of the open save, numbered starting at 1 in the UI.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Slot(pub usize);
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Slot {}", self.0 + 1)
+    }
+}
+
+impl CourseDetailPage {
+    pub fn new(
+        course: Course2Response,
+        thumbnail: Option<Vec<u8>>,
+        empty_slots: Vec<usize>,
+    ) -> CourseDetailPage {
+        let empty_slots: Vec<Slot> = empty_slots.into_iter().map(Slot).collect();
+        CourseDetailPage {
+            course,
+            thumbnail,
+            selected_slot: empty_slots.first().cloned(),
+            empty_slots,
+            state: scrollable::State::new(),
+            back_state: button::State::new(),
+            upvote_state: button::State::new(),
+            downvote_state: button::State::new(),
+            slot_state: pick_list::State::default(),
+            download_state: button::State::new(),
+        }
+    }
+
+    pub fn get_course(&self) -> &Course2Response {
+        &self.course
+    }
+
+    pub fn set_thumbnail(&mut self, thumbnail: Vec<u8>) {
+        self.thumbnail = Some(thumbnail);
+    }
+
+    pub fn set_own_vote(&mut self, value: i32) {
+        self.course.set_own_vote(value);
+    }
+
+    pub fn select_slot(&mut self, slot: Slot) {
+        self.selected_slot = Some(slot);
+    }
+
+    /// Votes are only offered while signed in, since SMMDB rejects them otherwise.
+    pub fn view(&mut self, state: &AppState, has_apikey: bool) -> Element<Message> {
+        let course = self.course.get_course();
+        let course_header = course.get_header();
+        let id = self.course.get_id().clone();
+
+        let thumbnail: Element<Message> = match &self.thumbnail {
+            Some(thumbnail) => Image::new(Handle::from_memory(thumbnail.clone()))
+                .width(Length::Units(640))
+                .into(),
+            None => Space::new(Length::Units(640), Length::Units(360)).into(),
+        };
+
+        let mut fields = Column::new()
+            .spacing(4)
+            .push(field("SMMDB ID", id.clone()))
+            .push(field("Uploader", self.course.get_uploader().clone()));
+        if let Some(difficulty) = self.course.get_difficulty() {
+            fields = fields.push(field("Difficulty", difficulty.to_string()));
+        }
+        fields = fields
+            .push(field(
+                "Uploaded",
+                format_timestamp(self.course.get_uploaded()),
+            ))
+            .push(field(
+                "Last modified",
+                format_timestamp(self.course.get_last_modified()),
+            ));
+        if let Some(game_style) = GameStyle::from_course(course) {
+            fields = fields.push(field("Game style", game_style.to_string()));
+        }
+        if let Some(theme) = CourseTheme::from_course(course) {
+            fields = fields.push(field("Theme", theme.to_string()));
+        }
+        fields = fields
+            .push(field(
+                "Time limit",
+                format!("{} seconds", course_header.get_time()),
+            ))
+            .push(field(
+                "Clear condition",
+                get_clear_condition(course).unwrap_or_else(|| "None".to_string()),
+            ));
+        if let Some(auto_scroll) = AutoScroll::from_course(course) {
+            fields = fields.push(field("Autoscroll", auto_scroll.to_string()));
+        }
+
+        let mut upvote = Button::new(
+            &mut self.upvote_state,
+            if self.course.get_own_vote() > 0 {
+                icon::UP_ARROW_GREEN.clone()
+            } else {
+                icon::UP_ARROW.clone()
+            }
+            .width(Length::Units(24))
+            .height(Length::Units(24)),
+        )
+        .style(DefaultButtonStyle);
+        let mut downvote = Button::new(
+            &mut self.downvote_state,
+            if self.course.get_own_vote() < 0 {
+                icon::DOWN_ARROW_RED.clone()
+            } else {
+                icon::DOWN_ARROW.clone()
+            }
+            .width(Length::Units(24))
+            .height(Length::Units(24)),
+        )
+        .style(DefaultButtonStyle);
+        if has_apikey {
+            upvote = match self.course.get_own_vote() {
+                n if n > 0 => upvote.on_press(Message::ResetCourseVote(id.clone())),
+                _ => upvote.on_press(Message::UpvoteCourse(id.clone())),
+            };
+            downvote = match self.course.get_own_vote() {
+                n if n < 0 => downvote.on_press(Message::ResetCourseVote(id.clone())),
+                _ => downvote.on_press(Message::DownvoteCourse(id.clone())),
+            };
+        }
+        let votes = Row::new()
+            .align_items(Align::Center)
+            .push(upvote)
+            .push(Space::with_width(Length::Units(12)))
+            .push(Text::new(format!("{}", self.course.get_votes())).size(20))
+            .push(Space::with_width(Length::Units(12)))
+            .push(downvote);
+
+        let download: Element<Message> = if self.empty_slots.is_empty() {
+            Text::new("Your save has no empty slots.")
+                .size(16)
+                .color(TEXT_HELP_COLOR)
+                .into()
+        } else {
+            let mut download_button = Button::new(&mut self.download_state, Text::new("Download"))
+                .style(DefaultButtonStyle);
+            match (state, self.selected_slot) {
+                (AppState::Loading, _) | (AppState::Uploading { .. }, _) | (_, None) => {}
+                (_, Some(slot)) => {
+                    download_button =
+                        download_button.on_press(Message::DownloadCourse(slot.0, id.clone()));
+                }
+            }
+            Row::new()
+                .align_items(Align::Center)
+                .push(
+                    PickList::new(
+                        &mut self.slot_state,
+                        self.empty_slots.clone(),
+                        self.selected_slot,
+                        Message::SelectDetailSlot,
+                    )
+                    .style(DefaultPickListStyle)
+                    .padding(4),
+                )
+                .push(Space::with_width(Length::Units(8)))
+                .push(download_button)
+                .into()
+        };
+
+        Scrollable::new(&mut self.state)
+            .padding(CONTAINER_PADDING)
+            .spacing(LIST_SPACING)
+            .width(Length::Fill)
+            .push(
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(
+                        Text::new(format!("{}", course_header.get_title()))
+                            .font(SMME)
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        Button::new(&mut self.back_state, Text::new("Back"))
+                            .style(DefaultButtonStyle)
+                            .on_press(Message::CloseCourseDetail),
+                    ),
+            )
+            .push(Text::new(format!("{}", course_header.get_description())).size(18))
+            .push(
+                Row::new()
+                    .push(thumbnail)
+                    .push(Space::with_width(Length::Units(20)))
+                    .push(
+                        Column::new()
+                            .spacing(LIST_SPACING)
+                            .push(fields)
+                            .push(votes)
+                            .push(download),
+                    ),
+            )
+            .into()
+    }
+}
+
+fn field<'a>(name: &str, value: String) -> Row<'a, Message> {
+    Row::new()
+        .push(
+            Text::new(format!("{}:", name))
+                .font(HELVETICA_BOLD)
+                .size(16)
+                .width(Length::Units(140)),
+        )
+        .push(Text::new(value).size(16))
+}
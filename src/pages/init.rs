@@ -1,4 +1,6 @@
-use crate::{components::SaveButton, font::*, styles::*, AppErrorState, AppState, Message};
+use crate::{
+    components::SaveButton, font::*, styles::*, AppErrorState, AppState, EmuSave, Message,
+};
 
 use iced::{button, Button, Column, Element, Length, Space, Text};
 
@@ -9,10 +11,10 @@ pub struct InitPage {
 }
 
 impl InitPage {
-    pub fn new(save_buttons: Vec<SaveButton>) -> InitPage {
+    pub fn new(saves: Vec<EmuSave>) -> InitPage {
         InitPage {
             open_custom_save: button::State::new(),
-            save_buttons,
+            save_buttons: saves.into_iter().map(SaveButton::new).collect(),
         }
     }
 }
@@ -1,9 +1,16 @@
+mod backups;
+mod course_detail;
 mod init;
 mod save;
 mod settings;
 
+pub use backups::BackupsPage;
+pub use course_detail::{CourseDetailPage, Slot};
 pub use init::InitPage;
-pub use save::SavePage;
+pub use save::{
+    ConflictResolution, FillSummary, PendingChange, SaveOverlay, SavePage, SaveSide, SaveWrite,
+    Transfer, TransferMode,
+};
 pub use settings::SettingsPage;
 
 #[derive(Clone, Debug)]
@@ -12,3 +19,14 @@ pub enum Page {
     Save(SavePage),
     Settings(SettingsPage),
 }
+
+impl Page {
+    /// Returns the open save, even if another page is shown on top of it.
+    pub fn get_save_page_mut(&mut self) -> Option<&mut SavePage> {
+        match self {
+            Page::Save(save_page) => Some(save_page),
+            Page::Settings(settings_page) => settings_page.get_prev_page_mut().get_save_page_mut(),
+            Page::Init(_) => None,
+        }
+    }
+}
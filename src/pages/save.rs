@@ -1,71 +1,1112 @@
 use crate::{
-    widgets::{SaveWidget, SmmdbWidget},
-    AppState, Smmdb,
+    backups::{get_slot_contents, Backup, BackupStore},
+    course_file::get_encrypted_course_data,
+    download_queue::DownloadQueue,
+    history::{History, SlotChange, SlotState},
+    library::Library,
+    pages::{BackupsPage, CourseDetailPage, Slot},
+    smmdb::format_timestamp,
+    upload_registry::UploadRegistry,
+    widgets::{LibraryWidget, SaveWidget, SmmdbWidget},
+    AppErrorState, AppState, Smmdb,
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use iced::{Element, Row};
+use smmdb_lib::proto::SMM2Course::SMM2Course;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Clone, Debug)]
+pub enum SaveOverlay {
+    CourseDetail(CourseDetailPage),
+    Backups(BackupsPage),
+}
 
 #[derive(Clone, Debug)]
 pub struct SavePage {
     save: smmdb_lib::Save,
     display_name: String,
+    location: PathBuf,
+    /// Shared with the other open save, so that neither drops the uploads of the other.
+    uploads: Arc<Mutex<UploadRegistry>>,
+    /// SMMDB ids of the slots as shown, which only go to the registry once they are written.
+    smmdb_ids: Vec<Option<String>>,
+    backups: BackupStore,
+    history: History,
+    staged: Option<StagedChanges>,
+    pending: Option<PendingWrite>,
+    other: Option<Box<SavePage>>,
+    overlay: Option<SaveOverlay>,
     save_widget: SaveWidget,
     smmdb_widget: SmmdbWidget,
+    library_widget: LibraryWidget,
 }
 
 impl SavePage {
-    pub fn new(save: smmdb_lib::Save, display_name: String) -> SavePage {
+    pub fn new(
+        save: smmdb_lib::Save,
+        display_name: String,
+        location: PathBuf,
+        uploads: Arc<Mutex<UploadRegistry>>,
+        backup_retention: usize,
+    ) -> SavePage {
+        let smmdb_ids = uploads
+            .lock()
+            .unwrap()
+            .get_slots(&location, save.get_own_courses().len());
         SavePage {
-            save_widget: SaveWidget::new(&save),
+            save_widget: SaveWidget::new(&save, smmdb_ids.clone()),
             save,
             display_name,
+            backups: BackupStore::new(location.clone(), backup_retention),
+            location,
+            uploads,
+            smmdb_ids,
+            history: History::default(),
+            staged: None,
+            pending: None,
+            other: None,
+            overlay: None,
             smmdb_widget: SmmdbWidget::new(),
+            library_widget: LibraryWidget::new(),
         }
     }
 
     pub fn view<'a>(
         &'a mut self,
         state: &AppState,
+        error_state: &AppErrorState,
         smmdb: &'a mut Smmdb,
+        library: &Library,
+        downloads: &DownloadQueue,
+        has_apikey: bool,
     ) -> Element<crate::Message> {
+        match &mut self.overlay {
+            Some(SaveOverlay::CourseDetail(course_detail_page)) => {
+                return course_detail_page.view(state, has_apikey)
+            }
+            Some(SaveOverlay::Backups(backups_page)) => {
+                return backups_page.view(state, error_state)
+            }
+            None => {}
+        }
+        let slots: Vec<Slot> = (0..self.save.get_own_courses().len())
+            .filter(|index| !downloads.is_reserved(*index))
+            .map(Slot)
+            .collect();
         Row::new()
-            .push(self.save_widget.view(state, &self.display_name))
-            .push(self.smmdb_widget.view(state, smmdb))
+            .push(
+                self.save_widget.view(
+                    state,
+                    error_state,
+                    &self.display_name,
+                    has_apikey,
+                    downloads,
+                    &self.history,
+                    self.staged
+                        .as_ref()
+                        .map(|staged| staged.operations.as_slice()),
+                ),
+            )
+            .push(match &mut self.other {
+                Some(other) => other.save_widget.view(
+                    state,
+                    &AppErrorState::None,
+                    &other.display_name,
+                    false,
+                    downloads,
+                    &other.history,
+                    None,
+                ),
+                None => self.smmdb_widget.view(state, smmdb),
+            })
+            .push(
+                self.library_widget
+                    .view(state, library, &slots, self.staged.is_some()),
+            )
             .into()
     }
 
-    pub async fn swap_courses(&mut self, first: u8, second: u8) -> Result<()> {
-        self.save.swap_course(first, second)?;
-        self.save
-            .save()
-            .await
-            .map_err(|err| -> anyhow::Error { err.into() })?;
+    pub fn open_overlay(&mut self, overlay: SaveOverlay) {
+        self.overlay = Some(overlay);
+    }
+
+    pub fn close_overlay(&mut self) {
+        self.overlay = None;
+    }
+
+    pub fn has_overlay(&self) -> bool {
+        self.overlay.is_some()
+    }
+
+    pub fn get_course_detail_mut(&mut self) -> Option<&mut CourseDetailPage> {
+        match &mut self.overlay {
+            Some(SaveOverlay::CourseDetail(course_detail_page)) => Some(course_detail_page),
+            _ => None,
+        }
+    }
+
+    pub fn get_backups_page_mut(&mut self) -> Option<&mut BackupsPage> {
+        match &mut self.overlay {
+            Some(SaveOverlay::Backups(backups_page)) => Some(backups_page),
+            _ => None,
+        }
+    }
+
+    pub fn has_focused_text_input(&self) -> bool {
+        self.smmdb_widget.has_focus() || self.library_widget.has_focus()
+    }
+
+    pub fn toggle_advanced_search(&mut self) {
+        self.smmdb_widget.toggle_advanced_search();
+    }
+
+    pub fn get_fill_limit(&self) -> Option<usize> {
+        self.smmdb_widget.get_fill_limit()
+    }
+
+    pub fn set_fill_limit(&mut self, fill_limit: &str) {
+        self.smmdb_widget.set_fill_limit(fill_limit);
+    }
+
+    pub fn set_fill_summary(&mut self, fill_summary: Option<FillSummary>) {
+        self.smmdb_widget.set_fill_summary(fill_summary);
+    }
+
+    pub fn set_library_filter(&mut self, filter: String) {
+        self.library_widget.set_filter(filter);
+    }
+
+    pub fn set_library_tag_input(&mut self, id: &str, tag_input: String) {
+        self.library_widget.set_tag_input(id, tag_input);
+    }
+
+    pub fn take_library_tag_input(&mut self, id: &str) -> String {
+        self.library_widget.take_tag_input(id)
+    }
+
+    pub fn select_library_slot(&mut self, id: &str, slot: Slot) {
+        self.library_widget.select_slot(id, slot);
+    }
+
+    pub fn get_history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn swap_courses(&mut self, first: u8, second: u8) -> Result<Option<SaveWrite>> {
+        let operation = format!("Swap slots {} and {}", first + 1, second + 1);
+        let change = SlotChange::Swap(first, second);
+        self.apply(
+            operation.clone(),
+            &change,
+            HistoryUpdate::Push(operation, change.clone()),
+        )
+    }
+
+    pub fn move_course(&mut self, from: u8, to: u8) -> Result<Option<SaveWrite>> {
+        if from == to {
+            return Ok(None);
+        }
+        let operation = format!("Move slot {} to slot {}", from + 1, to + 1);
+        let change = SlotChange::Move(from, to);
+        self.apply(
+            operation.clone(),
+            &change,
+            HistoryUpdate::Push(operation, change.clone()),
+        )
+    }
+
+    pub fn add_course(
+        &mut self,
+        index: u8,
+        course: smmdb_lib::Course2,
+    ) -> Result<Option<SaveWrite>> {
+        let operation = format!(
+            "Add {} to slot {}",
+            course.get_course().get_header().get_title(),
+            index + 1
+        );
+        self.replace(operation, vec![index], |save| {
+            save.add_course(index, course)?;
+            Ok(())
+        })
+    }
+
+    /// Adds several courses with a single write, either all of them or none.
+    pub fn add_courses(
+        &mut self,
+        courses: Vec<(u8, smmdb_lib::Course2)>,
+    ) -> Result<Option<SaveWrite>> {
+        if courses.is_empty() {
+            return Ok(None);
+        }
+        let len = self.save.get_own_courses().len();
+        for (position, (index, _)) in courses.iter().enumerate() {
+            if *index as usize >= len {
+                bail!("Slot {} does not exist.", index + 1);
+            }
+            if courses[..position].iter().any(|(other, _)| other == index) {
+                bail!("Slot {} would be filled twice.", index + 1);
+            }
+        }
+        let indices: Vec<u8> = courses.iter().map(|(index, _)| *index).collect();
+        let operation = format!("Add {} courses", indices.len());
+        self.replace(operation, indices, |save| {
+            for (index, course) in courses {
+                save.add_course(index, course)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn delete_course(&mut self, index: u8) -> Result<Option<SaveWrite>> {
+        let operation = match self.get_course(index as usize) {
+            Some(course) => format!(
+                "Delete {} from slot {}",
+                course.get_course().get_header().get_title(),
+                index + 1
+            ),
+            None => format!("Delete slot {}", index + 1),
+        };
+        self.replace(operation, vec![index], |save| {
+            save.remove_course(index)?;
+            Ok(())
+        })
+    }
+
+    pub fn undo(&mut self) -> Result<Option<SaveWrite>> {
+        let operation = match self.history.take_undo() {
+            Some(operation) => operation,
+            None => return Ok(None),
+        };
+        let res = self.apply(
+            format!("Undo: {}", operation.get_description()),
+            operation.get_change(),
+            HistoryUpdate::Undo,
+        );
+        if res.is_err() {
+            self.history.revert_undo();
+        }
+        res
+    }
+
+    pub fn redo(&mut self) -> Result<Option<SaveWrite>> {
+        let operation = match self.history.take_redo() {
+            Some(operation) => operation,
+            None => return Ok(None),
+        };
+        let res = self.apply(
+            format!("Redo: {}", operation.get_description()),
+            operation.get_change(),
+            HistoryUpdate::Redo,
+        );
+        if res.is_err() {
+            self.history.revert_redo();
+        }
+        res
+    }
+
+    fn apply(
+        &mut self,
+        operation: String,
+        change: &SlotChange,
+        history: HistoryUpdate,
+    ) -> Result<Option<SaveWrite>> {
+        let mut save = self.save.clone();
+        let mut smmdb_ids = self.smmdb_ids.clone();
+        match change {
+            SlotChange::Swap(first, second) => {
+                save.swap_course(*first, *second)?;
+                smmdb_ids.swap(*first as usize, *second as usize);
+            }
+            SlotChange::Move(from, to) => {
+                for (first, second) in get_shift_swaps(*from, *to) {
+                    save.swap_course(first, second)?;
+                    smmdb_ids.swap(first as usize, second as usize);
+                }
+            }
+            SlotChange::Replace(slots) => {
+                for (index, _, after) in slots {
+                    if save.get_own_courses()[*index as usize].is_some() {
+                        save.remove_course(*index)?;
+                    }
+                    if let Some(course) = &after.course {
+                        save.add_course(*index, course.get_course().clone())?;
+                    }
+                    smmdb_ids[*index as usize] = after.smmdb_id.clone();
+                }
+            }
+        }
+        Ok(self.commit(operation, save, smmdb_ids, history))
+    }
+
+    fn replace<F>(
+        &mut self,
+        operation: String,
+        indices: Vec<u8>,
+        update: F,
+    ) -> Result<Option<SaveWrite>>
+    where
+        F: FnOnce(&mut smmdb_lib::Save) -> Result<()>,
+    {
+        let mut save = self.save.clone();
+        update(&mut save)?;
+        let mut smmdb_ids = self.smmdb_ids.clone();
+        let slots = indices
+            .into_iter()
+            .map(|index| {
+                smmdb_ids[index as usize] = None;
+                let after = SlotState {
+                    course: save.get_own_courses()[index as usize].clone(),
+                    smmdb_id: None,
+                };
+                (index, self.get_slot_state(index), after)
+            })
+            .collect();
+        let history = HistoryUpdate::Push(operation.clone(), SlotChange::Replace(slots));
+        Ok(self.commit(operation, save, smmdb_ids, history))
+    }
+
+    fn get_slot_state(&self, index: u8) -> SlotState {
+        SlotState {
+            course: self.save.get_own_courses()[index as usize].clone(),
+            smmdb_id: self.smmdb_ids[index as usize].clone(),
+        }
+    }
+
+    /// All changes to the save folder go through here.
+    ///
+    /// Written changes are only shown by `finish_write`, staged changes right away.
+    fn commit(
+        &mut self,
+        operation: String,
+        save: smmdb_lib::Save,
+        smmdb_ids: Vec<Option<String>>,
+        history: HistoryUpdate,
+    ) -> Option<SaveWrite> {
+        if let Some(staged) = &mut self.staged {
+            staged.operations.push(operation);
+            self.show_change(save, smmdb_ids, history);
+            return None;
+        }
+        self.pending = Some(PendingWrite::Change { smmdb_ids, history });
+        Some(SaveWrite {
+            save,
+            backups: self.backups.clone(),
+            operation,
+        })
+    }
+
+    fn show_change(
+        &mut self,
+        save: smmdb_lib::Save,
+        smmdb_ids: Vec<Option<String>>,
+        history: HistoryUpdate,
+    ) {
+        self.save = save;
+        self.smmdb_ids = smmdb_ids;
+        if let HistoryUpdate::Push(operation, change) = history {
+            self.history.push(operation, change);
+        }
+        self.save_smmdb_ids();
         self.generate_course_panels();
+    }
+
+    pub fn finish_write(
+        &mut self,
+        side: SaveSide,
+        res: Result<smmdb_lib::Save, String>,
+    ) -> Result<(), String> {
+        let save_page = self
+            .get_side_mut(side)
+            .ok_or_else(|| "The save has been closed.".to_string())?;
+        let pending = match save_page.pending.take() {
+            Some(pending) => pending,
+            None => return res.map(|_| ()),
+        };
+        match (pending, res) {
+            (PendingWrite::Change { smmdb_ids, history }, Ok(save)) => {
+                save_page.show_change(save, smmdb_ids, history);
+            }
+            (PendingWrite::StagedChanges, Ok(save)) => {
+                save_page.save = save;
+                save_page.staged = Some(StagedChanges::new(save_page));
+                save_page.write_smmdb_ids();
+                save_page.generate_course_panels();
+            }
+            (pending, Err(err)) => {
+                match pending {
+                    PendingWrite::Change {
+                        history: HistoryUpdate::Undo,
+                        ..
+                    } => save_page.history.revert_undo(),
+                    PendingWrite::Change {
+                        history: HistoryUpdate::Redo,
+                        ..
+                    } => save_page.history.revert_redo(),
+                    _ => {}
+                }
+                return Err(err);
+            }
+        }
         Ok(())
     }
 
-    pub async fn add_course(&mut self, index: u8, course: smmdb_lib::Course2) -> Result<()> {
-        self.save.add_course(index, course)?;
-        self.save
-            .save()
-            .await
-            .map_err(|err| -> anyhow::Error { err.into() })?;
+    pub fn get_side_mut(&mut self, side: SaveSide) -> Option<&mut SavePage> {
+        match side {
+            SaveSide::Primary => Some(self),
+            SaveSide::Secondary => self.other.as_deref_mut(),
+        }
+    }
+
+    /// Turns staged mode on or off. It stays on, while there are pending changes.
+    pub fn set_staged(&mut self, staged: bool) {
+        if staged {
+            if self.staged.is_none() {
+                self.staged = Some(StagedChanges::new(self));
+            }
+        } else if !self.has_pending_changes() {
+            self.staged = None;
+        }
+        self.generate_course_panels();
+    }
+
+    pub fn is_staged(&self) -> bool {
+        self.staged.is_some()
+    }
+
+    pub fn has_pending_changes(&self) -> bool {
+        self.staged
+            .as_ref()
+            .map_or(false, |staged| !staged.operations.is_empty())
+    }
+
+    pub fn apply_staged_changes(&mut self) -> Option<SaveWrite> {
+        let operation = match &self.staged {
+            Some(staged) => match staged.operations.as_slice() {
+                [] => return None,
+                [operation] => operation.clone(),
+                operations => format!("Apply {} changes", operations.len()),
+            },
+            None => return None,
+        };
+        self.pending = Some(PendingWrite::StagedChanges);
+        Some(SaveWrite {
+            save: self.save.clone(),
+            backups: self.backups.clone(),
+            operation,
+        })
+    }
+
+    pub fn discard_staged_changes(&mut self) {
+        if let Some(staged) = self.staged.take() {
+            self.save = staged.save;
+            self.smmdb_ids = self
+                .uploads
+                .lock()
+                .unwrap()
+                .get_slots(&self.location, self.save.get_own_courses().len());
+            self.history = staged.history;
+            self.staged = Some(StagedChanges::new(self));
+        }
         self.generate_course_panels();
+    }
+
+    pub fn get_backups(&self) -> &BackupStore {
+        &self.backups
+    }
+
+    /// The save has to be loaded again afterwards.
+    pub fn restore_backup(&self, backup: &Backup) -> Result<()> {
+        self.backups.restore(
+            backup.get_id(),
+            &format!(
+                "Restore backup from {}",
+                format_timestamp(backup.get_created_at())
+            ),
+        )
+    }
+
+    pub fn set_backup_retention(&mut self, backup_retention: usize) {
+        self.backups.set_retention(backup_retention);
+        if let Some(other) = &mut self.other {
+            other.set_backup_retention(backup_retention);
+        }
+    }
+
+    pub fn open_other_save(
+        &mut self,
+        save: smmdb_lib::Save,
+        display_name: String,
+        location: PathBuf,
+        backup_retention: usize,
+    ) -> Result<()> {
+        if location == self.location {
+            bail!("This save is already open.");
+        }
+        let mut other = SavePage::new(
+            save,
+            display_name,
+            location,
+            self.uploads.clone(),
+            backup_retention,
+        );
+        other.save_widget.set_side(SaveSide::Secondary);
+        self.other = Some(Box::new(other));
+        self.save_widget.set_has_other_save(true);
         Ok(())
     }
 
-    pub async fn delete_course(&mut self, index: u8) -> Result<()> {
-        self.save.remove_course(index)?;
+    pub fn close_other_save(&mut self) {
+        self.other = None;
+        self.save_widget.set_has_other_save(false);
+    }
+
+    pub fn is_slot_occupied(&self, side: SaveSide, index: usize) -> bool {
+        match side {
+            SaveSide::Primary => self.get_course(index).is_some(),
+            SaveSide::Secondary => self
+                .other
+                .as_ref()
+                .map_or(false, |other| other.get_course(index).is_some()),
+        }
+    }
+
+    /// Courses are only moved while neither save has staged changes, as the course would be
+    /// written to one save and only staged in the other. The moved course is deleted from its
+    /// save by the caller, once the copy has been written.
+    pub fn transfer_course(
+        &mut self,
+        transfer: Transfer,
+        to: usize,
+        resolution: Option<ConflictResolution>,
+        downloads: &DownloadQueue,
+    ) -> Result<(SaveSide, Option<SaveWrite>)> {
+        let is_staged = |save_page: &SavePage| save_page.staged.is_some();
+        if transfer.mode == TransferMode::Move
+            && (is_staged(self) || self.other.as_deref().map_or(false, is_staged))
+        {
+            bail!("Apply the staged changes before moving courses between saves.");
+        }
+        let mut other = match self.other.take() {
+            Some(other) => other,
+            None => bail!("No second save is open."),
+        };
+        let res = match transfer.from {
+            SaveSide::Primary => {
+                transfer_between(self, &mut other, transfer, to, resolution, &|_| false)
+                    .map(|write| (SaveSide::Secondary, write))
+            }
+            SaveSide::Secondary => {
+                transfer_between(&other, self, transfer, to, resolution, &|index| {
+                    downloads.is_reserved(index)
+                })
+                .map(|write| (SaveSide::Primary, write))
+            }
+        };
+        self.other = Some(other);
+        res
+    }
+
+    pub fn get_save(&self) -> &smmdb_lib::Save {
+        &self.save
+    }
+
+    pub fn get_display_name(&self) -> &String {
+        &self.display_name
+    }
+
+    pub fn get_location(&self) -> &PathBuf {
+        &self.location
+    }
+
+    pub fn get_course(&self, index: usize) -> Option<&smmdb_lib::Course2> {
         self.save
-            .save()
-            .await
-            .map_err(|err| -> anyhow::Error { err.into() })?;
+            .get_own_courses()
+            .get(index)?
+            .as_ref()
+            .map(|course| course.get_course())
+    }
+
+    pub fn get_smmdb_id(&self, index: usize) -> Option<&String> {
+        self.smmdb_ids.get(index)?.as_ref()
+    }
+
+    pub fn get_course_data(&self, index: usize) -> Option<Vec<u8>> {
+        self.save.get_own_courses()[index]
+            .as_ref()
+            .map(|course| get_encrypted_course_data(course.get_course()))
+    }
+
+    pub fn get_empty_slots(&self) -> Vec<usize> {
+        self.save
+            .get_own_courses()
+            .iter()
+            .enumerate()
+            .filter(|(_, course)| course.is_none())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Whether a course is already in the save, either uploaded from it under the given
+    /// SMMDB id or with the same title and description.
+    pub fn contains_course(&self, smmdb_id: &str, course: &SMM2Course) -> bool {
+        let slots: Vec<(Option<&String>, Option<&SMM2Course>)> = self
+            .save
+            .get_own_courses()
+            .iter()
+            .enumerate()
+            .map(|(index, saved_course)| {
+                (
+                    self.get_smmdb_id(index),
+                    saved_course
+                        .as_ref()
+                        .map(|saved_course| saved_course.get_course().get_course()),
+                )
+            })
+            .collect();
+        is_in_slots(&slots, smmdb_id, course)
+    }
+
+    /// While changes are staged, the upload is remembered for the save on disk as well,
+    /// if the course is already in it.
+    pub fn set_smmdb_id(&mut self, index: usize, smmdb_id: String) {
+        let saved_index = match self.get_pending_changes().get(index) {
+            Some(None) => Some(index),
+            Some(Some(PendingChange::Moved(from))) => Some(*from),
+            _ => None,
+        };
+        self.smmdb_ids[index] = Some(smmdb_id.clone());
+        if let Some(saved_index) = saved_index {
+            self.update_registry(|uploads| uploads.set(&self.location, saved_index, smmdb_id));
+        }
         self.generate_course_panels();
-        Ok(())
+    }
+
+    pub fn forget_smmdb_id(&mut self, smmdb_id: &str) {
+        self.clear_smmdb_id(smmdb_id);
+        if let Some(other) = &mut self.other {
+            other.clear_smmdb_id(smmdb_id);
+        }
+    }
+
+    fn clear_smmdb_id(&mut self, smmdb_id: &str) {
+        let pending_ids = match &mut self.pending {
+            Some(PendingWrite::Change { smmdb_ids, .. }) => Some(smmdb_ids),
+            _ => None,
+        };
+        for id in self
+            .smmdb_ids
+            .iter_mut()
+            .chain(pending_ids.into_iter().flatten())
+        {
+            if id.as_deref() == Some(smmdb_id) {
+                *id = None;
+            }
+        }
+        self.generate_course_panels();
+    }
+
+    fn save_smmdb_ids(&self) {
+        if self.staged.is_none() {
+            self.write_smmdb_ids();
+        }
+    }
+
+    fn write_smmdb_ids(&self) {
+        self.update_registry(|uploads| uploads.set_slots(&self.location, &self.smmdb_ids));
+    }
+
+    fn update_registry<F: FnOnce(&mut UploadRegistry)>(&self, update: F) {
+        let mut uploads = self.uploads.lock().unwrap();
+        update(&mut uploads);
+        if let Err(err) = uploads.save() {
+            log::warn!("Could not save uploaded courses: {}", err);
+        }
     }
 
     fn generate_course_panels(&mut self) {
-        self.save_widget.generate_course_panels(&self.save);
+        self.save_widget.generate_course_panels(
+            &self.save,
+            self.smmdb_ids.clone(),
+            self.get_pending_changes(),
+        );
+    }
+
+    fn get_pending_changes(&self) -> Vec<Option<PendingChange>> {
+        let len = self.save.get_own_courses().len();
+        let staged = match &self.staged {
+            Some(staged) if !staged.operations.is_empty() => staged,
+            _ => return vec![None; len],
+        };
+        let original = get_slot_contents(&staged.save);
+        get_slot_contents(&self.save)
+            .into_iter()
+            .enumerate()
+            .map(|(index, current)| {
+                let before = original.get(index).cloned().flatten();
+                match current {
+                    current if current == before => None,
+                    None => Some(PendingChange::Removed),
+                    Some(current) => {
+                        match original
+                            .iter()
+                            .position(|slot| slot.as_ref() == Some(&current))
+                        {
+                            Some(from) => Some(PendingChange::Moved(from)),
+                            None if before.is_none() => Some(PendingChange::Added),
+                            None => Some(PendingChange::Replaced),
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct StagedChanges {
+    save: smmdb_lib::Save,
+    history: History,
+    operations: Vec<String>,
+}
+
+impl StagedChanges {
+    fn new(save_page: &SavePage) -> StagedChanges {
+        StagedChanges {
+            save: save_page.save.clone(),
+            history: save_page.history.clone(),
+            operations: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SaveWrite {
+    save: smmdb_lib::Save,
+    backups: BackupStore,
+    operation: String,
+}
+
+impl SaveWrite {
+    /// Nothing is changed, if the backup cannot be taken.
+    pub async fn write(mut self) -> Result<smmdb_lib::Save, String> {
+        self.backups.create(&self.operation).map_err(|err| {
+            format!(
+                "Could not back up the save, it was left unchanged.\n{}",
+                err
+            )
+        })?;
+        self.save
+            .save()
+            .await
+            .map_err(|err| anyhow::Error::from(err).to_string())?;
+        Ok(self.save)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum PendingWrite {
+    Change {
+        smmdb_ids: Vec<Option<String>>,
+        history: HistoryUpdate,
+    },
+    StagedChanges,
+}
+
+#[derive(Clone, Debug)]
+enum HistoryUpdate {
+    Push(String, SlotChange),
+    /// The change has been taken from the history and is put back, if it fails.
+    Undo,
+    Redo,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SaveSide {
+    Primary,
+    Secondary,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransferMode {
+    Copy,
+    Move,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transfer {
+    pub from: SaveSide,
+    pub index: usize,
+    pub mode: TransferMode,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConflictResolution {
+    Replace,
+    /// Uses the next empty slot after the target, or before it if there is none.
+    NextEmptySlot,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PendingChange {
+    Added,
+    Removed,
+    Replaced,
+    Moved(usize),
+}
+
+fn transfer_between(
+    source: &SavePage,
+    target: &mut SavePage,
+    transfer: Transfer,
+    to: usize,
+    resolution: Option<ConflictResolution>,
+    is_reserved: &dyn Fn(usize) -> bool,
+) -> Result<Option<SaveWrite>> {
+    let course = source
+        .get_course(transfer.index)
+        .cloned()
+        .ok_or_else(|| anyhow!("Slot {} is empty.", transfer.index + 1))?;
+    if is_reserved(to) {
+        bail!("Slot {} is reserved by a download.", to + 1);
+    }
+    let to = match resolution {
+        _ if target.get_course(to).is_none() => to,
+        Some(ConflictResolution::Replace) => to,
+        Some(ConflictResolution::NextEmptySlot) => target
+            .get_empty_slots()
+            .into_iter()
+            .filter(|index| !is_reserved(*index))
+            .min_by_key(|index| (*index < to, *index))
+            .ok_or_else(|| anyhow!("The save has no empty slot left."))?,
+        None => bail!("Slot {} is not empty.", to + 1),
+    };
+    target.add_course(to as u8, course)
+}
+
+/// Returns the swaps of neighbouring slots, which move a course from one slot to another
+/// and shift the courses in between towards the slot it came from.
+fn get_shift_swaps(from: u8, to: u8) -> Vec<(u8, u8)> {
+    if from < to {
+        (from..to).map(|index| (index, index + 1)).collect()
+    } else {
+        (to..from).rev().map(|index| (index + 1, index)).collect()
+    }
+}
+
+fn is_in_slots(
+    slots: &[(Option<&String>, Option<&SMM2Course>)],
+    smmdb_id: &str,
+    course: &SMM2Course,
+) -> bool {
+    let header = course.get_header();
+    slots.iter().any(|(id, saved_course)| {
+        id.map(|id| id.as_str()) == Some(smmdb_id)
+            || saved_course.map_or(false, |saved_course| {
+                let saved_header = saved_course.get_header();
+                saved_header.get_title() == header.get_title()
+                    && saved_header.get_description() == header.get_description()
+            })
+    })
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FillSummary {
+    pub installed: Vec<(usize, String)>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+impl FillSummary {
+    /// Starts a summary with the courses, which could not be fetched,
+    /// and returns the others ordered by slot.
+    pub fn sort_fetched<T>(
+        fetched: Vec<(usize, String, Result<T, String>)>,
+        skipped: Vec<String>,
+    ) -> (FillSummary, Vec<(usize, String, T)>) {
+        let mut fill_summary = FillSummary {
+            skipped,
+            ..FillSummary::default()
+        };
+        let mut courses = vec![];
+        for (index, title, course) in fetched {
+            match course {
+                Ok(course) => courses.push((index, title, course)),
+                Err(err) => fill_summary.failed.push((title, err)),
+            }
+        }
+        courses.sort_by_key(|(index, _, _)| *index);
+        (fill_summary, courses)
+    }
+
+    pub fn finish(&mut self, courses: Vec<(usize, String)>, res: Result<(), String>) {
+        match res {
+            Ok(()) => self.installed = courses,
+            Err(err) => self
+                .failed
+                .extend(courses.into_iter().map(|(_, title)| (title, err.clone()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn course(title: &str, description: &str) -> SMM2Course {
+        let mut course = SMM2Course::new();
+        course.mut_header().set_title(title.to_string());
+        course.mut_header().set_description(description.to_string());
+        course
+    }
+
+    fn apply_change(slots: &mut Vec<char>, change: &SlotChange) {
+        match change {
+            SlotChange::Swap(first, second) => slots.swap(*first as usize, *second as usize),
+            SlotChange::Move(from, to) => {
+                for (first, second) in get_shift_swaps(*from, *to) {
+                    slots.swap(first as usize, second as usize);
+                }
+            }
+            SlotChange::Replace(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn shifts_courses_back_when_moving_forward() {
+        assert_eq!(get_shift_swaps(1, 4), vec![(1, 2), (2, 3), (3, 4)]);
+
+        let mut slots: Vec<char> = "abcdef".chars().collect();
+        apply_change(&mut slots, &SlotChange::Move(1, 4));
+        assert_eq!(slots.into_iter().collect::<String>(), "acdebf");
+    }
+
+    #[test]
+    fn shifts_courses_forward_when_moving_back() {
+        assert_eq!(get_shift_swaps(4, 1), vec![(4, 3), (3, 2), (2, 1)]);
+
+        let mut slots: Vec<char> = "abcdef".chars().collect();
+        apply_change(&mut slots, &SlotChange::Move(4, 1));
+        assert_eq!(slots.into_iter().collect::<String>(), "aebcdf");
+    }
+
+    #[test]
+    fn moves_to_neighbouring_slots_with_a_single_swap() {
+        assert_eq!(get_shift_swaps(2, 3), vec![(2, 3)]);
+        assert_eq!(get_shift_swaps(3, 2), vec![(3, 2)]);
+        assert!(get_shift_swaps(2, 2).is_empty());
+    }
+
+    #[test]
+    fn undoing_a_move_restores_every_slot() {
+        let original: Vec<char> = "abcdef".chars().collect();
+        for from in 0..6 {
+            for to in 0..6 {
+                let mut history = History::default();
+                let mut slots = original.clone();
+                let change = SlotChange::Move(from, to);
+                apply_change(&mut slots, &change);
+                history.push("Move".to_string(), change);
+
+                let undo = history.take_undo().unwrap();
+                apply_change(&mut slots, undo.get_change());
+                assert_eq!(slots, original, "Moving {} to {}", from, to);
+
+                let redo = history.take_redo().unwrap();
+                apply_change(&mut slots, redo.get_change());
+                let mut moved = original.clone();
+                let course = moved.remove(from as usize);
+                moved.insert(to as usize, course);
+                assert_eq!(slots, moved, "Moving {} to {}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn finds_courses_by_smmdb_id() {
+        let smmdb_id = "uploaded".to_string();
+        let saved = course("Saved", "");
+        let slots = vec![(None, None), (Some(&smmdb_id), Some(&saved))];
+
+        assert!(is_in_slots(&slots, "uploaded", &course("Renamed", "")));
+        assert!(!is_in_slots(&slots, "other", &course("Renamed", "")));
+    }
+
+    #[test]
+    fn finds_courses_by_title_and_description() {
+        let saved = course("Saved", "Description");
+        let slots = vec![(None, Some(&saved)), (None, None)];
+
+        assert!(is_in_slots(&slots, "id", &course("Saved", "Description")));
+        assert!(!is_in_slots(&slots, "id", &course("Saved", "Other")));
+        assert!(!is_in_slots(&slots, "id", &course("Other", "Description")));
+        assert!(!is_in_slots(&[], "id", &course("Saved", "Description")));
+    }
+
+    #[test]
+    fn sorts_fetched_courses_by_slot() {
+        let fetched = vec![
+            (5, "Fifth".to_string(), Ok(5)),
+            (2, "Second".to_string(), Err("Not found".to_string())),
+            (1, "First".to_string(), Ok(1)),
+        ];
+
+        let (fill_summary, courses) =
+            FillSummary::sort_fetched(fetched, vec!["Skipped".to_string()]);
+
+        assert_eq!(
+            courses,
+            vec![(1, "First".to_string(), 1), (5, "Fifth".to_string(), 5)]
+        );
+        assert_eq!(fill_summary.skipped, vec!["Skipped"]);
+        assert_eq!(
+            fill_summary.failed,
+            vec![("Second".to_string(), "Not found".to_string())]
+        );
+        assert!(fill_summary.installed.is_empty());
+    }
+
+    #[test]
+    fn summarizes_installed_courses() {
+        let mut fill_summary = FillSummary::default();
+
+        fill_summary.finish(vec![(1, "First".to_string())], Ok(()));
+
+        assert_eq!(fill_summary.installed, vec![(1, "First".to_string())]);
+        assert!(fill_summary.failed.is_empty());
+    }
+
+    #[test]
+    fn summarizes_courses_as_failed_if_the_save_is_not_written() {
+        let (mut fill_summary, courses) = FillSummary::sort_fetched(
+            vec![
+                (1, "First".to_string(), Ok(())),
+                (2, "Second".to_string(), Err("Not found".to_string())),
+                (3, "Third".to_string(), Ok(())),
+            ],
+            vec![],
+        );
+
+        fill_summary.finish(
+            courses
+                .into_iter()
+                .map(|(index, title, _)| (index, title))
+                .collect(),
+            Err("Disk full".to_string()),
+        );
+
+        assert!(fill_summary.installed.is_empty());
+        assert_eq!(
+            fill_summary.failed,
+            vec![
+                ("Second".to_string(), "Not found".to_string()),
+                ("First".to_string(), "Disk full".to_string()),
+                ("Third".to_string(), "Disk full".to_string()),
+            ]
+        );
     }
 }
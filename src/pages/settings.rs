@@ -1,7 +1,10 @@
-use crate::{font::*, styles::*, AppErrorState, Message, Page, Settings};
+use crate::{
+    font::*, settings::EndpointProfile, styles::*, AppErrorState, Message, Page, Settings,
+};
 
 use iced::{
-    button, text_input, Button, Column, Element, Length, Row, Rule, Space, Text, TextInput,
+    button, pick_list, text_input, Button, Column, Element, Length, PickList, Row, Rule, Space,
+    Text, TextInput,
 };
 
 #[derive(Clone, Debug)]
@@ -11,6 +14,10 @@ pub struct SettingsPage {
     has_changed: bool,
     prev_page: Box<Page>,
     apikey: text_input::State,
+    endpoint_profile: pick_list::State<EndpointProfile>,
+    base_url: text_input::State,
+    backup_retention: String,
+    backup_retention_state: text_input::State,
     save: button::State,
     close: button::State,
 }
@@ -20,11 +27,15 @@ impl SettingsPage {
         let has_apikey = settings.apikey.is_some();
         settings.apikey = None;
         SettingsPage {
+            backup_retention: settings.backup_retention.to_string(),
             settings,
             has_apikey,
             has_changed: false,
             prev_page: Box::new(prev_page),
             apikey: text_input::State::new(),
+            endpoint_profile: pick_list::State::default(),
+            base_url: text_input::State::new(),
+            backup_retention_state: text_input::State::new(),
             save: button::State::new(),
             close: button::State::new(),
         }
@@ -35,10 +46,31 @@ impl SettingsPage {
         self.has_changed = true;
     }
 
+    pub fn set_base_url(&mut self, base_url: String) {
+        self.settings.base_url = base_url;
+        self.has_changed = true;
+    }
+
+    /// Sets the number of backups to keep from user input, which takes effect if it is
+    /// a positive number.
+    pub fn set_backup_retention(&mut self, backup_retention: String) {
+        if let Ok(retention) = backup_retention.trim().parse::<usize>() {
+            if retention > 0 {
+                self.settings.backup_retention = retention;
+                self.has_changed = true;
+            }
+        }
+        self.backup_retention = backup_retention;
+    }
+
     pub fn get_prev_page(&self) -> Page {
         *self.prev_page.clone()
     }
 
+    pub fn get_prev_page_mut(&mut self) -> &mut Page {
+        &mut self.prev_page
+    }
+
     pub fn view<'a>(&'a mut self, error_state: &AppErrorState) -> Element<crate::Message> {
         let empty = "".to_string();
         let mut content = Column::new()
@@ -67,6 +99,56 @@ impl SettingsPage {
                 .size(14)
                 .color(TEXT_HELP_COLOR),
             )
+            .push(Space::with_height(Length::Units(24)))
+            .push(Text::new("SMMDB server:").font(HELVETICA_BOLD))
+            .push(
+                PickList::new(
+                    &mut self.endpoint_profile,
+                    &EndpointProfile::ALL[..],
+                    EndpointProfile::from_url(&self.settings.base_url),
+                    Message::ChangeEndpointProfile,
+                )
+                .style(DefaultPickListStyle)
+                .padding(4),
+            )
+            .push(
+                TextInput::new(
+                    &mut self.base_url,
+                    "Server URL",
+                    &self.settings.base_url,
+                    Message::ChangeBaseUrl,
+                )
+                .padding(4),
+            )
+            .push(
+                Text::new(
+                    "\
+            Select a server profile or enter the URL of a custom SMMDB server. \
+            You should only change this if you know what you are doing.",
+                )
+                .size(14)
+                .color(TEXT_HELP_COLOR),
+            )
+            .push(Space::with_height(Length::Units(24)))
+            .push(Text::new("Backups:").font(HELVETICA_BOLD))
+            .push(
+                TextInput::new(
+                    &mut self.backup_retention_state,
+                    "Number of backups",
+                    &self.backup_retention,
+                    Message::ChangeBackupRetention,
+                )
+                .padding(4),
+            )
+            .push(
+                Text::new(
+                    "\
+            A backup of your save is taken before every change. \
+            This many backups are kept per save, older ones are deleted.",
+                )
+                .size(14)
+                .color(TEXT_HELP_COLOR),
+            )
             .push(Space::with_height(Length::Units(24)));
 
         content = if let AppErrorState::Some(err) = error_state {
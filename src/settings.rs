@@ -1,14 +1,29 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use serde::{Deserialize, Serialize};
 use std::{
+    fmt,
     fs::{create_dir, read, write, File},
     io::Write,
     path::PathBuf,
 };
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Settings {
     pub apikey: Option<String>,
+    #[serde(default = "base_url_default")]
+    pub base_url: String,
+    #[serde(default = "backup_retention_default")]
+    pub backup_retention: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            apikey: None,
+            base_url: base_url_default(),
+            backup_retention: backup_retention_default(),
+        }
+    }
 }
 
 impl Settings {
@@ -26,16 +41,7 @@ impl Settings {
     }
 
     fn get_path() -> Result<PathBuf> {
-        let mut config_dir = if let Some(config_dir) = dirs::config_dir() {
-            config_dir
-        } else {
-            dirs::data_dir().expect("Could not initialize app directory")
-        };
-        config_dir.push("smmdb-client");
-        if !config_dir.exists() {
-            create_dir(config_dir.clone())?;
-        }
-        let mut settings_path = config_dir.clone();
+        let mut settings_path = get_config_dir()?;
         settings_path.push("settings.json");
         Ok(settings_path)
     }
@@ -47,3 +53,72 @@ impl Settings {
         Ok(())
     }
 }
+
+pub fn get_config_dir() -> Result<PathBuf> {
+    let mut config_dir = if let Some(config_dir) = dirs::config_dir() {
+        config_dir
+    } else {
+        dirs::data_dir().expect("Could not initialize app directory")
+    };
+    config_dir.push("smmdb-client");
+    if !config_dir.exists() {
+        create_dir(config_dir.clone())?;
+    }
+    Ok(config_dir)
+}
+
+/// Checks that a base URL can be used for requests to SMMDB and strips a trailing slash.
+pub fn validate_base_url(base_url: &str) -> Result<String> {
+    let base_url = base_url.trim().trim_end_matches('/');
+    let url = reqwest::Url::parse(base_url)
+        .map_err(|err| anyhow!("\"{}\" is not a valid server URL: {}", base_url, err))?;
+    match url.scheme() {
+        "http" | "https" => Ok(base_url.to_string()),
+        scheme => bail!(
+            "The server URL has to start with http or https, not {}.",
+            scheme
+        ),
+    }
+}
+
+fn base_url_default() -> String {
+    EndpointProfile::Production.get_url().to_string()
+}
+
+fn backup_retention_default() -> usize {
+    10
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EndpointProfile {
+    Production,
+    Local,
+}
+
+impl EndpointProfile {
+    pub const ALL: [EndpointProfile; 2] = [EndpointProfile::Production, EndpointProfile::Local];
+
+    pub fn get_url(&self) -> &'static str {
+        match self {
+            EndpointProfile::Production => "https://api.smmdb.net",
+            EndpointProfile::Local => "http://localhost:3030",
+        }
+    }
+
+    pub fn from_url(url: &str) -> Option<EndpointProfile> {
+        let url = url.trim_end_matches('/');
+        EndpointProfile::ALL
+            .iter()
+            .find(|profile| profile.get_url() == url)
+            .cloned()
+    }
+}
+
+impl fmt::Display for EndpointProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndpointProfile::Production => write!(f, "Production"),
+            EndpointProfile::Local => write!(f, "Local"),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use reqwest::{header, Response, StatusCode};
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ApiError {
+    NetworkUnreachable(String),
+    Timeout,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    RateLimited(Option<u64>),
+    ServerError(u16),
+    MalformedJson(String),
+    Rejected(String),
+    Other(String),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorAction {
+    ReenterApiKey,
+    Retry,
+    GoOffline,
+}
+
+impl ApiError {
+    pub fn from_response(response: &Response) -> Option<ApiError> {
+        let status = response.status();
+        if status.is_success() {
+            return None;
+        }
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok());
+        Some(ApiError::from_status(status, retry_after))
+    }
+
+    pub fn from_status(status: StatusCode, retry_after: Option<u64>) -> ApiError {
+        match status {
+            StatusCode::UNAUTHORIZED => ApiError::Unauthorized,
+            StatusCode::FORBIDDEN => ApiError::Forbidden,
+            StatusCode::NOT_FOUND => ApiError::NotFound,
+            StatusCode::TOO_MANY_REQUESTS => ApiError::RateLimited(retry_after),
+            StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => ApiError::Timeout,
+            status if status.is_server_error() => ApiError::ServerError(status.as_u16()),
+            status => ApiError::Other(format!("Unexpected response status {}", status)),
+        }
+    }
+
+    /// Whether SMMDB could not be reached at all, in which case cached data can be shown.
+    pub fn is_connection_error(&self) -> bool {
+        match self {
+            ApiError::NetworkUnreachable(_) | ApiError::Timeout | ApiError::ServerError(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn get_actions(&self) -> &'static [ErrorAction] {
+        match self {
+            ApiError::Unauthorized | ApiError::Forbidden => &[ErrorAction::ReenterApiKey],
+            ApiError::NetworkUnreachable(_) | ApiError::Timeout => {
+                &[ErrorAction::Retry, ErrorAction::GoOffline]
+            }
+            ApiError::RateLimited(_) | ApiError::ServerError(_) | ApiError::MalformedJson(_) => {
+                &[ErrorAction::Retry]
+            }
+            ApiError::NotFound | ApiError::Rejected(_) | ApiError::Other(_) => &[],
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::NetworkUnreachable(err) => write!(
+                f,
+                "Could not connect to SMMDB. Please check your internet connection.\n{}",
+                err
+            ),
+            ApiError::Timeout => write!(f, "SMMDB did not respond in time."),
+            ApiError::Unauthorized => write!(
+                f,
+                "Could not sign in! Your API key seems to be wrong."
+            ),
+            ApiError::Forbidden => write!(
+                f,
+                "You are not allowed to do this. Please check that you are signed in with the correct account."
+            ),
+            ApiError::NotFound => write!(f, "The requested course could not be found."),
+            ApiError::RateLimited(Some(seconds)) => write!(
+                f,
+                "Too many requests. Please try again in {} seconds.",
+                seconds
+            ),
+            ApiError::RateLimited(None) => {
+                write!(f, "Too many requests. Please try again later.")
+            }
+            ApiError::ServerError(status) => write!(
+                f,
+                "SMMDB encountered an internal error (status {}). Please try again later.",
+                status
+            ),
+            ApiError::MalformedJson(err) => {
+                write!(f, "SMMDB sent an invalid response.\n{}", err)
+            }
+            ApiError::Rejected(reason) => {
+                write!(f, "SMMDB rejected the course.\n{}", reason)
+            }
+            ApiError::Other(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            ApiError::Timeout
+        } else if let Some(status) = err.status() {
+            ApiError::from_status(status, None)
+        } else if err.is_decode() {
+            ApiError::MalformedJson(err.to_string())
+        } else if err.is_connect() || err.is_request() {
+            ApiError::NetworkUnreachable(err.to_string())
+        } else {
+            ApiError::Other(err.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(err: serde_json::Error) -> Self {
+        ApiError::MalformedJson(err.to_string())
+    }
+}
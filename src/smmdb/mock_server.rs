@@ -0,0 +1,474 @@
+use super::{Course2Response, CourseMetaBody, Difficulty, QueryParams, VoteBody};
+use crate::course_meta::{CourseTheme, GameStyle};
+
+use smmdb_lib::proto::SMM2Course::SMM2Course;
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+pub const VALID_APIKEY: &str = "valid-apikey";
+/// The account id of the user signed in with `VALID_APIKEY`, who owns every even fixture course.
+pub const ACCOUNT_ID: &str = "owner";
+pub const COURSE_COUNT: usize = 30;
+pub const COURSE_DATA_SIZE: usize = 64 * 1024;
+
+pub struct MockServer {
+    base_url: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+struct MockState {
+    courses: Vec<Course2Response>,
+    votes: HashMap<String, i32>,
+    queries: Vec<QueryParams>,
+    uploads: Vec<Vec<u8>>,
+    download_ranges: Vec<Option<String>>,
+    chunked_downloads: bool,
+    interrupt_download_at: Option<usize>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+    /// Whether `Content-Length` is sent, otherwise the body ends when the connection is closed.
+    has_content_length: bool,
+    /// Closes the connection after this many bytes of the body.
+    truncate_at: Option<usize>,
+}
+
+impl MockServer {
+    pub fn start() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(MockState {
+            courses: (0..COURSE_COUNT).map(fixture_course).collect(),
+            votes: HashMap::new(),
+            queries: vec![],
+            uploads: vec![],
+            download_ranges: vec![],
+            chunked_downloads: false,
+            interrupt_download_at: None,
+        }));
+
+        let server_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if let Ok(stream) = stream {
+                    handle_connection(stream, &server_state);
+                }
+            }
+        });
+
+        MockServer { base_url, state }
+    }
+
+    pub fn get_base_url(&self) -> String {
+        self.base_url.clone()
+    }
+
+    pub fn get_queries(&self) -> Vec<QueryParams> {
+        self.state.lock().unwrap().queries.clone()
+    }
+
+    pub fn get_vote(&self, course_id: &str) -> Option<i32> {
+        self.state.lock().unwrap().votes.get(course_id).cloned()
+    }
+
+    pub fn get_course(&self, course_id: &str) -> Option<Course2Response> {
+        find_course(&self.state, course_id)
+    }
+
+    pub fn get_download_ranges(&self) -> Vec<Option<String>> {
+        self.state.lock().unwrap().download_ranges.clone()
+    }
+
+    pub fn set_chunked_downloads(&self, chunked_downloads: bool) {
+        self.state.lock().unwrap().chunked_downloads = chunked_downloads;
+    }
+
+    pub fn interrupt_next_download(&self, at: usize) {
+        self.state.lock().unwrap().interrupt_download_at = Some(at);
+    }
+
+    pub fn get_uploads(&self) -> Vec<Vec<u8>> {
+        self.state.lock().unwrap().uploads.clone()
+    }
+}
+
+pub fn fixture_id(index: usize) -> String {
+    format!("{:024x}", index)
+}
+
+pub fn fixture_course(index: usize) -> Course2Response {
+    let mut course = SMM2Course::new();
+    course.mut_header().set_title(format!("Course {}", index));
+    course
+        .mut_header()
+        .set_description(format!("Description of course {}", index));
+    course.mut_header().set_time(100 + index as u32 * 10);
+    Course2Response {
+        id: fixture_id(index),
+        owner: if index % 2 == 0 {
+            ACCOUNT_ID.to_string()
+        } else {
+            "other".to_string()
+        },
+        uploader: "uploader".to_string(),
+        difficulty: Some(Difficulty::Normal),
+        last_modified: 1_600_000_000 + index as i64,
+        uploaded: 1_600_000_000 + index as i64,
+        votes: 10,
+        own_vote: 0,
+        course,
+    }
+}
+
+pub fn fixture_course_data(id: &str) -> Vec<u8> {
+    id.bytes().cycle().take(COURSE_DATA_SIZE).collect()
+}
+
+fn handle_connection(mut stream: TcpStream, state: &Arc<Mutex<MockState>>) {
+    let response = match read_request(&stream) {
+        Some(request) => route(request, state),
+        None => Response::empty(400),
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: {}\r\nConnection: close\r\n",
+        response.status, response.content_type
+    );
+    if response.has_content_length {
+        head.push_str(&format!("Content-Length: {}\r\n", response.body.len()));
+    }
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    let body = match response.truncate_at {
+        Some(at) if at < response.body.len() => &response.body[..at],
+        _ => &response.body[..],
+    };
+    stream.write_all(head.as_bytes()).ok();
+    stream.write_all(body).ok();
+    stream.flush().ok();
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = match target.find('?') {
+        Some(index) => (target[..index].to_string(), target[index + 1..].to_string()),
+        None => (target.to_string(), String::new()),
+    };
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(index) = line.find(':') {
+            headers.insert(
+                line[..index].trim().to_lowercase(),
+                line[index + 1..].trim().to_string(),
+            );
+        }
+    }
+
+    let content_length = headers
+        .get("content-length")
+        .and_then(|length| length.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(Request {
+        method,
+        path,
+        query,
+        headers,
+        body,
+    })
+}
+
+fn route(request: Request, state: &Arc<Mutex<MockState>>) -> Response {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    match (request.method.as_str(), &segments[..]) {
+        ("GET", ["courses2"]) => get_courses(&request, state),
+        ("GET", ["courses2", "thumbnail", id]) => find_course(state, id)
+            .map(|_| Response::new(200, "image/jpeg", fixture_thumbnail(id)))
+            .unwrap_or_else(|| Response::empty(404)),
+        ("GET", ["courses2", "download", id]) => match find_course(state, id) {
+            Some(_) => download_course(&request, state, id),
+            None => Response::empty(404),
+        },
+        ("POST", ["courses2", "vote", id]) => {
+            if !is_authorized(&request) {
+                return Response::empty(401);
+            }
+            if find_course(state, id).is_none() {
+                return Response::empty(404);
+            }
+            match serde_json::from_slice::<VoteBody>(&request.body) {
+                Ok(vote) => {
+                    state
+                        .lock()
+                        .unwrap()
+                        .votes
+                        .insert(id.to_string(), vote.value);
+                    Response::empty(204)
+                }
+                Err(_) => Response::empty(400),
+            }
+        }
+        ("PUT", ["courses2"]) => {
+            if !is_authorized(&request) {
+                return Response::empty(401);
+            }
+            put_course(&request, state, None)
+        }
+        ("PUT", ["courses2", id]) => match authorize_owner(&request, state, id) {
+            Ok(()) => put_course(&request, state, Some(*id)),
+            Err(response) => response,
+        },
+        ("POST", ["courses2", id]) => {
+            if let Err(response) = authorize_owner(&request, state, id) {
+                return response;
+            }
+            match serde_json::from_slice::<CourseMetaBody>(&request.body) {
+                Ok(meta) => {
+                    let mut state = state.lock().unwrap();
+                    if let Some(course) = state.courses.iter_mut().find(|course| course.id == *id) {
+                        course.difficulty = Some(meta.difficulty);
+                    }
+                    Response::empty(204)
+                }
+                Err(_) => Response::empty(400),
+            }
+        }
+        ("DELETE", ["courses2", id]) => match authorize_owner(&request, state, id) {
+            Ok(()) => {
+                state
+                    .lock()
+                    .unwrap()
+                    .courses
+                    .retain(|course| course.id != *id);
+                Response::empty(204)
+            }
+            Err(response) => response,
+        },
+        ("POST", ["login"]) => {
+            if is_authorized(&request) {
+                Response::new(
+                    200,
+                    "application/json",
+                    format!(r#"{{"id":"{}","username":"Mock"}}"#, ACCOUNT_ID).into_bytes(),
+                )
+            } else {
+                Response::empty(401)
+            }
+        }
+        _ => Response::empty(404),
+    }
+}
+
+fn get_courses(request: &Request, state: &Arc<Mutex<MockState>>) -> Response {
+    let query_params: QueryParams = match serde_qs::from_str(&request.query) {
+        Ok(query_params) => query_params,
+        Err(_) => return Response::empty(400),
+    };
+    let mut state = state.lock().unwrap();
+    state.queries.push(query_params.clone());
+
+    if query_params.get_title() == "malformed" {
+        return Response::new(200, "application/json", b"not json".to_vec());
+    }
+    let courses: Vec<&Course2Response> = state
+        .courses
+        .iter()
+        .filter(|course| matches_title(course, &query_params))
+        .filter(
+            |course| match (query_params.get_id(), query_params.get_ids()) {
+                (Some(id), _) => &course.id == id,
+                (None, Some(ids)) => ids.contains(&course.id),
+                (None, None) => true,
+            },
+        )
+        .filter(|course| match query_params.get_owner() {
+            Some(owner) => &course.owner == owner,
+            None => true,
+        })
+        .filter(|course| matches_meta(course, &query_params))
+        .skip(query_params.skip as usize)
+        .take(query_params.limit as usize)
+        .collect();
+    Response::new(
+        200,
+        "application/json",
+        serde_json::to_vec(&courses).unwrap(),
+    )
+}
+
+fn matches_meta(course: &Course2Response, query_params: &QueryParams) -> bool {
+    let time = course.course.get_header().get_time();
+    query_params.get_game_style().map_or(true, |game_style| {
+        GameStyle::from_course(&course.course) == Some(game_style)
+    }) && query_params.get_theme().map_or(true, |theme| {
+        CourseTheme::from_course(&course.course) == Some(theme)
+    }) && query_params
+        .get_min_time()
+        .map_or(true, |min_time| time >= min_time)
+        && query_params
+            .get_max_time()
+            .map_or(true, |max_time| time <= max_time)
+}
+
+fn matches_title(course: &Course2Response, query_params: &QueryParams) -> bool {
+    let mut query_title = query_params.get_title().to_string();
+    let mut title = course.course.get_header().get_title().to_string();
+    if query_params.is_title_trimmed() {
+        query_title = query_title.trim().to_string();
+    }
+    if !query_params.is_title_case_sensitive() {
+        query_title = query_title.to_lowercase();
+        title = title.to_lowercase();
+    }
+    if query_params.is_title_exact() && !query_title.is_empty() {
+        title == query_title
+    } else {
+        title.contains(&query_title)
+    }
+}
+
+fn put_course(request: &Request, state: &Arc<Mutex<MockState>>, id: Option<&str>) -> Response {
+    if request.body.is_empty() {
+        return Response::new(400, "text/plain", b"Invalid course file".to_vec());
+    }
+    if request.body.starts_with(b"duplicate") {
+        let body = serde_json::json!({
+            "succeeded": [],
+            "failed": [{ "title": "Duplicate", "error": "Course already exists" }],
+        });
+        return Response::new(200, "application/json", serde_json::to_vec(&body).unwrap());
+    }
+
+    let mut state = state.lock().unwrap();
+    let course = match id {
+        Some(id) => {
+            let course = state
+                .courses
+                .iter_mut()
+                .find(|course| course.id == id)
+                .unwrap();
+            course.last_modified += 1;
+            course.clone()
+        }
+        None => {
+            let course = fixture_course(COURSE_COUNT + state.uploads.len());
+            state.courses.push(course.clone());
+            course
+        }
+    };
+    state.uploads.push(request.body.clone());
+    let body = serde_json::json!({ "succeeded": [course], "failed": [] });
+    Response::new(200, "application/json", serde_json::to_vec(&body).unwrap())
+}
+
+fn download_course(request: &Request, state: &Arc<Mutex<MockState>>, id: &str) -> Response {
+    let mut state = state.lock().unwrap();
+    let range = request.headers.get("range").cloned();
+    state.download_ranges.push(range.clone());
+
+    let data = fixture_course_data(id);
+    let start = range
+        .as_ref()
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok());
+    let mut response = match start {
+        Some(start) if start < data.len() => {
+            let mut response =
+                Response::new(206, "application/octet-stream", data[start..].to_vec());
+            response.headers.push((
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, data.len() - 1, data.len()),
+            ));
+            response
+        }
+        Some(_) => return Response::empty(416),
+        None => Response::new(200, "application/octet-stream", data),
+    };
+    response.has_content_length = !state.chunked_downloads;
+    response.truncate_at = state.interrupt_download_at.take();
+    response
+}
+
+fn find_course(state: &Arc<Mutex<MockState>>, id: &str) -> Option<Course2Response> {
+    state
+        .lock()
+        .unwrap()
+        .courses
+        .iter()
+        .find(|course| course.id == id)
+        .cloned()
+}
+
+fn fixture_thumbnail(id: &str) -> Vec<u8> {
+    let mut thumbnail = vec![0xFF, 0xD8];
+    thumbnail.extend(id.bytes());
+    thumbnail
+}
+
+fn is_authorized(request: &Request) -> bool {
+    request.headers.get("authorization") == Some(&format!("APIKEY {}", VALID_APIKEY))
+}
+
+fn authorize_owner(
+    request: &Request,
+    state: &Arc<Mutex<MockState>>,
+    id: &str,
+) -> Result<(), Response> {
+    if !is_authorized(request) {
+        return Err(Response::empty(401));
+    }
+    match find_course(state, id) {
+        Some(course) if course.owner == ACCOUNT_ID => Ok(()),
+        Some(_) => Err(Response::empty(403)),
+        None => Err(Response::empty(404)),
+    }
+}
+
+impl Response {
+    fn new(status: u16, content_type: &'static str, body: Vec<u8>) -> Response {
+        Response {
+            status,
+            content_type,
+            headers: vec![],
+            body,
+            has_content_length: true,
+            truncate_at: None,
+        }
+    }
+
+    fn empty(status: u16) -> Response {
+        Response::new(status, "text/plain", vec![])
+    }
+}
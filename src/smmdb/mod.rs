@@ -0,0 +1,1012 @@
+use crate::{
+    cache::THUMBNAIL_CACHE,
+    components::SmmdbCoursePanel,
+    course_meta::{CourseTheme, GameStyle, MetaFilter},
+    http, Download, Progress, Upload, UploadProgress,
+};
+
+use chrono::{Local, TimeZone};
+use iced::Subscription;
+use indexmap::IndexMap;
+use reqwest::{header, Body, Method, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use smmdb_lib::proto::SMM2Course::SMM2Course;
+use std::{fmt, sync::Arc};
+
+mod error;
+#[cfg(test)]
+mod mock_server;
+#[cfg(test)]
+mod tests;
+
+pub use error::*;
+
+#[derive(Debug)]
+pub struct Smmdb {
+    apikey: Option<String>,
+    query_params: QueryParams,
+    course_panels: IndexMap<String, SmmdbCoursePanel>,
+    error: Option<ApiError>,
+    offline: bool,
+    cached_at: Option<i64>,
+    account: Option<Account>,
+    ids_input: String,
+    meta_filter: MetaFilter,
+    listing_id: u64,
+    prefetched_courses: Option<Vec<Course2Response>>,
+    prefetching: bool,
+    loading_more: bool,
+    end_reached: bool,
+}
+
+impl Smmdb {
+    pub fn new(apikey: Option<String>) -> Smmdb {
+        Smmdb {
+            apikey,
+            query_params: serde_json::from_str::<QueryParams>("{}").unwrap(),
+            course_panels: IndexMap::new(),
+            error: None,
+            offline: false,
+            cached_at: None,
+            account: None,
+            ids_input: String::new(),
+            meta_filter: MetaFilter::default(),
+            listing_id: 0,
+            prefetched_courses: None,
+            prefetching: false,
+            loading_more: false,
+            end_reached: false,
+        }
+    }
+
+    pub fn set_courses(&mut self, courses: Vec<Course2Response>) {
+        self.course_panels.clear();
+        self.listing_id += 1;
+        self.prefetched_courses = None;
+        self.prefetching = false;
+        self.loading_more = false;
+        self.append_courses(courses);
+    }
+
+    pub fn append_courses(&mut self, courses: Vec<Course2Response>) {
+        self.end_reached = (courses.len() as u32) < self.query_params.limit;
+        courses
+            .into_iter()
+            .map(SmmdbCoursePanel::new)
+            .for_each(|course| {
+                self.course_panels.insert(course.get_id().clone(), course);
+            });
+    }
+
+    /// Identifies the current listing, so that pages of a previous listing can be discarded.
+    pub fn get_listing_id(&self) -> u64 {
+        self.listing_id
+    }
+
+    pub fn get_next_page_query_params(&self) -> QueryParams {
+        let mut query_params = self.query_params.clone();
+        query_params.skip = self.course_panels.len() as u32;
+        query_params
+    }
+
+    pub fn is_end_reached(&self) -> bool {
+        self.end_reached
+    }
+
+    pub fn is_loading_more(&self) -> bool {
+        self.loading_more
+    }
+
+    pub fn set_loading_more(&mut self, loading_more: bool) {
+        self.loading_more = loading_more;
+    }
+
+    pub fn is_prefetching(&self) -> bool {
+        self.prefetching
+    }
+
+    pub fn set_prefetching(&mut self, prefetching: bool) {
+        self.prefetching = prefetching;
+    }
+
+    /// Stores the next page, if it still follows the loaded courses.
+    pub fn set_prefetched_courses(
+        &mut self,
+        listing_id: u64,
+        skip: u32,
+        courses: Vec<Course2Response>,
+    ) -> bool {
+        if listing_id != self.listing_id || skip != self.course_panels.len() as u32 {
+            return false;
+        }
+        self.prefetching = false;
+        if courses.is_empty() {
+            self.end_reached = true;
+        } else {
+            self.prefetched_courses = Some(courses);
+        }
+        true
+    }
+
+    pub fn take_prefetched_courses(&mut self) -> Option<Vec<Course2Response>> {
+        self.prefetched_courses.take()
+    }
+
+    pub fn set_course_panel_thumbnail(&mut self, id: &String, thumbnail: Vec<u8>) {
+        if let Some(course_panel) = self.course_panels.get_mut(id) {
+            course_panel.set_thumbnail(thumbnail);
+        }
+    }
+
+    pub fn set_course(&mut self, course: Course2Response) {
+        if let Some(course_panel) = self.course_panels.get_mut(course.get_id()) {
+            *course_panel = SmmdbCoursePanel::new(course);
+        }
+    }
+
+    pub fn remove_course(&mut self, id: &String) {
+        self.course_panels.shift_remove(id);
+    }
+
+    pub fn set_course_difficulty(&mut self, id: &String, difficulty: Difficulty) {
+        if let Some(course_panel) = self.course_panels.get_mut(id) {
+            course_panel.set_difficulty(difficulty);
+        }
+    }
+
+    pub fn get_course_panels(&mut self) -> &mut IndexMap<String, SmmdbCoursePanel> {
+        &mut self.course_panels
+    }
+
+    pub fn get_visible_course_panels(&mut self) -> Vec<&mut SmmdbCoursePanel> {
+        let meta_filter = &self.meta_filter;
+        self.course_panels
+            .values_mut()
+            .filter(|panel| meta_filter.matches(panel.get_course().get_course()))
+            .collect()
+    }
+
+    pub fn get_fill_candidates(&self, limit: Option<usize>) -> Vec<Course2Response> {
+        self.course_panels
+            .values()
+            .map(|panel| panel.get_course())
+            .filter(|course| self.meta_filter.matches(course.get_course()))
+            .take(limit.unwrap_or(std::usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_meta_filter(&self) -> &MetaFilter {
+        &self.meta_filter
+    }
+
+    pub fn get_meta_filter_mut(&mut self) -> &mut MetaFilter {
+        &mut self.meta_filter
+    }
+
+    pub fn get_query_params(&self) -> &QueryParams {
+        &self.query_params
+    }
+
+    pub fn reset_pagination(&mut self) {
+        self.query_params.skip = 0;
+    }
+
+    pub fn set_title(&mut self, title: String) {
+        if let "" = title.as_ref() {
+            self.query_params.title = None;
+        } else {
+            self.query_params.title = Some(title);
+        }
+    }
+
+    pub fn set_uploader(&mut self, uploader: String) {
+        if let "" = uploader.as_ref() {
+            self.query_params.uploader = None;
+        } else {
+            self.query_params.uploader = Some(uploader);
+        }
+    }
+
+    pub fn set_game_style(&mut self, game_style: Option<GameStyle>) {
+        self.meta_filter.set_game_style(game_style);
+        self.query_params.game_style = game_style;
+    }
+
+    pub fn set_theme(&mut self, theme: Option<CourseTheme>) {
+        self.meta_filter.set_theme(theme);
+        self.query_params.theme = theme;
+    }
+
+    pub fn set_min_time(&mut self, min_time: String) {
+        self.meta_filter.set_min_time(min_time);
+        self.query_params.min_time = self.meta_filter.get_min_time();
+    }
+
+    pub fn set_max_time(&mut self, max_time: String) {
+        self.meta_filter.set_max_time(max_time);
+        self.query_params.max_time = self.meta_filter.get_max_time();
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        if let Difficulty::Unset = difficulty {
+            self.query_params.difficulty = None;
+        } else {
+            self.query_params.difficulty = Some(difficulty);
+        }
+    }
+
+    pub fn set_sort(&mut self, sort: SortOptions) {
+        self.query_params.sort = Some(sort);
+    }
+
+    pub fn add_sort_key(&mut self, val: SortValue) {
+        let mut sort = self
+            .query_params
+            .sort
+            .take()
+            .unwrap_or_else(|| SortOptions::from(vec![]));
+        sort.push(val);
+        self.query_params.sort = Some(sort);
+    }
+
+    pub fn remove_sort_key(&mut self, index: usize) {
+        if let Some(sort) = self.query_params.sort.as_mut() {
+            sort.remove(index);
+            if sort.is_empty() {
+                self.query_params.sort = None;
+            }
+        }
+    }
+
+    pub fn toggle_sort_direction(&mut self, index: usize) {
+        if let Some(sort) = self.query_params.sort.as_mut() {
+            sort.toggle_direction(index);
+        }
+    }
+
+    pub fn move_sort_key_up(&mut self, index: usize) {
+        if let Some(sort) = self.query_params.sort.as_mut() {
+            sort.move_up(index);
+        }
+    }
+
+    pub fn set_owner(&mut self, owner: Option<String>) {
+        self.query_params.owner = owner;
+    }
+
+    pub fn set_owner_input(&mut self, owner: String) {
+        let owner = owner.trim();
+        if owner.is_empty() {
+            self.query_params.owner = None;
+        } else {
+            self.query_params.owner = Some(owner.to_string());
+        }
+    }
+
+    pub fn set_title_exact(&mut self, title_exact: bool) {
+        self.query_params.title_exact = title_exact;
+    }
+
+    pub fn set_title_case_sensitive(&mut self, title_case_sensitive: bool) {
+        self.query_params.title_case_sensitive = title_case_sensitive;
+    }
+
+    pub fn set_title_trimmed(&mut self, title_trimmed: bool) {
+        self.query_params.title_trimmed = title_trimmed;
+    }
+
+    pub fn get_ids_input(&self) -> &str {
+        &self.ids_input
+    }
+
+    /// Sets the SMMDB ids to look up from a pasted list,
+    /// which can be separated by commas or whitespace.
+    pub fn set_ids_input(&mut self, ids_input: String) {
+        let mut ids: Vec<String> = ids_input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|id| !id.is_empty())
+            .map(|id| id.to_string())
+            .collect();
+        self.ids_input = ids_input;
+        match ids.len() {
+            0 => {
+                self.query_params.id = None;
+                self.query_params.ids = None;
+            }
+            1 => {
+                self.query_params.id = ids.pop();
+                self.query_params.ids = None;
+            }
+            _ => {
+                self.query_params.id = None;
+                self.query_params.ids = Some(ids);
+            }
+        }
+    }
+
+    pub fn set_apikey(&mut self, apikey: String) {
+        self.apikey = Some(apikey);
+    }
+
+    pub fn get_account(&self) -> Option<&Account> {
+        self.account.as_ref()
+    }
+
+    pub fn set_account(&mut self, account: Option<Account>) {
+        if account.is_none() {
+            self.query_params.owner = None;
+        }
+        self.account = account;
+    }
+
+    pub fn get_error(&self) -> Option<&ApiError> {
+        self.error.as_ref()
+    }
+
+    pub fn set_error(&mut self, error: Option<ApiError>) {
+        self.error = error;
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+        if offline {
+            self.error = None;
+        } else {
+            self.cached_at = None;
+        }
+    }
+
+    pub fn get_cached_at(&self) -> Option<i64> {
+        self.cached_at
+    }
+
+    pub fn set_cached_courses(&mut self, courses: Vec<Course2Response>, fetched_at: i64) {
+        self.set_courses(courses);
+        self.set_offline(true);
+        self.cached_at = Some(fetched_at);
+        // Only the first page of a listing is cached.
+        self.end_reached = true;
+    }
+
+    pub fn set_own_vote(&mut self, course_id: String, value: i32) {
+        if let Some(course) = self.course_panels.get_mut(&course_id) {
+            course.set_own_vote(value);
+        }
+    }
+
+    pub async fn update(
+        base_url: String,
+        query_params: QueryParams,
+        apikey: Option<String>,
+    ) -> Result<Vec<Course2Response>, ApiError> {
+        let qs =
+            serde_qs::to_string(&query_params).map_err(|err| ApiError::Other(err.to_string()))?;
+        let mut request = http::api_request(
+            Method::GET,
+            &get_url(&base_url, &format!("courses2?{}", qs)),
+        );
+        if let Some(apikey) = apikey {
+            request = request.header(header::AUTHORIZATION, &format!("APIKEY {}", apikey));
+        }
+
+        let body = check_response(http::send_idempotent(request).await?)?
+            .text()
+            .await?;
+        let response: Vec<Course2Response> = serde_json::from_str(&body)?;
+        Ok(response)
+    }
+
+    /// Fetches a thumbnail in the given size, which is either "m" or "l".
+    pub async fn fetch_thumbnail(
+        base_url: String,
+        id: String,
+        last_modified: i64,
+        size: &'static str,
+    ) -> Result<Vec<u8>, ApiError> {
+        let cached = THUMBNAIL_CACHE
+            .lock()
+            .unwrap()
+            .get(&base_url, &id, size, last_modified);
+        if let Some(thumbnail) = cached {
+            return Ok(thumbnail);
+        }
+
+        let response = http::send_idempotent(http::api_request(
+            Method::GET,
+            &get_url(
+                &base_url,
+                &format!("courses2/thumbnail/{}?size={}", id, size),
+            ),
+        ))
+        .await?;
+        let bytes = check_response(response)?.bytes().await?;
+        let thumbnail: Vec<u8> = bytes.into_iter().collect();
+        if let Err(err) =
+            THUMBNAIL_CACHE
+                .lock()
+                .unwrap()
+                .insert(&base_url, &id, size, last_modified, &thumbnail)
+        {
+            log::warn!("Could not cache thumbnail: {}", err);
+        }
+        Ok(thumbnail)
+    }
+
+    pub async fn fetch_course(base_url: String, id: String) -> Result<Vec<u8>, ApiError> {
+        let response = http::send_idempotent(
+            http::client().get(&get_url(&base_url, &format!("courses2/download/{}", id))),
+        )
+        .await?;
+        let bytes = check_response(response)?.bytes().await?;
+        Ok(bytes.into_iter().collect())
+    }
+
+    pub fn download_course(
+        base_url: &str,
+        job_id: u64,
+        id: String,
+    ) -> Subscription<(u64, Progress)> {
+        Subscription::from_recipe(Download {
+            id: job_id,
+            url: get_url(base_url, &format!("courses2/download/{}", id)),
+        })
+    }
+
+    /// Uploads a course or, if an SMMDB id is given, replaces that course's data.
+    pub fn upload_course(
+        base_url: &str,
+        upload_id: u64,
+        save_index: usize,
+        apikey: String,
+        data: Arc<Vec<u8>>,
+        smmdb_id: Option<String>,
+    ) -> Subscription<UploadProgress> {
+        let path = match smmdb_id {
+            Some(smmdb_id) => format!("courses2/{}", smmdb_id),
+            None => "courses2".to_string(),
+        };
+        Subscription::from_recipe(Upload {
+            id: upload_id,
+            save_index,
+            url: get_url(base_url, &path),
+            apikey,
+            data,
+        })
+    }
+
+    pub async fn put_course(
+        url: String,
+        apikey: String,
+        body: Body,
+        content_length: usize,
+    ) -> Result<Course2Response, ApiError> {
+        let response = http::client()
+            .put(&url)
+            .header(header::AUTHORIZATION, &format!("APIKEY {}", apikey))
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .header(header::CONTENT_LENGTH, content_length)
+            .body(body)
+            .send()
+            .await?;
+        if response.status() == StatusCode::BAD_REQUEST {
+            return Err(ApiError::Rejected(response.text().await?));
+        }
+
+        let body = check_response(response)?.text().await?;
+        let mut response: UploadResponse = serde_json::from_str(&body)?;
+        if !response.succeeded.is_empty() {
+            return Ok(response.succeeded.remove(0));
+        }
+        let reason = response
+            .failed
+            .into_iter()
+            .map(|failure| match failure.title {
+                Some(title) => format!("{}: {}", title, failure.error),
+                None => failure.error,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Err(ApiError::Rejected(reason))
+    }
+
+    pub async fn try_sign_in(base_url: String, apikey: String) -> Result<Account, ApiError> {
+        let response = http::api_request(Method::POST, &get_url(&base_url, "login"))
+            .header(header::AUTHORIZATION, &format!("APIKEY {}", apikey))
+            .send()
+            .await?;
+        let body = check_response(response)?.text().await?;
+        let account: Account = serde_json::from_str(&body)?;
+        Ok(account)
+    }
+
+    pub async fn delete_course(
+        base_url: String,
+        course_id: String,
+        apikey: String,
+    ) -> Result<(), ApiError> {
+        let response = http::api_request(
+            Method::DELETE,
+            &get_url(&base_url, &format!("courses2/{}", course_id)),
+        )
+        .header(header::AUTHORIZATION, &format!("APIKEY {}", apikey))
+        .send()
+        .await?;
+        check_response(response)?;
+        Ok(())
+    }
+
+    pub async fn set_course_difficulty(
+        base_url: String,
+        course_id: String,
+        difficulty: Difficulty,
+        apikey: String,
+    ) -> Result<(), ApiError> {
+        let body = serde_json::to_string(&CourseMetaBody { difficulty })?;
+        let response = http::api_request(
+            Method::POST,
+            &get_url(&base_url, &format!("courses2/{}", course_id)),
+        )
+        .header(header::AUTHORIZATION, &format!("APIKEY {}", apikey))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await?;
+        check_response(response)?;
+        Ok(())
+    }
+
+    pub async fn vote(
+        base_url: String,
+        course_id: String,
+        value: i32,
+        apikey: String,
+    ) -> Result<(), ApiError> {
+        let body = serde_json::to_string(&VoteBody { value })?;
+        let response = http::api_request(
+            Method::POST,
+            &get_url(&base_url, &format!("courses2/vote/{}", course_id)),
+        )
+        .header(header::AUTHORIZATION, &format!("APIKEY {}", apikey))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await?;
+        check_response(response)?;
+        Ok(())
+    }
+}
+
+fn check_response(response: Response) -> Result<Response, ApiError> {
+    match ApiError::from_response(&response) {
+        Some(err) => Err(err),
+        None => Ok(response),
+    }
+}
+
+pub fn format_timestamp(timestamp: i64) -> String {
+    Local
+        .timestamp(timestamp, 0)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+fn get_url(base_url: &str, path: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), path)
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Course2Response {
+    id: String,
+    owner: String,
+    uploader: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    difficulty: Option<Difficulty>,
+    last_modified: i64,
+    uploaded: i64,
+    votes: i32,
+    #[serde(default)]
+    own_vote: i32,
+    course: SMM2Course,
+}
+
+impl Course2Response {
+    pub fn get_id(&self) -> &String {
+        &self.id
+    }
+
+    pub fn get_owner(&self) -> &String {
+        &self.owner
+    }
+
+    pub fn get_uploader(&self) -> &String {
+        &self.uploader
+    }
+
+    pub fn get_uploaded(&self) -> i64 {
+        self.uploaded
+    }
+
+    pub fn get_last_modified(&self) -> i64 {
+        self.last_modified
+    }
+
+    pub fn get_votes(&self) -> i32 {
+        self.votes
+    }
+
+    pub fn get_own_vote(&self) -> i32 {
+        self.own_vote
+    }
+
+    pub fn set_own_vote(&mut self, value: i32) {
+        let diff = value - self.own_vote;
+        self.votes = self.votes + diff;
+        self.own_vote = value;
+    }
+
+    pub fn get_course(&self) -> &SMM2Course {
+        &self.course
+    }
+
+    pub fn get_difficulty(&self) -> Option<&Difficulty> {
+        self.difficulty.as_ref()
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = Some(difficulty);
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Account {
+    id: String,
+    username: String,
+}
+
+impl Account {
+    pub fn get_id(&self) -> &String {
+        &self.id
+    }
+
+    pub fn get_username(&self) -> &String {
+        &self.username
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UploadResponse {
+    #[serde(default)]
+    succeeded: Vec<Course2Response>,
+    #[serde(default)]
+    failed: Vec<UploadFailure>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UploadFailure {
+    #[serde(default)]
+    title: Option<String>,
+    error: String,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Unset,
+    Easy,
+    Normal,
+    Expert,
+    SuperExpert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Unset,
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Expert,
+        Difficulty::SuperExpert,
+    ];
+
+    pub const TAGS: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Expert,
+        Difficulty::SuperExpert,
+    ];
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Unset => write!(f, ""),
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Normal => write!(f, "Normal"),
+            Difficulty::Expert => write!(f, "Expert"),
+            Difficulty::SuperExpert => write!(f, "SuperExpert"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QueryParams {
+    #[serde(default = "limit_default")]
+    pub limit: u32,
+    #[serde(default)]
+    pub skip: u32,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    ids: Option<Vec<String>>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    title_exact: bool,
+    #[serde(default)]
+    title_case_sensitive: bool,
+    #[serde(default = "is_true")]
+    title_trimmed: bool,
+    #[serde(default)]
+    owner: Option<String>,
+    #[serde(default)]
+    uploader: Option<String>,
+    #[serde(default)]
+    sort: Option<SortOptions>,
+    #[serde(default)]
+    difficulty: Option<Difficulty>,
+    #[serde(default)]
+    game_style: Option<GameStyle>,
+    #[serde(default)]
+    theme: Option<CourseTheme>,
+    #[serde(default)]
+    min_time: Option<u32>,
+    #[serde(default)]
+    max_time: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VoteBody {
+    #[serde(default)]
+    pub value: i32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CourseMetaBody {
+    pub difficulty: Difficulty,
+}
+
+impl QueryParams {
+    pub fn get_title(&self) -> &str {
+        if let Some(title) = self.title.as_ref() {
+            title
+        } else {
+            ""
+        }
+    }
+
+    pub fn get_uploader(&self) -> &str {
+        if let Some(uploader) = self.uploader.as_ref() {
+            uploader
+        } else {
+            ""
+        }
+    }
+
+    pub fn get_id(&self) -> Option<&String> {
+        self.id.as_ref()
+    }
+
+    pub fn get_ids(&self) -> Option<&Vec<String>> {
+        self.ids.as_ref()
+    }
+
+    pub fn is_title_exact(&self) -> bool {
+        self.title_exact
+    }
+
+    pub fn is_title_case_sensitive(&self) -> bool {
+        self.title_case_sensitive
+    }
+
+    pub fn is_title_trimmed(&self) -> bool {
+        self.title_trimmed
+    }
+
+    pub fn get_owner(&self) -> Option<&String> {
+        self.owner.as_ref()
+    }
+
+    pub fn get_sort(&self) -> Option<SortOptions> {
+        self.sort.clone()
+    }
+
+    pub fn get_difficulty(&self) -> Option<Difficulty> {
+        self.difficulty.clone()
+    }
+
+    pub fn get_game_style(&self) -> Option<GameStyle> {
+        self.game_style
+    }
+
+    pub fn get_theme(&self) -> Option<CourseTheme> {
+        self.theme
+    }
+
+    pub fn get_min_time(&self) -> Option<u32> {
+        self.min_time
+    }
+
+    pub fn get_max_time(&self) -> Option<u32> {
+        self.max_time
+    }
+}
+
+fn limit_default() -> u32 {
+    25
+}
+
+fn is_true() -> bool {
+    true
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Sort {
+    pub val: SortValue,
+    dir: i32,
+}
+
+impl Sort {
+    pub fn new(val: SortValue, ascending: bool) -> Sort {
+        Sort {
+            val,
+            dir: if ascending { 1 } else { -1 },
+        }
+    }
+
+    pub fn is_ascending(&self) -> bool {
+        self.dir > 0
+    }
+}
+
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ascending() {
+            write!(f, "{} asc", self.val)
+        } else {
+            write!(f, "{} desc", self.val)
+        }
+    }
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Sort {
+            val: SortValue::LastModified,
+            dir: -1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Serialize)]
+pub struct SortOptions(Vec<Sort>);
+
+impl SortOptions {
+    pub fn get_keys(&self) -> &[Sort] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, val: &SortValue) -> bool {
+        self.0.iter().any(|sort| sort.val == *val)
+    }
+
+    pub fn push(&mut self, val: SortValue) {
+        if !self.contains(&val) {
+            self.0.push(Sort::new(val, false));
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.0.len() {
+            self.0.remove(index);
+        }
+    }
+
+    pub fn toggle_direction(&mut self, index: usize) {
+        if let Some(sort) = self.0.get_mut(index) {
+            sort.dir = -sort.dir;
+        }
+    }
+
+    pub fn move_up(&mut self, index: usize) {
+        if index > 0 && index < self.0.len() {
+            self.0.swap(index - 1, index);
+        }
+    }
+}
+
+impl From<Vec<Sort>> for SortOptions {
+    fn from(keys: Vec<Sort>) -> Self {
+        SortOptions(keys)
+    }
+}
+
+lazy_static! {
+    pub static ref SORT_OPTIONS: [SortOptions; 2] = [
+        SortOptions(vec![Sort {
+            val: SortValue::LastModified,
+            dir: -1,
+        }]),
+        SortOptions(vec![
+            Sort {
+                val: SortValue::Votes,
+                dir: -1,
+            },
+            Sort {
+                val: SortValue::LastModified,
+                dir: -1,
+            },
+        ]),
+    ];
+}
+
+impl fmt::Display for SortOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<String> = self.0.iter().map(|sort| sort.to_string()).collect();
+        write!(f, "{}", keys.join(", "))
+    }
+}
+
+impl PartialEq for SortOptions {
+    /// Sort options are equal, if they sort by the same keys in the same order and direction.
+    /// The direction is compared by its sign only, since SMMDB accepts any positive or
+    /// negative number.
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(other.0.iter())
+                .all(|(a, b)| a.val == b.val && a.is_ascending() == b.is_ascending())
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, Debug, Eq, PartialEq, Serialize)]
+pub enum SortValue {
+    #[serde(rename = "last_modified")]
+    LastModified,
+    #[serde(rename = "uploaded")]
+    Uploaded,
+    #[serde(rename = "course.header.title")]
+    CourseHeaderTitle,
+    #[serde(rename = "votes")]
+    Votes,
+}
+
+impl SortValue {
+    pub const ALL: [SortValue; 4] = [
+        SortValue::LastModified,
+        SortValue::Uploaded,
+        SortValue::CourseHeaderTitle,
+        SortValue::Votes,
+    ];
+}
+
+impl fmt::Display for SortValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortValue::LastModified => write!(f, "Last Modified"),
+            SortValue::Uploaded => write!(f, "Uploaded"),
+            SortValue::CourseHeaderTitle => write!(f, "Title"),
+            SortValue::Votes => write!(f, "Votes"),
+        }
+    }
+}
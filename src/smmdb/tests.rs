@@ -0,0 +1,820 @@
+use super::{mock_server::*, *};
+use crate::{
+    course_meta::{ClearConditionPresence, CourseTheme, DayTime, GameStyle},
+    Download, Progress, Upload, UploadProgress,
+};
+
+use futures::{Future, StreamExt};
+use iced_native::subscription::Recipe;
+use std::{collections::hash_map::DefaultHasher, sync::Arc};
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Runtime::new().unwrap().block_on(future)
+}
+
+fn default_query_params() -> QueryParams {
+    serde_json::from_str("{}").unwrap()
+}
+
+#[test]
+fn serializes_default_query_params() {
+    let qs = serde_qs::to_string(&default_query_params()).unwrap();
+
+    assert!(qs.contains("limit=25"));
+    assert!(qs.contains("skip=0"));
+    assert!(qs.contains("title_trimmed=true"));
+    assert!(!qs.contains("title="));
+    assert!(!qs.contains("difficulty"));
+}
+
+#[test]
+fn query_params_round_trip_through_query_string() {
+    let mut smmdb = Smmdb::new(None);
+    smmdb.set_title("Super Mario & Co".to_string());
+    smmdb.set_uploader("Uploader".to_string());
+    smmdb.set_difficulty(Difficulty::SuperExpert);
+    smmdb.set_sort(SORT_OPTIONS[1].clone());
+
+    let qs = serde_qs::to_string(smmdb.get_query_params()).unwrap();
+    let query_params: QueryParams = serde_qs::from_str(&qs).unwrap();
+
+    assert_eq!(query_params.get_title(), "Super Mario & Co");
+    assert_eq!(query_params.get_uploader(), "Uploader");
+    assert_eq!(query_params.get_difficulty(), Some(Difficulty::SuperExpert));
+    assert_eq!(query_params.get_sort(), Some(SORT_OPTIONS[1].clone()));
+}
+
+#[test]
+fn custom_sort_round_trips_through_query_string() {
+    let mut smmdb = Smmdb::new(None);
+    smmdb.add_sort_key(SortValue::CourseHeaderTitle);
+    smmdb.add_sort_key(SortValue::Uploaded);
+    smmdb.add_sort_key(SortValue::Uploaded);
+    smmdb.toggle_sort_direction(0);
+    smmdb.move_sort_key_up(1);
+
+    let sort = smmdb.get_query_params().get_sort().unwrap();
+    assert_eq!(sort.to_string(), "Uploaded desc, Title asc");
+    assert!(!SORT_OPTIONS.contains(&sort));
+
+    let qs = serde_qs::to_string(smmdb.get_query_params()).unwrap();
+    let query_params: QueryParams = serde_qs::from_str(&qs).unwrap();
+    assert_eq!(query_params.get_sort(), Some(sort));
+
+    smmdb.remove_sort_key(0);
+    smmdb.remove_sort_key(0);
+    assert_eq!(smmdb.get_query_params().get_sort(), None);
+}
+
+#[test]
+fn sort_equality_ignores_direction_magnitude() {
+    let sort: SortOptions = vec![Sort::new(SortValue::Votes, false)].into();
+    let qs_sort: SortOptions = serde_json::from_str(r#"[{"val":"votes","dir":-2}]"#).unwrap();
+
+    assert_eq!(sort, qs_sort);
+    assert_eq!(
+        sort,
+        SortOptions::from(vec![Sort::new(SortValue::Votes, false)])
+    );
+    assert_ne!(
+        sort,
+        SortOptions::from(vec![Sort::new(SortValue::Votes, true)])
+    );
+}
+
+#[test]
+fn server_receives_query_params() {
+    let server = MockServer::start();
+    let mut smmdb = Smmdb::new(None);
+    smmdb.set_title("Course 1".to_string());
+    smmdb.set_sort(SORT_OPTIONS[0].clone());
+
+    let courses = block_on(Smmdb::update(
+        server.get_base_url(),
+        smmdb.get_query_params().clone(),
+        None,
+    ))
+    .unwrap();
+
+    let queries = server.get_queries();
+    assert_eq!(queries.len(), 1);
+    assert_eq!(queries[0].get_title(), "Course 1");
+    assert_eq!(queries[0].get_sort(), Some(SORT_OPTIONS[0].clone()));
+    // "Course 1" and "Course 10" to "Course 19"
+    assert_eq!(courses.len(), 11);
+}
+
+#[test]
+fn advanced_query_params_round_trip_through_query_string() {
+    let mut smmdb = Smmdb::new(None);
+    smmdb.set_title_exact(true);
+    smmdb.set_title_case_sensitive(true);
+    smmdb.set_title_trimmed(false);
+    smmdb.set_owner_input(" owner ".to_string());
+    smmdb.set_ids_input(format!(
+        "{}, {}\n{}",
+        fixture_id(1),
+        fixture_id(2),
+        fixture_id(3)
+    ));
+
+    let qs = serde_qs::to_string(smmdb.get_query_params()).unwrap();
+    let query_params: QueryParams = serde_qs::from_str(&qs).unwrap();
+
+    assert!(query_params.is_title_exact());
+    assert!(query_params.is_title_case_sensitive());
+    assert!(!query_params.is_title_trimmed());
+    assert_eq!(query_params.get_owner().unwrap(), "owner");
+    assert_eq!(query_params.get_id(), None);
+    assert_eq!(
+        query_params.get_ids().unwrap(),
+        &vec![fixture_id(1), fixture_id(2), fixture_id(3)]
+    );
+}
+
+#[test]
+fn looks_up_courses_by_id() {
+    let server = MockServer::start();
+    let mut smmdb = Smmdb::new(None);
+
+    smmdb.set_ids_input(fixture_id(12));
+    assert_eq!(smmdb.get_query_params().get_id(), Some(&fixture_id(12)));
+    let courses = block_on(Smmdb::update(
+        server.get_base_url(),
+        smmdb.get_query_params().clone(),
+        None,
+    ))
+    .unwrap();
+    assert_eq!(courses.len(), 1);
+    assert_eq!(courses[0].get_id(), &fixture_id(12));
+
+    smmdb.set_ids_input(format!("{} {}", fixture_id(3), fixture_id(21)));
+    assert_eq!(smmdb.get_query_params().get_id(), None);
+    let courses = block_on(Smmdb::update(
+        server.get_base_url(),
+        smmdb.get_query_params().clone(),
+        None,
+    ))
+    .unwrap();
+    let ids: Vec<&String> = courses.iter().map(|course| course.get_id()).collect();
+    assert_eq!(ids, vec![&fixture_id(3), &fixture_id(21)]);
+}
+
+#[test]
+fn matches_exact_title() {
+    let server = MockServer::start();
+    let mut smmdb = Smmdb::new(None);
+    smmdb.set_title("course 1".to_string());
+    smmdb.set_title_exact(true);
+
+    let courses = block_on(Smmdb::update(
+        server.get_base_url(),
+        smmdb.get_query_params().clone(),
+        None,
+    ))
+    .unwrap();
+    assert_eq!(courses.len(), 1);
+    assert_eq!(courses[0].get_id(), &fixture_id(1));
+
+    smmdb.set_title_case_sensitive(true);
+    let courses = block_on(Smmdb::update(
+        server.get_base_url(),
+        smmdb.get_query_params().clone(),
+        None,
+    ))
+    .unwrap();
+    assert!(courses.is_empty());
+}
+
+#[test]
+fn loads_more_courses() {
+    let server = MockServer::start();
+    let mut smmdb = Smmdb::new(None);
+
+    let courses = block_on(Smmdb::update(
+        server.get_base_url(),
+        smmdb.get_query_params().clone(),
+        None,
+    ))
+    .unwrap();
+    assert_eq!(courses.len(), 25);
+    smmdb.set_courses(courses);
+    assert!(!smmdb.is_end_reached());
+
+    let listing_id = smmdb.get_listing_id();
+    let query_params = smmdb.get_next_page_query_params();
+    assert_eq!(query_params.skip, 25);
+    let courses = block_on(Smmdb::update(server.get_base_url(), query_params, None)).unwrap();
+    assert_eq!(courses.len(), COURSE_COUNT - 25);
+    assert_eq!(courses[0].get_id(), &fixture_id(25));
+
+    assert!(smmdb.set_prefetched_courses(listing_id, 25, courses));
+    assert_eq!(smmdb.get_course_panels().len(), 25);
+    smmdb.append_courses(smmdb.take_prefetched_courses().unwrap());
+    assert_eq!(smmdb.get_course_panels().len(), COURSE_COUNT);
+    assert!(smmdb.is_end_reached());
+    assert_eq!(smmdb.get_query_params().skip, 0);
+}
+
+#[test]
+fn detects_end_of_results_on_full_page() {
+    let server = MockServer::start();
+    let mut smmdb = Smmdb::new(None);
+    let mut query_params = smmdb.get_query_params().clone();
+    query_params.limit = COURSE_COUNT as u32;
+    smmdb.set_courses(vec![]);
+
+    let courses = block_on(Smmdb::update(
+        server.get_base_url(),
+        query_params.clone(),
+        None,
+    ))
+    .unwrap();
+    smmdb.append_courses(courses);
+    assert_eq!(smmdb.get_course_panels().len(), COURSE_COUNT);
+
+    query_params.skip = COURSE_COUNT as u32;
+    let courses = block_on(Smmdb::update(server.get_base_url(), query_params, None)).unwrap();
+    assert!(smmdb.set_prefetched_courses(smmdb.get_listing_id(), COURSE_COUNT as u32, courses));
+    assert!(smmdb.is_end_reached());
+    assert!(smmdb.take_prefetched_courses().is_none());
+}
+
+#[test]
+fn discards_pages_of_previous_listing() {
+    let mut smmdb = Smmdb::new(None);
+    smmdb.set_courses((0..25).map(fixture_course).collect());
+    let listing_id = smmdb.get_listing_id();
+
+    smmdb.set_courses((0..25).map(fixture_course).collect());
+
+    assert!(!smmdb.set_prefetched_courses(listing_id, 25, vec![fixture_course(25)]));
+    assert!(!smmdb.set_prefetched_courses(smmdb.get_listing_id(), 20, vec![fixture_course(25)]));
+    assert!(smmdb.take_prefetched_courses().is_none());
+}
+
+#[test]
+fn sends_meta_filters_to_server() {
+    let server = MockServer::start();
+    let mut smmdb = Smmdb::new(None);
+    smmdb.set_game_style(Some(GameStyle::Smb1));
+    smmdb.set_theme(Some(CourseTheme::Ground));
+    smmdb.set_min_time("150".to_string());
+    smmdb.set_max_time("200".to_string());
+    smmdb.set_max_time("forever".to_string());
+    assert_eq!(smmdb.get_query_params().get_max_time(), Some(200));
+    assert!(smmdb.get_meta_filter().get_time_error().is_some());
+
+    let courses = block_on(Smmdb::update(
+        server.get_base_url(),
+        smmdb.get_query_params().clone(),
+        None,
+    ))
+    .unwrap();
+
+    let queries = server.get_queries();
+    assert_eq!(queries[0].get_game_style(), Some(GameStyle::Smb1));
+    assert_eq!(queries[0].get_theme(), Some(CourseTheme::Ground));
+    assert_eq!(queries[0].get_min_time(), Some(150));
+    assert_eq!(queries[0].get_max_time(), Some(200));
+    let ids: Vec<String> = courses
+        .iter()
+        .map(|course| course.get_id().clone())
+        .collect();
+    assert_eq!(ids, (5..=10).map(fixture_id).collect::<Vec<_>>());
+
+    smmdb.set_game_style(Some(GameStyle::Sm3dw));
+    let courses = block_on(Smmdb::update(
+        server.get_base_url(),
+        smmdb.get_query_params().clone(),
+        None,
+    ))
+    .unwrap();
+    assert!(courses.is_empty());
+
+    smmdb.set_game_style(None);
+    smmdb.set_theme(None);
+    smmdb.set_min_time("".to_string());
+    smmdb.set_max_time("".to_string());
+    let qs = serde_qs::to_string(smmdb.get_query_params()).unwrap();
+    assert!(!qs.contains("game_style"));
+    assert!(!qs.contains("theme"));
+    assert!(!qs.contains("min_time"));
+    assert!(!qs.contains("max_time"));
+    assert!(smmdb.get_meta_filter().get_time_error().is_none());
+}
+
+#[test]
+fn meta_filters_round_trip_through_query_string() {
+    let mut smmdb = Smmdb::new(None);
+    smmdb.set_game_style(Some(GameStyle::Nsmbu));
+    smmdb.set_theme(Some(CourseTheme::GhostHouse));
+    smmdb.set_min_time("60".to_string());
+
+    let qs = serde_qs::to_string(smmdb.get_query_params()).unwrap();
+    assert!(qs.contains("game_style=WU"));
+    assert!(qs.contains("theme=GHOST_HOUSE"));
+    let query_params: QueryParams = serde_qs::from_str(&qs).unwrap();
+
+    assert_eq!(query_params.get_game_style(), Some(GameStyle::Nsmbu));
+    assert_eq!(query_params.get_theme(), Some(CourseTheme::GhostHouse));
+    assert_eq!(query_params.get_min_time(), Some(60));
+    assert_eq!(query_params.get_max_time(), None);
+}
+
+#[test]
+fn filters_loaded_courses_by_metadata() {
+    let mut smmdb = Smmdb::new(None);
+    smmdb.set_courses(
+        (0..10)
+            .map(|index| {
+                let mut course = fixture_course(index);
+                if index % 2 == 1 {
+                    course.course.mut_header().set_clear_condition(1);
+                }
+                course
+            })
+            .collect(),
+    );
+    assert!(!smmdb.get_meta_filter().is_active());
+    assert_eq!(smmdb.get_visible_course_panels().len(), 10);
+
+    smmdb
+        .get_meta_filter_mut()
+        .set_clear_condition(Some(ClearConditionPresence::With));
+    let ids: Vec<String> = smmdb
+        .get_visible_course_panels()
+        .iter()
+        .map(|panel| panel.get_id().clone())
+        .collect();
+    assert_eq!(
+        ids,
+        (0..5).map(|i| fixture_id(i * 2 + 1)).collect::<Vec<_>>()
+    );
+
+    smmdb.get_meta_filter_mut().set_day_time(Some(DayTime::Day));
+    assert_eq!(smmdb.get_visible_course_panels().len(), 5);
+    smmdb
+        .get_meta_filter_mut()
+        .set_day_time(Some(DayTime::Night));
+    assert!(smmdb.get_visible_course_panels().is_empty());
+
+    smmdb.get_meta_filter_mut().set_day_time(None);
+    smmdb.get_meta_filter_mut().set_clear_condition(None);
+    assert!(!smmdb.get_meta_filter().is_active());
+    assert_eq!(smmdb.get_course_panels().len(), 10);
+
+    smmdb.set_min_time("150".to_string());
+    smmdb.set_max_time("170".to_string());
+    let ids: Vec<String> = smmdb
+        .get_visible_course_panels()
+        .iter()
+        .map(|panel| panel.get_id().clone())
+        .collect();
+    assert_eq!(ids, (5..=7).map(fixture_id).collect::<Vec<_>>());
+
+    smmdb.set_game_style(Some(GameStyle::Smb1));
+    smmdb.set_theme(Some(CourseTheme::Ground));
+    assert_eq!(smmdb.get_visible_course_panels().len(), 3);
+    smmdb.set_game_style(Some(GameStyle::Sm3dw));
+    assert!(smmdb.get_visible_course_panels().is_empty());
+    smmdb.set_game_style(None);
+    smmdb.set_theme(Some(CourseTheme::Castle));
+    assert!(smmdb.get_visible_course_panels().is_empty());
+}
+
+#[test]
+fn fill_candidates_are_top_visible_courses() {
+    let mut smmdb = Smmdb::new(None);
+    smmdb.set_courses(
+        (0..10)
+            .map(|index| {
+                let mut course = fixture_course(index);
+                if index >= 4 {
+                    course.course.mut_header().set_clear_condition(1);
+                }
+                course
+            })
+            .collect(),
+    );
+
+    let ids = |courses: Vec<Course2Response>| -> Vec<String> {
+        courses
+            .iter()
+            .map(|course| course.get_id().clone())
+            .collect()
+    };
+    assert_eq!(ids(smmdb.get_fill_candidates(None)).len(), 10);
+    assert_eq!(
+        ids(smmdb.get_fill_candidates(Some(3))),
+        (0..3).map(fixture_id).collect::<Vec<_>>()
+    );
+
+    smmdb
+        .get_meta_filter_mut()
+        .set_clear_condition(Some(ClearConditionPresence::With));
+    assert_eq!(
+        ids(smmdb.get_fill_candidates(Some(3))),
+        (4..7).map(fixture_id).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn reports_malformed_json() {
+    let server = MockServer::start();
+    let mut smmdb = Smmdb::new(None);
+    smmdb.set_title("malformed".to_string());
+
+    let res = block_on(Smmdb::update(
+        server.get_base_url(),
+        smmdb.get_query_params().clone(),
+        None,
+    ));
+
+    match res {
+        Err(ApiError::MalformedJson(_)) => {}
+        res => panic!("expected malformed JSON error, got {:?}", res),
+    }
+}
+
+#[test]
+fn reports_unreachable_server() {
+    let res = block_on(Smmdb::update(
+        "http://127.0.0.1:1".to_string(),
+        default_query_params(),
+        None,
+    ));
+
+    assert!(res.unwrap_err().is_connection_error());
+}
+
+#[test]
+fn set_own_vote_updates_votes() {
+    let mut course = fixture_course(0);
+    assert_eq!(course.get_votes(), 10);
+
+    course.set_own_vote(1);
+    assert_eq!(course.get_own_vote(), 1);
+    assert_eq!(course.get_votes(), 11);
+
+    course.set_own_vote(-1);
+    assert_eq!(course.get_own_vote(), -1);
+    assert_eq!(course.get_votes(), 9);
+
+    course.set_own_vote(0);
+    assert_eq!(course.get_own_vote(), 0);
+    assert_eq!(course.get_votes(), 10);
+}
+
+#[test]
+fn votes_with_valid_apikey() {
+    let server = MockServer::start();
+    let course_id = fixture_id(3);
+
+    block_on(Smmdb::vote(
+        server.get_base_url(),
+        course_id.clone(),
+        -1,
+        VALID_APIKEY.to_string(),
+    ))
+    .unwrap();
+
+    assert_eq!(server.get_vote(&course_id), Some(-1));
+}
+
+#[test]
+fn rejects_vote_with_invalid_apikey() {
+    let server = MockServer::start();
+    let course_id = fixture_id(3);
+
+    let res = block_on(Smmdb::vote(
+        server.get_base_url(),
+        course_id.clone(),
+        1,
+        "invalid".to_string(),
+    ));
+
+    assert_eq!(res, Err(ApiError::Unauthorized));
+    assert_eq!(server.get_vote(&course_id), None);
+}
+
+#[test]
+fn signs_in() {
+    let server = MockServer::start();
+
+    let account = block_on(Smmdb::try_sign_in(
+        server.get_base_url(),
+        VALID_APIKEY.to_string(),
+    ))
+    .unwrap();
+    assert_eq!(account.get_id(), ACCOUNT_ID);
+    assert_eq!(account.get_username(), "Mock");
+    assert_eq!(
+        block_on(Smmdb::try_sign_in(
+            server.get_base_url(),
+            "invalid".to_string()
+        )),
+        Err(ApiError::Unauthorized)
+    );
+}
+
+#[test]
+fn fetches_course() {
+    let server = MockServer::start();
+    let course_id = fixture_id(7);
+
+    let data = block_on(Smmdb::fetch_course(
+        server.get_base_url(),
+        course_id.clone(),
+    ))
+    .unwrap();
+
+    assert_eq!(data, fixture_course_data(&course_id));
+}
+
+#[test]
+fn reports_missing_course() {
+    let server = MockServer::start();
+
+    let res = block_on(Smmdb::fetch_course(
+        server.get_base_url(),
+        "unknown".to_string(),
+    ));
+
+    assert_eq!(res, Err(ApiError::NotFound));
+}
+
+fn download(server: &MockServer, course_id: &str) -> Vec<Progress> {
+    let download = Download {
+        id: 3,
+        url: get_url(
+            &server.get_base_url(),
+            &format!("courses2/download/{}", course_id),
+        ),
+    };
+
+    block_on(async move {
+        let mut stream = Recipe::<DefaultHasher, ()>::stream(
+            Box::new(download),
+            futures::stream::empty().boxed(),
+        );
+        let mut events = vec![];
+        while let Some((job_id, event)) = stream.next().await {
+            assert_eq!(job_id, 3);
+            events.push(event);
+        }
+        events
+    })
+}
+
+#[test]
+fn download_reports_progress() {
+    let server = MockServer::start();
+    let course_id = fixture_id(5);
+
+    let events = download(&server, &course_id);
+
+    assert!(matches!(events.first(), Some(Progress::Started)));
+    let percentages: Vec<f32> = events
+        .iter()
+        .filter_map(|event| match event {
+            Progress::Advanced(percentage) => Some(*percentage),
+            _ => None,
+        })
+        .collect();
+    assert!(!percentages.is_empty());
+    assert!(percentages.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(percentages.last(), Some(&100.));
+    match events.last() {
+        Some(Progress::Finished(data)) => assert_eq!(data, &fixture_course_data(&course_id)),
+        event => panic!("expected finished download, got {:?}", event),
+    }
+}
+
+#[test]
+fn download_without_content_length_reports_received_bytes() {
+    let server = MockServer::start();
+    server.set_chunked_downloads(true);
+    let course_id = fixture_id(5);
+
+    let events = download(&server, &course_id);
+
+    assert!(matches!(events.first(), Some(Progress::Started)));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Progress::Advanced(_))));
+    let received: Vec<u64> = events
+        .iter()
+        .filter_map(|event| match event {
+            Progress::Received(received) => Some(*received),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(received.last(), Some(&(COURSE_DATA_SIZE as u64)));
+    match events.last() {
+        Some(Progress::Finished(data)) => assert_eq!(data, &fixture_course_data(&course_id)),
+        event => panic!("expected finished download, got {:?}", event),
+    }
+}
+
+#[test]
+fn download_resumes_interrupted_transfer() {
+    let server = MockServer::start();
+    server.interrupt_next_download(10_000);
+    let course_id = fixture_id(5);
+
+    let events = download(&server, &course_id);
+
+    match events.last() {
+        Some(Progress::Finished(data)) => assert_eq!(data, &fixture_course_data(&course_id)),
+        event => panic!("expected finished download, got {:?}", event),
+    }
+    assert_eq!(
+        server.get_download_ranges(),
+        vec![None, Some("bytes=10000-".to_string())]
+    );
+}
+
+#[test]
+fn download_reports_missing_course() {
+    let server = MockServer::start();
+
+    let events = download(&server, "unknown");
+
+    assert!(matches!(
+        events.last(),
+        Some(Progress::Errored(ApiError::NotFound))
+    ));
+}
+
+fn upload(server: &MockServer, path: &str, apikey: &str, data: Vec<u8>) -> Vec<UploadProgress> {
+    let upload = Upload {
+        id: 0,
+        save_index: 0,
+        url: get_url(&server.get_base_url(), path),
+        apikey: apikey.to_string(),
+        data: Arc::new(data),
+    };
+
+    block_on(async move {
+        let mut stream =
+            Recipe::<DefaultHasher, ()>::stream(Box::new(upload), futures::stream::empty().boxed());
+        let mut events = vec![];
+        while let Some(event) = stream.next().await {
+            let is_done = matches!(
+                event,
+                UploadProgress::Finished(_) | UploadProgress::Errored(_)
+            );
+            events.push(event);
+            if is_done {
+                break;
+            }
+        }
+        events
+    })
+}
+
+#[test]
+fn upload_reports_progress() {
+    let server = MockServer::start();
+    let data = fixture_course_data("upload");
+
+    let events = upload(&server, "courses2", VALID_APIKEY, data.clone());
+
+    assert!(matches!(events.first(), Some(UploadProgress::Started)));
+    let percentages: Vec<f32> = events
+        .iter()
+        .filter_map(|event| match event {
+            UploadProgress::Advanced(percentage) => Some(*percentage),
+            _ => None,
+        })
+        .collect();
+    assert!(percentages.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(percentages.last(), Some(&100.));
+    match events.last() {
+        Some(UploadProgress::Finished(course)) => {
+            assert_eq!(course.get_id(), &fixture_id(COURSE_COUNT))
+        }
+        event => panic!("expected finished upload, got {:?}", event),
+    }
+    assert_eq!(server.get_uploads(), vec![data]);
+}
+
+#[test]
+fn upload_surfaces_validation_errors() {
+    let server = MockServer::start();
+
+    match upload(&server, "courses2", VALID_APIKEY, vec![]).last() {
+        Some(UploadProgress::Errored(ApiError::Rejected(reason))) => {
+            assert_eq!(reason, "Invalid course file")
+        }
+        event => panic!("expected rejected upload, got {:?}", event),
+    }
+    match upload(&server, "courses2", VALID_APIKEY, b"duplicate".to_vec()).last() {
+        Some(UploadProgress::Errored(ApiError::Rejected(reason))) => {
+            assert_eq!(reason, "Duplicate: Course already exists")
+        }
+        event => panic!("expected rejected upload, got {:?}", event),
+    }
+    assert!(server.get_uploads().is_empty());
+}
+
+#[test]
+fn rejects_upload_with_invalid_apikey() {
+    let server = MockServer::start();
+
+    let events = upload(
+        &server,
+        "courses2",
+        "invalid",
+        fixture_course_data("upload"),
+    );
+
+    assert!(matches!(
+        events.last(),
+        Some(UploadProgress::Errored(ApiError::Unauthorized))
+    ));
+    assert!(server.get_uploads().is_empty());
+}
+
+#[test]
+fn lists_own_courses() {
+    let server = MockServer::start();
+    let mut smmdb = Smmdb::new(Some(VALID_APIKEY.to_string()));
+    smmdb.set_owner(Some(ACCOUNT_ID.to_string()));
+
+    let courses = block_on(Smmdb::update(
+        server.get_base_url(),
+        smmdb.get_query_params().clone(),
+        Some(VALID_APIKEY.to_string()),
+    ))
+    .unwrap();
+
+    assert_eq!(server.get_queries()[0].get_owner().unwrap(), ACCOUNT_ID);
+    assert_eq!(courses.len(), COURSE_COUNT / 2);
+    assert!(courses
+        .iter()
+        .all(|course| course.get_owner() == ACCOUNT_ID));
+}
+
+#[test]
+fn deletes_own_course() {
+    let server = MockServer::start();
+
+    block_on(Smmdb::delete_course(
+        server.get_base_url(),
+        fixture_id(0),
+        VALID_APIKEY.to_string(),
+    ))
+    .unwrap();
+    assert!(server.get_course(&fixture_id(0)).is_none());
+
+    let res = block_on(Smmdb::delete_course(
+        server.get_base_url(),
+        fixture_id(1),
+        VALID_APIKEY.to_string(),
+    ));
+    assert_eq!(res, Err(ApiError::Forbidden));
+    assert!(server.get_course(&fixture_id(1)).is_some());
+}
+
+#[test]
+fn changes_course_difficulty() {
+    let server = MockServer::start();
+
+    block_on(Smmdb::set_course_difficulty(
+        server.get_base_url(),
+        fixture_id(2),
+        Difficulty::Expert,
+        VALID_APIKEY.to_string(),
+    ))
+    .unwrap();
+
+    assert_eq!(
+        server.get_course(&fixture_id(2)).unwrap().get_difficulty(),
+        Some(&Difficulty::Expert)
+    );
+}
+
+#[test]
+fn replaces_course_data() {
+    let server = MockServer::start();
+    let course_id = fixture_id(4);
+    let data = fixture_course_data("replacement");
+
+    let events = upload(
+        &server,
+        &format!("courses2/{}", course_id),
+        VALID_APIKEY,
+        data.clone(),
+    );
+
+    match events.last() {
+        Some(UploadProgress::Finished(course)) => {
+            assert_eq!(course.get_id(), &course_id);
+            assert!(course.get_last_modified() > fixture_course(4).get_last_modified());
+        }
+        event => panic!("expected finished upload, got {:?}", event),
+    }
+    assert_eq!(server.get_uploads(), vec![data]);
+}
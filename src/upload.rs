@@ -0,0 +1,97 @@
+use crate::{
+    smmdb::{ApiError, Course2Response},
+    Smmdb,
+};
+
+use futures::{channel::mpsc, future, stream, StreamExt};
+use reqwest::Body;
+use std::{io, sync::Arc};
+
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// Each upload has its own id, so uploading the same course again starts a new upload.
+pub struct Upload {
+    pub id: u64,
+    pub save_index: usize,
+    pub url: String,
+    pub apikey: String,
+    pub data: Arc<Vec<u8>>,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for Upload
+where
+    H: std::hash::Hasher,
+{
+    type Output = UploadProgress;
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        self.id.hash(state);
+        self.save_index.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: futures::stream::BoxStream<'static, I>,
+    ) -> futures::stream::BoxStream<'static, Self::Output> {
+        let Upload {
+            url, apikey, data, ..
+        } = *self;
+        let (sender, receiver) = mpsc::unbounded();
+        let total = data.len();
+
+        // Progress is reported whenever the request body hands out the next chunk.
+        let progress_sender = sender.clone();
+        let mut uploaded = 0;
+        let chunks: Vec<Result<Vec<u8>, io::Error>> = data
+            .chunks(CHUNK_SIZE)
+            .map(|chunk| Ok(chunk.to_vec()))
+            .collect();
+        let body = stream::iter(chunks).inspect(move |chunk| {
+            if let Ok(chunk) = chunk {
+                uploaded += chunk.len();
+                let percentage = (uploaded as f32 / total as f32) * 100.0;
+                progress_sender
+                    .unbounded_send(UploadProgress::Advanced(percentage))
+                    .ok();
+            }
+        });
+
+        let request = async move {
+            let progress =
+                match Smmdb::put_course(url, apikey, Body::wrap_stream(body), total).await {
+                    Ok(course) => UploadProgress::Finished(course),
+                    Err(err) => UploadProgress::Errored(err),
+                };
+            sender.unbounded_send(progress).ok();
+        };
+
+        Box::pin(
+            stream::once(future::ready(UploadProgress::Started))
+                .chain(stream::select(
+                    receiver,
+                    stream::once(request).filter_map(|_| future::ready(None)),
+                ))
+                .scan(false, |is_done, progress| {
+                    if *is_done {
+                        return future::ready(None);
+                    }
+                    *is_done = matches!(
+                        progress,
+                        UploadProgress::Finished(_) | UploadProgress::Errored(_)
+                    );
+                    future::ready(Some(progress))
+                }),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum UploadProgress {
+    Started,
+    Advanced(f32),
+    Finished(Course2Response),
+    Errored(ApiError),
+}
@@ -0,0 +1,136 @@
+use crate::settings::get_config_dir;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{read, write},
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct UploadRegistry {
+    saves: HashMap<String, HashMap<usize, String>>,
+}
+
+impl UploadRegistry {
+    pub fn load() -> UploadRegistry {
+        UploadRegistry::get_path()
+            .ok()
+            .and_then(|path| read(path).ok())
+            .and_then(|registry| serde_json::from_slice(&registry).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        write(UploadRegistry::get_path()?, serde_json::to_string(&self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, location: &Path, index: usize) -> Option<&String> {
+        self.saves.get(&get_key(location))?.get(&index)
+    }
+
+    pub fn set(&mut self, location: &Path, index: usize, smmdb_id: String) {
+        self.saves
+            .entry(get_key(location))
+            .or_default()
+            .insert(index, smmdb_id);
+    }
+
+    pub fn get_slots(&self, location: &Path, len: usize) -> Vec<Option<String>> {
+        (0..len)
+            .map(|index| self.get(location, index).cloned())
+            .collect()
+    }
+
+    pub fn set_slots(&mut self, location: &Path, smmdb_ids: &[Option<String>]) {
+        self.saves.insert(
+            get_key(location),
+            smmdb_ids
+                .iter()
+                .enumerate()
+                .filter_map(|(index, smmdb_id)| Some((index, smmdb_id.clone()?)))
+                .collect(),
+        );
+    }
+
+    pub fn remove(&mut self, location: &Path, index: usize) {
+        if let Some(slots) = self.saves.get_mut(&get_key(location)) {
+            slots.remove(&index);
+        }
+    }
+
+    pub fn swap(&mut self, location: &Path, first: usize, second: usize) {
+        if let Some(slots) = self.saves.get_mut(&get_key(location)) {
+            let first_id = slots.remove(&first);
+            let second_id = slots.remove(&second);
+            if let Some(id) = first_id {
+                slots.insert(second, id);
+            }
+            if let Some(id) = second_id {
+                slots.insert(first, id);
+            }
+        }
+    }
+
+    pub fn forget(&mut self, smmdb_id: &str) {
+        for slots in self.saves.values_mut() {
+            slots.retain(|_, id| id != smmdb_id);
+        }
+    }
+
+    fn get_path() -> Result<PathBuf> {
+        let mut path = get_config_dir()?;
+        path.push("uploads.json");
+        Ok(path)
+    }
+}
+
+fn get_key(location: &Path) -> String {
+    location.to_string_lossy().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_slots_of_one_save_only() {
+        let first = Path::new("first");
+        let second = Path::new("second");
+        let mut uploads = UploadRegistry::default();
+        uploads.set(first, 0, "a".to_string());
+        uploads.set(first, 3, "b".to_string());
+        uploads.set(second, 1, "c".to_string());
+
+        uploads.set_slots(first, &[None, Some("b".to_string()), None]);
+
+        assert_eq!(
+            uploads.get_slots(first, 4),
+            vec![None, Some("b".to_string()), None, None]
+        );
+        assert_eq!(
+            uploads.get_slots(second, 2),
+            vec![None, Some("c".to_string())]
+        );
+    }
+
+    #[test]
+    fn forgets_deleted_courses_in_every_save() {
+        let first = Path::new("first");
+        let second = Path::new("second");
+        let mut uploads = UploadRegistry::default();
+        uploads.set(first, 0, "a".to_string());
+        uploads.set(first, 1, "b".to_string());
+        uploads.set(second, 2, "a".to_string());
+
+        uploads.forget("a");
+
+        assert_eq!(
+            uploads.get_slots(first, 2),
+            vec![None, Some("b".to_string())]
+        );
+        assert_eq!(uploads.get_slots(second, 3), vec![None, None, None]);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    download_queue::{DownloadQueue, JobStatus},
+    font,
+    styles::*,
+    Message,
+};
+
+use iced::{button, Align, Button, Column, Element, Length, ProgressBar, Row, Space, Text};
+
+#[derive(Clone, Debug, Default)]
+pub struct DownloadsWidget {
+    job_states: Vec<JobState>,
+}
+
+impl DownloadsWidget {
+    pub fn new() -> DownloadsWidget {
+        DownloadsWidget::default()
+    }
+
+    pub fn view(&mut self, downloads: &DownloadQueue) -> Element<Message> {
+        if downloads.is_empty() {
+            return Space::with_height(Length::Shrink).into();
+        }
+
+        let jobs = downloads.get_jobs();
+        self.job_states.resize_with(jobs.len(), JobState::default);
+        let mut content = Column::new()
+            .spacing(4)
+            .push(Text::new("Downloads:").font(font::HELVETICA_BOLD).size(16));
+        for (job, job_state) in jobs.iter().zip(self.job_states.iter_mut()) {
+            let status: Element<Message> = match job.get_status() {
+                JobStatus::Pending => Text::new("Queued").size(15).color(TEXT_HELP_COLOR).into(),
+                JobStatus::Running(Some(progress)) => ProgressBar::new(0.0..=100.0, *progress)
+                    .height(Length::Units(16))
+                    .into(),
+                JobStatus::Running(None) => Text::new("Downloading...").size(15).into(),
+                JobStatus::Failed(err) => Text::new(format!("Failed: {}", err))
+                    .size(15)
+                    .color(TEXT_DANGER_COLOR)
+                    .into(),
+            };
+            let mut row = Row::new()
+                .align_items(Align::Center)
+                .push(
+                    Text::new(format!(
+                        "Slot {}: {}",
+                        job.get_save_index() + 1,
+                        job.get_title()
+                    ))
+                    .size(15)
+                    .width(Length::FillPortion(1)),
+                )
+                .push(Space::with_width(Length::Units(8)))
+                .push(Column::new().width(Length::FillPortion(1)).push(status))
+                .push(Space::with_width(Length::Units(8)));
+            if let JobStatus::Failed(_) = job.get_status() {
+                row = row
+                    .push(
+                        Button::new(&mut job_state.retry_state, Text::new("Retry"))
+                            .style(DefaultButtonStyle)
+                            .on_press(Message::RetryDownload(job.get_id())),
+                    )
+                    .push(Space::with_width(Length::Units(4)));
+            }
+            row = row.push(
+                Button::new(
+                    &mut job_state.cancel_state,
+                    Text::new(match job.get_status() {
+                        JobStatus::Failed(_) => "Dismiss",
+                        _ => "Cancel",
+                    }),
+                )
+                .style(DefaultButtonDangerStyle)
+                .on_press(Message::CancelDownload(job.get_id())),
+            );
+            content = content.push(row);
+        }
+        content.into()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct JobState {
+    retry_state: button::State,
+    cancel_state: button::State,
+}
@@ -0,0 +1,290 @@
+use iced_native::{
+    layout, mouse, overlay, Background, Clipboard, Color, Element, Event, Hasher, Layout, Length,
+    Point, Rectangle, Widget,
+};
+use iced_wgpu::{Primitive, Renderer};
+
+use crate::styles::COLOR_GREEN;
+
+const DRAG_THRESHOLD: f32 = 8.;
+const INDICATOR_WIDTH: f32 = 4.;
+
+/// Dropping a child in the middle of another one swaps both, dropping it close to the edge
+/// of another one inserts it there.
+pub struct DragList<'a, Message> {
+    state: &'a mut State,
+    content: Element<'a, Message, Renderer>,
+    on_drop: Box<dyn Fn(usize, DropTarget) -> Message + 'a>,
+    is_enabled: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct State {
+    drag: Option<Drag>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+    from: usize,
+    origin: Point,
+    is_dragging: bool,
+    target: Option<DropTarget>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DropTarget {
+    /// Onto the child with the given index.
+    Swap(usize),
+    /// Into the gap before the child with the given index.
+    Insert(usize),
+}
+
+impl DropTarget {
+    pub fn get_index(&self, from: usize) -> usize {
+        match *self {
+            DropTarget::Swap(index) => index,
+            DropTarget::Insert(index) if index > from => index - 1,
+            DropTarget::Insert(index) => index,
+        }
+    }
+}
+
+impl<'a, Message> DragList<'a, Message> {
+    pub fn new<T, F>(state: &'a mut State, content: T, on_drop: F) -> Self
+    where
+        T: Into<Element<'a, Message, Renderer>>,
+        F: 'a + Fn(usize, DropTarget) -> Message,
+    {
+        DragList {
+            state,
+            content: content.into(),
+            on_drop: Box::new(on_drop),
+            is_enabled: true,
+        }
+    }
+
+    pub fn enabled(mut self, is_enabled: bool) -> Self {
+        self.is_enabled = is_enabled;
+        self
+    }
+}
+
+impl<'a, Message> Widget<Message, Renderer> for DragList<'a, Message> {
+    fn width(&self) -> Length {
+        self.content.width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.height()
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.layout(renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &<Renderer as iced_native::Renderer>::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+    ) -> (Primitive, mouse::Interaction) {
+        let (content, interaction) = self
+            .content
+            .draw(renderer, defaults, layout, cursor_position);
+        match self.state.drag {
+            Some(Drag {
+                is_dragging: true,
+                target,
+                ..
+            }) => {
+                let mut primitives = vec![content];
+                if let Some(indicator) = target.and_then(|target| draw_indicator(layout, target)) {
+                    primitives.push(indicator);
+                }
+                (
+                    Primitive::Group { primitives },
+                    mouse::Interaction::Grabbing,
+                )
+            }
+            _ => (content, interaction),
+        }
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        use std::hash::Hash;
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.content.hash_layout(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<Message>,
+        renderer: &Renderer,
+        clipboard: Option<&dyn Clipboard>,
+    ) {
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) if self.is_enabled => {
+                self.state.drag = layout
+                    .children()
+                    .position(|child| child.bounds().contains(cursor_position))
+                    .map(|from| Drag {
+                        from,
+                        origin: cursor_position,
+                        is_dragging: false,
+                        target: None,
+                    });
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                if let Some(drag) = &mut self.state.drag {
+                    let (dx, dy) = (
+                        cursor_position.x - drag.origin.x,
+                        cursor_position.y - drag.origin.y,
+                    );
+                    if (dx * dx + dy * dy).sqrt() > DRAG_THRESHOLD {
+                        drag.is_dragging = true;
+                    }
+                    if drag.is_dragging {
+                        drag.target = get_drop_target(layout, cursor_position, drag.from);
+                    }
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if let Some(Drag {
+                    from,
+                    is_dragging: true,
+                    target: Some(target),
+                    ..
+                }) = self.state.drag.take()
+                {
+                    messages.push((self.on_drop)(from, target));
+                }
+            }
+            _ => {}
+        }
+
+        self.content.on_event(
+            event,
+            layout,
+            cursor_position,
+            messages,
+            renderer,
+            clipboard,
+        );
+    }
+
+    fn overlay(&mut self, layout: Layout<'_>) -> Option<overlay::Element<'_, Message, Renderer>> {
+        self.content.overlay(layout)
+    }
+}
+
+/// Returns where a child would be dropped, unless dropping it there changes nothing.
+fn get_drop_target(layout: Layout<'_>, cursor_position: Point, from: usize) -> Option<DropTarget> {
+    if !layout.bounds().contains(cursor_position) {
+        return None;
+    }
+    let children: Vec<Rectangle> = layout.children().map(|child| child.bounds()).collect();
+    let target = children
+        .iter()
+        .enumerate()
+        .find(|(_, bounds)| cursor_position.y < bounds.y + bounds.height)
+        .map(|(index, bounds)| {
+            let offset = (cursor_position.y - bounds.y) / bounds.height;
+            if offset < 0.25 {
+                DropTarget::Insert(index)
+            } else if offset > 0.75 {
+                DropTarget::Insert(index + 1)
+            } else {
+                DropTarget::Swap(index)
+            }
+        })
+        .unwrap_or(DropTarget::Insert(children.len()));
+    if target.get_index(from) == from {
+        None
+    } else {
+        Some(target)
+    }
+}
+
+fn draw_indicator(layout: Layout<'_>, target: DropTarget) -> Option<Primitive> {
+    let children: Vec<Rectangle> = layout.children().map(|child| child.bounds()).collect();
+    match target {
+        DropTarget::Swap(index) => children.get(index).map(|bounds| Primitive::Quad {
+            bounds: *bounds,
+            background: Background::Color(Color::TRANSPARENT),
+            border_radius: 8,
+            border_width: INDICATOR_WIDTH as u16,
+            border_color: COLOR_GREEN,
+        }),
+        DropTarget::Insert(index) => {
+            let y = match (
+                index.checked_sub(1).and_then(|i| children.get(i)),
+                children.get(index),
+            ) {
+                (Some(before), Some(after)) => (before.y + before.height + after.y) / 2.,
+                (Some(before), None) => before.y + before.height,
+                (None, Some(after)) => after.y,
+                (None, None) => return None,
+            };
+            let bounds = layout.bounds();
+            Some(Primitive::Quad {
+                bounds: Rectangle {
+                    x: bounds.x,
+                    y: y - INDICATOR_WIDTH / 2.,
+                    width: bounds.width,
+                    height: INDICATOR_WIDTH,
+                },
+                background: Background::Color(COLOR_GREEN),
+                border_radius: 2,
+                border_width: 0,
+                border_color: Color::TRANSPARENT,
+            })
+        }
+    }
+}
+
+impl<'a, Message> From<DragList<'a, Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(drag_list: DragList<'a, Message>) -> Element<'a, Message, Renderer> {
+        Element::new(drag_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swaps_in_place() {
+        assert_eq!(DropTarget::Swap(2).get_index(5), 2);
+        assert_eq!(DropTarget::Swap(5).get_index(2), 5);
+    }
+
+    #[test]
+    fn inserts_before_the_dragged_child() {
+        assert_eq!(DropTarget::Insert(0).get_index(3), 0);
+        assert_eq!(DropTarget::Insert(2).get_index(3), 2);
+        assert_eq!(DropTarget::Insert(3).get_index(3), 3);
+    }
+
+    #[test]
+    fn inserts_after_the_dragged_child() {
+        // The gap before index 5 is at index 4, once the child at index 2 has been taken out.
+        assert_eq!(DropTarget::Insert(5).get_index(2), 4);
+        assert_eq!(DropTarget::Insert(4).get_index(3), 3);
+    }
+
+    #[test]
+    fn inserts_at_the_end_of_the_list() {
+        let len = 6;
+        assert_eq!(DropTarget::Insert(len).get_index(0), len - 1);
+        assert_eq!(DropTarget::Insert(len).get_index(len - 1), len - 1);
+    }
+}
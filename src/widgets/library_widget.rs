@@ -0,0 +1,144 @@
+use crate::{
+    components::LibraryCoursePanel, font, library::Library, pages::Slot, styles::*, AppState,
+    Message,
+};
+
+use iced::{
+    button, scrollable, text_input, Button, Column, Element, Length, Scrollable, Text, TextInput,
+};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug)]
+pub struct LibraryWidget {
+    state: scrollable::State,
+    filter: String,
+    filter_state: text_input::State,
+    dismiss_state: button::State,
+    course_panels: HashMap<String, LibraryCoursePanel>,
+}
+
+impl LibraryWidget {
+    pub fn new() -> LibraryWidget {
+        LibraryWidget {
+            state: scrollable::State::new(),
+            filter: String::new(),
+            filter_state: text_input::State::new(),
+            dismiss_state: button::State::new(),
+            course_panels: HashMap::new(),
+        }
+    }
+
+    pub fn has_focus(&self) -> bool {
+        self.filter_state.is_focused() || self.course_panels.values().any(|panel| panel.has_focus())
+    }
+
+    pub fn view<'a>(
+        &'a mut self,
+        state: &AppState,
+        library: &Library,
+        slots: &[Slot],
+        is_staged: bool,
+    ) -> Element<'a, Message> {
+        let entries: Vec<_> = library
+            .get_entries()
+            .iter()
+            .filter(|entry| entry.matches(&self.filter))
+            .collect();
+        self.course_panels
+            .retain(|id, _| library.get_entry(id).is_some());
+        for entry in entries.iter() {
+            self.course_panels
+                .entry(entry.get_id().clone())
+                .or_insert_with(LibraryCoursePanel::new);
+        }
+
+        let mut content = Scrollable::new(&mut self.state)
+            .padding(CONTAINER_PADDING)
+            .spacing(LIST_SPACING)
+            .push(Text::new("Library").font(font::SMME));
+
+        if let Some(error) = library.get_error() {
+            content = content.push(
+                Column::new()
+                    .spacing(8)
+                    .push(
+                        Text::new(error)
+                            .font(font::HELVETICA_BOLD)
+                            .size(18)
+                            .color(COLOR_DARK_RED),
+                    )
+                    .push(
+                        Button::new(&mut self.dismiss_state, Text::new("Dismiss"))
+                            .style(DefaultButtonDangerStyle)
+                            .on_press(Message::DismissLibraryError),
+                    ),
+            );
+        }
+
+        content = content
+            .push(
+                TextInput::new(
+                    &mut self.filter_state,
+                    "Filter by title or tag",
+                    &self.filter,
+                    Message::LibraryFilterChanged,
+                )
+                .style(DefaultTextInputStyle)
+                .padding(4),
+            )
+            .push(
+                Text::new(match library.get_entries().len() {
+                    0 => {
+                        "Stash courses from your save or from SMMDB to keep them here.".to_string()
+                    }
+                    1 => "1 course".to_string(),
+                    count if entries.len() < count => {
+                        format!("Showing {} of {} courses", entries.len(), count)
+                    }
+                    count => format!("{} courses", count),
+                })
+                .size(15)
+                .color(TEXT_HELP_COLOR),
+            );
+        if is_staged {
+            content = content.push(
+                Text::new("Apply or discard the staged changes to stash or move courses.")
+                    .size(15)
+                    .color(TEXT_HELP_COLOR),
+            );
+        }
+
+        let mut course_panels: HashMap<&String, &mut LibraryCoursePanel> =
+            self.course_panels.iter_mut().collect();
+        for entry in entries {
+            if let Some(panel) = course_panels.remove(entry.get_id()) {
+                content = content.push(panel.view(state, entry, slots, is_staged));
+            }
+        }
+
+        content.width(Length::FillPortion(1)).into()
+    }
+
+    pub fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+    }
+
+    pub fn set_tag_input(&mut self, id: &str, tag_input: String) {
+        if let Some(panel) = self.course_panels.get_mut(id) {
+            panel.set_tag_input(tag_input);
+        }
+    }
+
+    pub fn take_tag_input(&mut self, id: &str) -> String {
+        self.course_panels
+            .get_mut(id)
+            .map(|panel| panel.take_tag_input())
+            .unwrap_or_default()
+    }
+
+    pub fn select_slot(&mut self, id: &str, slot: Slot) {
+        if let Some(panel) = self.course_panels.get_mut(id) {
+            panel.select_slot(slot);
+        }
+    }
+}
@@ -1,5 +1,13 @@
+mod downloads_widget;
+mod drag_list;
+mod library_widget;
 mod save_widget;
+mod scroll_sentinel;
 mod smmdb_widget;
 
+pub use downloads_widget::*;
+pub use drag_list::*;
+pub use library_widget::*;
 pub use save_widget::*;
+pub use scroll_sentinel::*;
 pub use smmdb_widget::*;
@@ -1,47 +1,225 @@
-use crate::{components::CoursePanel, font, styles::*, AppState};
+use crate::{
+    components::CoursePanel,
+    download_queue::DownloadQueue,
+    font,
+    history::History,
+    pages::{PendingChange, SaveSide},
+    styles::*,
+    widgets::{drag_list, DownloadsWidget, DragList},
+    AppErrorState, AppState, Message,
+};
 
-use iced::{scrollable, Element, Length, Scrollable, Text};
+use iced::{
+    button, scrollable, Align, Button, Checkbox, Column, Element, Length, Row, Scrollable, Text,
+};
 
 #[derive(Clone, Debug)]
 pub struct SaveWidget {
     state: scrollable::State,
     course_panels: Vec<CoursePanel>,
+    downloads_widget: DownloadsWidget,
+    dismiss_error_state: button::State,
+    backups_state: button::State,
+    undo_state: button::State,
+    redo_state: button::State,
+    apply_state: button::State,
+    discard_state: button::State,
+    other_save_state: button::State,
+    drag_state: drag_list::State,
+    side: SaveSide,
+    has_other_save: bool,
 }
 
 impl SaveWidget {
-    pub fn new(save: &smmdb_lib::Save) -> SaveWidget {
-        let course_panels = save
-            .get_own_courses()
-            .iter()
-            .map(|course| CoursePanel::new(course.clone()))
-            .collect();
-        SaveWidget {
+    pub fn new(save: &smmdb_lib::Save, smmdb_ids: Vec<Option<String>>) -> SaveWidget {
+        let mut save_widget = SaveWidget {
             state: scrollable::State::new(),
-            course_panels,
-        }
+            course_panels: vec![],
+            downloads_widget: DownloadsWidget::new(),
+            dismiss_error_state: button::State::new(),
+            backups_state: button::State::new(),
+            undo_state: button::State::new(),
+            redo_state: button::State::new(),
+            apply_state: button::State::new(),
+            discard_state: button::State::new(),
+            other_save_state: button::State::new(),
+            drag_state: drag_list::State::default(),
+            side: SaveSide::Primary,
+            has_other_save: false,
+        };
+        save_widget.generate_course_panels(save, smmdb_ids, vec![]);
+        save_widget
     }
 
     pub fn view<'a>(
         &'a mut self,
         state: &AppState,
+        error_state: &AppErrorState,
         display_name: &String,
+        has_apikey: bool,
+        downloads: &DownloadQueue,
+        history: &History,
+        staged_operations: Option<&[String]>,
     ) -> Element<crate::Message> {
+        let is_busy = matches!(state, AppState::Loading);
+        let side = self.side;
+        let has_other_save = self.has_other_save;
+
+        let mut header = Row::new()
+            .align_items(Align::Center)
+            .spacing(8)
+            .push(Text::new(display_name).font(font::SMME).width(Length::Fill));
+        if side == SaveSide::Secondary {
+            header = header.push(
+                Button::new(&mut self.other_save_state, Text::new("Close"))
+                    .style(DefaultButtonDangerStyle)
+                    .on_press(Message::CloseOtherSave),
+            );
+            let mut content = Scrollable::new(&mut self.state)
+                .padding(CONTAINER_PADDING)
+                .spacing(LIST_SPACING)
+                .push(header);
+            for (index, panel) in self.course_panels.iter_mut().enumerate() {
+                content = content.push(panel.view(state, index, false, None, side, true, false));
+            }
+            return content.width(Length::FillPortion(1)).into();
+        }
+
+        let mut undo_button =
+            Button::new(&mut self.undo_state, Text::new("Undo")).style(DefaultButtonStyle);
+        if !is_busy && history.can_undo() {
+            undo_button = undo_button.on_press(Message::Undo);
+        }
+        let mut redo_button =
+            Button::new(&mut self.redo_state, Text::new("Redo")).style(DefaultButtonStyle);
+        if !is_busy && history.can_redo() {
+            redo_button = redo_button.on_press(Message::Redo);
+        }
+        header = header.push(undo_button).push(redo_button).push(
+            Button::new(&mut self.backups_state, Text::new("Backups"))
+                .style(DefaultButtonStyle)
+                .on_press(Message::OpenBackups),
+        );
+        if !has_other_save {
+            let mut other_save_button =
+                Button::new(&mut self.other_save_state, Text::new("Second save"))
+                    .style(DefaultButtonStyle);
+            if !is_busy {
+                other_save_button = other_save_button.on_press(Message::OpenOtherSave);
+            }
+            header = header.push(other_save_button);
+        }
+
         let mut content = Scrollable::new(&mut self.state)
             .padding(CONTAINER_PADDING)
             .spacing(LIST_SPACING)
-            .push(Text::new(display_name).font(font::SMME));
+            .push(header);
+        content = content.push(match staged_operations {
+            Some(operations) if !operations.is_empty() => {
+                let mut apply_button =
+                    Button::new(&mut self.apply_state, Text::new("Apply changes"))
+                        .style(DefaultButtonStyle);
+                let mut discard_button = Button::new(&mut self.discard_state, Text::new("Discard"))
+                    .style(DefaultButtonDangerStyle);
+                if !is_busy {
+                    apply_button = apply_button.on_press(Message::ApplyStagedChanges);
+                    discard_button = discard_button.on_press(Message::DiscardStagedChanges);
+                }
+                Column::new()
+                    .spacing(8)
+                    .push(
+                        Row::new()
+                            .align_items(Align::Center)
+                            .spacing(8)
+                            .push(
+                                Text::new(match operations.len() {
+                                    1 => "1 pending change".to_string(),
+                                    count => format!("{} pending changes", count),
+                                })
+                                .font(font::HELVETICA_BOLD)
+                                .width(Length::Fill),
+                            )
+                            .push(discard_button)
+                            .push(apply_button),
+                    )
+                    .push(
+                        operations
+                            .iter()
+                            .fold(Column::new().spacing(2), |column, operation| {
+                                column.push(Text::new(operation).size(14).color(TEXT_HELP_COLOR))
+                            }),
+                    )
+            }
+            staged_operations => Column::new().push(Checkbox::new(
+                staged_operations.is_some(),
+                "Stage changes and write them to the save at once",
+                Message::ToggleStagedMode,
+            )),
+        });
+        if let AppErrorState::Some(err) = error_state {
+            content = content.push(
+                Column::new()
+                    .spacing(8)
+                    .push(
+                        Text::new(err)
+                            .font(font::HELVETICA_BOLD)
+                            .size(18)
+                            .color(COLOR_DARK_RED),
+                    )
+                    .push(
+                        Button::new(&mut self.dismiss_error_state, Text::new("Dismiss"))
+                            .style(DefaultButtonDangerStyle)
+                            .on_press(Message::ResetState),
+                    ),
+            );
+        }
+        content = content.push(self.downloads_widget.view(downloads));
+        let mut course_panels = Column::new().spacing(LIST_SPACING);
         for (index, panel) in self.course_panels.iter_mut().enumerate() {
-            content = content.push(panel.view(state, index));
+            course_panels = course_panels.push(panel.view(
+                state,
+                index,
+                has_apikey,
+                downloads.get_job_for_slot(index),
+                side,
+                has_other_save,
+                staged_operations.is_some(),
+            ));
         }
+        content = content.push(
+            DragList::new(&mut self.drag_state, course_panels, Message::DropCourse)
+                .enabled(matches!(state, AppState::Default)),
+        );
 
         content.width(Length::FillPortion(1)).into()
     }
 
-    pub fn generate_course_panels(&mut self, save: &smmdb_lib::Save) {
+    pub fn set_side(&mut self, side: SaveSide) {
+        self.side = side;
+    }
+
+    pub fn set_has_other_save(&mut self, has_other_save: bool) {
+        self.has_other_save = has_other_save;
+    }
+
+    pub fn generate_course_panels(
+        &mut self,
+        save: &smmdb_lib::Save,
+        smmdb_ids: Vec<Option<String>>,
+        pending_changes: Vec<Option<PendingChange>>,
+    ) {
         self.course_panels = save
             .get_own_courses()
             .iter()
-            .map(|course| CoursePanel::new(course.clone()))
+            .zip(smmdb_ids)
+            .enumerate()
+            .map(|(index, (course, smmdb_id))| {
+                CoursePanel::new(
+                    course.clone(),
+                    smmdb_id,
+                    pending_changes.get(index).cloned().flatten(),
+                )
+            })
             .collect();
     }
 }
@@ -0,0 +1,149 @@
+use iced_native::{
+    layout, mouse, overlay, scrollable, Clipboard, Element, Event, Hasher, Layout, Length, Point,
+    Rectangle, Widget,
+};
+
+/// How far below the visible part of the content its end may be, to count as approached.
+const APPROACH_DISTANCE: f32 = 1000.;
+/// How far a `Scrollable` scrolls per line of a mouse wheel.
+const LINE_HEIGHT: f32 = 60.;
+
+/// Emits a message, when the end of the content of a `Scrollable` comes close to its viewport.
+///
+/// The scroll position is taken from a copy of the scrollable's state, which is made
+/// before the scrollable borrows it. Mouse wheel events are applied to the copy, just like
+/// the scrollable applies them.
+pub struct ScrollSentinel<'a, Message, Renderer> {
+    state: &'a mut State,
+    scroll: scrollable::State,
+    content: Element<'a, Message, Renderer>,
+    on_approach: Option<Message>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct State {
+    approached_at: Option<f32>,
+}
+
+impl<'a, Message, Renderer> ScrollSentinel<'a, Message, Renderer> {
+    pub fn new<T>(state: &'a mut State, scroll: scrollable::State, content: T) -> Self
+    where
+        T: Into<Element<'a, Message, Renderer>>,
+    {
+        ScrollSentinel {
+            state,
+            scroll,
+            content: content.into(),
+            on_approach: None,
+        }
+    }
+
+    pub fn on_approach(mut self, message: Message) -> Self {
+        self.on_approach = Some(message);
+        self
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for ScrollSentinel<'a, Message, Renderer>
+where
+    Message: Clone,
+    Renderer: iced_native::Renderer,
+{
+    fn width(&self) -> Length {
+        self.content.width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.height()
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.layout(renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+    ) -> Renderer::Output {
+        self.content
+            .draw(renderer, defaults, layout, cursor_position)
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        use std::hash::Hash;
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.content.hash_layout(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<Message>,
+        renderer: &Renderer,
+        clipboard: Option<&dyn Clipboard>,
+    ) {
+        let bounds = layout.bounds();
+        let content_bounds = match layout.children().next() {
+            Some(content) => content.bounds(),
+            None => return,
+        };
+        if let Event::Mouse(mouse::Event::WheelScrolled { delta }) = &event {
+            if bounds.contains(cursor_position) {
+                let delta_y = match *delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y * LINE_HEIGHT,
+                    mouse::ScrollDelta::Pixels { y, .. } => y,
+                };
+                self.scroll.scroll(delta_y, bounds, content_bounds);
+            }
+        }
+
+        self.content.on_event(
+            event,
+            layout,
+            cursor_position,
+            messages,
+            renderer,
+            clipboard,
+        );
+
+        let offset = self.scroll.offset(bounds, content_bounds) as f32;
+        if is_end_approached(bounds, content_bounds, offset) {
+            if let Some(message) = &self.on_approach {
+                if self.state.approached_at != Some(content_bounds.height) {
+                    self.state.approached_at = Some(content_bounds.height);
+                    messages.push(message.clone());
+                }
+            }
+        } else {
+            self.state.approached_at = None;
+        }
+    }
+
+    fn overlay(&mut self, layout: Layout<'_>) -> Option<overlay::Element<'_, Message, Renderer>> {
+        self.content.overlay(layout)
+    }
+}
+
+fn is_end_approached(bounds: Rectangle, content_bounds: Rectangle, offset: f32) -> bool {
+    let visible_end = bounds.y + offset + bounds.height;
+    let content_end = content_bounds.y + content_bounds.height;
+    content_end - visible_end < APPROACH_DISTANCE
+}
+
+impl<'a, Message, Renderer> From<ScrollSentinel<'a, Message, Renderer>>
+    for Element<'a, Message, Renderer>
+where
+    Message: 'a + Clone,
+    Renderer: 'a + iced_native::Renderer,
+{
+    fn from(sentinel: ScrollSentinel<'a, Message, Renderer>) -> Element<'a, Message, Renderer> {
+        Element::new(sentinel)
+    }
+}
@@ -1,41 +1,124 @@
 use crate::{
+    course_meta::{Choice, ClearConditionPresence, CourseTheme, DayTime, GameStyle},
     font,
-    smmdb::{Difficulty, SortOptions, SORT_OPTIONS},
+    pages::FillSummary,
+    smmdb::{format_timestamp, Difficulty, ErrorAction, SortOptions, SortValue, SORT_OPTIONS},
     styles::*,
+    widgets::{scroll_sentinel, ScrollSentinel},
     AppState, Message, Smmdb,
 };
 
 use iced::{
-    button, pick_list, scrollable, text_input, Align, Button, Column, Element, Length, PickList,
-    Row, Scrollable, Space, Text, TextInput,
+    button, pick_list, scrollable, text_input, Align, Button, Checkbox, Column, Container, Element,
+    Length, PickList, Row, Scrollable, Space, Text, TextInput,
 };
 
 #[derive(Clone, Debug)]
 pub struct SmmdbWidget {
     state: scrollable::State,
+    sentinel_state: scroll_sentinel::State,
     title_state: text_input::State,
     uploader_state: text_input::State,
     difficulty_state: pick_list::State<Difficulty>,
     sort_state: pick_list::State<SortOptions>,
+    sort_key_states: Vec<SortKeyState>,
+    add_sort_key_state: pick_list::State<SortValue>,
     search_state: button::State,
-    backward_state: button::State,
-    forward_state: button::State,
+    advanced_state: button::State,
+    ids_state: text_input::State,
+    owner_state: text_input::State,
+    game_style_state: pick_list::State<Choice<GameStyle>>,
+    theme_state: pick_list::State<Choice<CourseTheme>>,
+    day_time_state: pick_list::State<Choice<DayTime>>,
+    clear_condition_state: pick_list::State<Choice<ClearConditionPresence>>,
+    min_time_state: text_input::State,
+    max_time_state: text_input::State,
+    show_advanced: bool,
+    fill_limit: Option<usize>,
+    fill_limit_state: text_input::State,
+    fill_state: button::State,
+    fill_summary: Option<FillSummary>,
+    dismiss_fill_state: button::State,
+    load_more_state: button::State,
+    retry_state: button::State,
+    offline_state: button::State,
+    apikey_state: button::State,
+    dismiss_state: button::State,
 }
 
 impl SmmdbWidget {
     pub fn new() -> SmmdbWidget {
         SmmdbWidget {
             state: scrollable::State::new(),
+            sentinel_state: scroll_sentinel::State::default(),
             title_state: text_input::State::new(),
             uploader_state: text_input::State::new(),
             difficulty_state: pick_list::State::default(),
             sort_state: pick_list::State::default(),
+            sort_key_states: vec![],
+            add_sort_key_state: pick_list::State::default(),
             search_state: button::State::new(),
-            backward_state: button::State::new(),
-            forward_state: button::State::new(),
+            advanced_state: button::State::new(),
+            ids_state: text_input::State::new(),
+            owner_state: text_input::State::new(),
+            game_style_state: pick_list::State::default(),
+            theme_state: pick_list::State::default(),
+            day_time_state: pick_list::State::default(),
+            clear_condition_state: pick_list::State::default(),
+            min_time_state: text_input::State::new(),
+            max_time_state: text_input::State::new(),
+            show_advanced: false,
+            fill_limit: None,
+            fill_limit_state: text_input::State::new(),
+            fill_state: button::State::new(),
+            fill_summary: None,
+            dismiss_fill_state: button::State::new(),
+            load_more_state: button::State::new(),
+            retry_state: button::State::new(),
+            offline_state: button::State::new(),
+            apikey_state: button::State::new(),
+            dismiss_state: button::State::new(),
         }
     }
 
+    pub fn toggle_advanced_search(&mut self) {
+        self.show_advanced = !self.show_advanced;
+    }
+
+    pub fn get_fill_limit(&self) -> Option<usize> {
+        self.fill_limit
+    }
+
+    /// Sets how many of the top results fill empty slots, ignoring anything but a positive number.
+    pub fn set_fill_limit(&mut self, fill_limit: &str) {
+        let fill_limit = fill_limit.trim();
+        if fill_limit.is_empty() {
+            self.fill_limit = None;
+        } else if let Ok(fill_limit) = fill_limit.parse::<usize>() {
+            if fill_limit > 0 {
+                self.fill_limit = Some(fill_limit);
+            }
+        }
+    }
+
+    pub fn set_fill_summary(&mut self, fill_summary: Option<FillSummary>) {
+        self.fill_summary = fill_summary;
+    }
+
+    pub fn has_focus(&self) -> bool {
+        [
+            &self.title_state,
+            &self.uploader_state,
+            &self.ids_state,
+            &self.owner_state,
+            &self.min_time_state,
+            &self.max_time_state,
+            &self.fill_limit_state,
+        ]
+        .iter()
+        .any(|state| state.is_focused())
+    }
+
     pub fn view<'a>(
         &'a mut self,
         state: &AppState,
@@ -67,19 +150,107 @@ impl SmmdbWidget {
         )
         .style(DefaultPickListStyle)
         .padding(4);
+        // Offer the current sort as well, if it has been built by the user.
+        let mut sort_options = SORT_OPTIONS.to_vec();
+        if let Some(sort) = query_params.get_sort() {
+            if !sort_options.contains(&sort) {
+                sort_options.push(sort);
+            }
+        }
         let sort_pick_list = PickList::new(
             &mut self.sort_state,
-            &SORT_OPTIONS[..],
+            sort_options,
             query_params.get_sort().clone(),
             Message::SortChanged,
         )
         .style(DefaultPickListStyle)
         .padding(4);
+
+        let sort = query_params.get_sort();
+        let sort_keys = sort.as_ref().map(|sort| sort.get_keys()).unwrap_or(&[]);
+        self.sort_key_states
+            .resize_with(sort_keys.len(), SortKeyState::default);
+        let mut sort_builder = Column::new();
+        for (index, (sort_key, key_state)) in sort_keys
+            .iter()
+            .zip(self.sort_key_states.iter_mut())
+            .enumerate()
+        {
+            let mut up_button =
+                Button::new(&mut key_state.up_state, Text::new("Up")).style(DefaultButtonStyle);
+            if index > 0 {
+                up_button = up_button.on_press(Message::MoveSortKeyUp(index));
+            }
+            sort_builder = sort_builder
+                .push(
+                    Row::new()
+                        .align_items(Align::Center)
+                        .push(
+                            Text::new(format!("{}. {}", index + 1, sort_key.val))
+                                .size(15)
+                                .width(Length::Fill),
+                        )
+                        .push(
+                            Button::new(
+                                &mut key_state.direction_state,
+                                Text::new(if sort_key.is_ascending() {
+                                    "Ascending"
+                                } else {
+                                    "Descending"
+                                }),
+                            )
+                            .style(DefaultButtonStyle)
+                            .on_press(Message::ToggleSortDirection(index)),
+                        )
+                        .push(Space::with_width(Length::Units(4)))
+                        .push(up_button)
+                        .push(Space::with_width(Length::Units(4)))
+                        .push(
+                            Button::new(&mut key_state.remove_state, Text::new("Remove"))
+                                .style(DefaultButtonDangerStyle)
+                                .on_press(Message::RemoveSortKey(index)),
+                        ),
+                )
+                .push(Space::with_height(Length::Units(4)));
+        }
+        let unused_sort_values: Vec<SortValue> = SortValue::ALL
+            .iter()
+            .filter(|val| !sort_keys.iter().any(|sort_key| sort_key.val == **val))
+            .cloned()
+            .collect();
+        if !unused_sort_values.is_empty() {
+            sort_builder = sort_builder.push(
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(Text::new("Then by:").size(15))
+                    .push(Space::with_width(Length::Units(8)))
+                    .push(
+                        PickList::new(
+                            &mut self.add_sort_key_state,
+                            unused_sort_values,
+                            None,
+                            Message::AddSortKey,
+                        )
+                        .style(DefaultPickListStyle)
+                        .padding(4),
+                    ),
+            );
+        }
+        let advanced_button = Button::new(
+            &mut self.advanced_state,
+            Text::new(if self.show_advanced {
+                "Hide advanced search"
+            } else {
+                "Advanced search"
+            }),
+        )
+        .style(DefaultButtonStyle)
+        .on_press(Message::ToggleAdvancedSearch);
         let search_button = Button::new(&mut self.search_state, Text::new("Search"))
             .style(DefaultButtonStyle)
             .on_press(Message::ApplyFilters);
 
-        let filter = Column::new()
+        let mut filter = Column::new()
             .push(Text::new("Filters:").font(font::HELVETICA_BOLD).size(16))
             .push(title_text_input)
             .push(Space::with_height(Length::Units(4)))
@@ -91,59 +262,358 @@ impl SmmdbWidget {
             .push(Text::new("Sort by:").font(font::HELVETICA_BOLD).size(16))
             .push(sort_pick_list)
             .push(Space::with_height(Length::Units(4)))
-            .push(search_button);
-
-        let paginate_text = Text::new(&format!(
-            "{} – {}",
-            query_params.skip + 1,
-            query_params.skip + smmdb.get_course_panels().len() as u32
-        ));
+            .push(sort_builder)
+            .push(Space::with_height(Length::Units(4)));
+        let query_params = smmdb.get_query_params();
+        let meta_filter = smmdb.get_meta_filter();
+        let mut meta_filters = Column::new()
+            .push(
+                Text::new("Course metadata:")
+                    .font(font::HELVETICA_BOLD)
+                    .size(16),
+            )
+            .push(meta_filter_row(
+                "Game style",
+                PickList::new(
+                    &mut self.game_style_state,
+                    Choice::all(&GameStyle::ALL),
+                    Some(meta_filter.get_game_style().into()),
+                    Message::GameStyleFilterChanged,
+                ),
+            ))
+            .push(meta_filter_row(
+                "Theme",
+                PickList::new(
+                    &mut self.theme_state,
+                    Choice::all(&CourseTheme::ALL),
+                    Some(meta_filter.get_theme().into()),
+                    Message::ThemeFilterChanged,
+                ),
+            ))
+            .push(meta_filter_row(
+                "Day/night",
+                PickList::new(
+                    &mut self.day_time_state,
+                    Choice::all(&DayTime::ALL),
+                    Some(meta_filter.get_day_time().into()),
+                    Message::DayTimeFilterChanged,
+                ),
+            ))
+            .push(meta_filter_row(
+                "Clear condition",
+                PickList::new(
+                    &mut self.clear_condition_state,
+                    Choice::all(&ClearConditionPresence::ALL),
+                    Some(meta_filter.get_clear_condition().into()),
+                    Message::ClearConditionFilterChanged,
+                ),
+            ))
+            .push(
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(Text::new("Time limit").size(15).width(Length::Units(120)))
+                    .push(
+                        TextInput::new(
+                            &mut self.min_time_state,
+                            "Min seconds",
+                            meta_filter.get_min_time_input(),
+                            Message::MinTimeChanged,
+                        )
+                        .style(DefaultTextInputStyle)
+                        .padding(4),
+                    )
+                    .push(Space::with_width(Length::Units(8)))
+                    .push(
+                        TextInput::new(
+                            &mut self.max_time_state,
+                            "Max seconds",
+                            meta_filter.get_max_time_input(),
+                            Message::MaxTimeChanged,
+                        )
+                        .style(DefaultTextInputStyle)
+                        .padding(4),
+                    ),
+            )
+            .spacing(4);
+        if let Some(time_error) = meta_filter.get_time_error() {
+            meta_filters =
+                meta_filters.push(Text::new(time_error).size(15).color(TEXT_DANGER_COLOR));
+        }
+        filter = filter
+            .push(meta_filters)
+            .push(Space::with_height(Length::Units(4)))
+            .push(advanced_button)
+            .push(Space::with_height(Length::Units(4)));
+        if self.show_advanced {
+            filter = filter
+                .push(Checkbox::new(
+                    query_params.is_title_exact(),
+                    "Exact title",
+                    Message::TitleExactChanged,
+                ))
+                .push(Space::with_height(Length::Units(4)))
+                .push(Checkbox::new(
+                    query_params.is_title_case_sensitive(),
+                    "Case sensitive title",
+                    Message::TitleCaseSensitiveChanged,
+                ))
+                .push(Space::with_height(Length::Units(4)))
+                .push(Checkbox::new(
+                    query_params.is_title_trimmed(),
+                    "Ignore surrounding whitespace in title",
+                    Message::TitleTrimmedChanged,
+                ))
+                .push(Space::with_height(Length::Units(4)))
+                .push(
+                    TextInput::new(
+                        &mut self.ids_state,
+                        "SMMDB ids, separated by commas or spaces",
+                        smmdb.get_ids_input(),
+                        Message::IdsChanged,
+                    )
+                    .style(DefaultTextInputStyle)
+                    .padding(4),
+                )
+                .push(Space::with_height(Length::Units(4)))
+                .push(
+                    TextInput::new(
+                        &mut self.owner_state,
+                        "Owner id",
+                        query_params
+                            .get_owner()
+                            .map(|owner| owner.as_str())
+                            .unwrap_or(""),
+                        Message::OwnerChanged,
+                    )
+                    .style(DefaultTextInputStyle)
+                    .padding(4),
+                )
+                .push(Space::with_height(Length::Units(4)));
+        }
+        if let Some(account) = smmdb.get_account() {
+            filter = filter
+                .push(Checkbox::new(
+                    query_params.get_owner() == Some(account.get_id()),
+                    format!("My uploads ({})", account.get_username()),
+                    Message::ToggleOwnCourses,
+                ))
+                .push(Space::with_height(Length::Units(4)));
+        }
+        filter = filter.push(search_button);
 
-        let mut backward_button = Button::new(&mut self.backward_state, Text::new("<").size(24))
+        let fill_limit = self
+            .fill_limit
+            .map(|fill_limit| fill_limit.to_string())
+            .unwrap_or_default();
+        let mut fill_button = Button::new(&mut self.fill_state, Text::new("Fill empty slots"))
             .style(DefaultButtonStyle);
-        backward_button = match state {
-            AppState::Loading | AppState::Downloading { .. } => backward_button,
+        match state {
+            AppState::Loading | AppState::Uploading { .. } => {}
+            _ if smmdb.get_course_panels().is_empty() => {}
             _ => {
-                if smmdb.can_paginate_backward() {
-                    backward_button.on_press(Message::PaginateBackward)
-                } else {
-                    backward_button
-                }
+                fill_button = fill_button.on_press(Message::FillEmptySlots);
             }
-        };
-        let mut forward_button =
-            Button::new(&mut self.forward_state, Text::new(">").size(24)).style(DefaultButtonStyle);
-        forward_button = match state {
-            AppState::Loading | AppState::Downloading { .. } => forward_button,
-            _ => {
-                if smmdb.can_paginate_forward() {
-                    forward_button.on_press(Message::PaginateForward)
-                } else {
-                    forward_button
-                }
+        }
+        let mut fill = Column::new()
+            .spacing(4)
+            .push(
+                Text::new("Fill empty slots:")
+                    .font(font::HELVETICA_BOLD)
+                    .size(16),
+            )
+            .push(
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(
+                        TextInput::new(
+                            &mut self.fill_limit_state,
+                            "Number of top results (all loaded results if empty)",
+                            &fill_limit,
+                            Message::FillLimitChanged,
+                        )
+                        .style(DefaultTextInputStyle)
+                        .padding(4),
+                    )
+                    .push(Space::with_width(Length::Units(8)))
+                    .push(fill_button),
+            );
+        if let Some(fill_summary) = &self.fill_summary {
+            let mut summary = Column::new().spacing(2);
+            if fill_summary.installed.is_empty() {
+                summary = summary.push(Text::new("No courses were installed.").size(15));
+            }
+            for (index, title) in &fill_summary.installed {
+                summary =
+                    summary.push(Text::new(format!("Slot {}: {}", index + 1, title)).size(15));
             }
+            for title in &fill_summary.skipped {
+                summary = summary.push(
+                    Text::new(format!("Skipped {}, it is already in your save", title))
+                        .size(15)
+                        .color(TEXT_HELP_COLOR),
+                );
+            }
+            for (title, reason) in &fill_summary.failed {
+                summary = summary.push(
+                    Text::new(format!("Could not install {}: {}", title, reason))
+                        .size(15)
+                        .color(TEXT_DANGER_COLOR),
+                );
+            }
+            fill = fill.push(summary).push(
+                Button::new(&mut self.dismiss_fill_state, Text::new("Dismiss"))
+                    .style(DefaultButtonStyle)
+                    .on_press(Message::DismissFillSummary),
+            );
+        }
+
+        let course_count = smmdb.get_course_panels().len();
+        let loaded_count = match course_count {
+            1 => "1 course".to_string(),
+            count if smmdb.is_end_reached() => format!("{} courses", count),
+            count => format!("{}+ courses", count),
         };
+        let course_count_text = Text::new(if smmdb.get_meta_filter().is_active() {
+            format!(
+                "Showing {} of {}",
+                smmdb.get_visible_course_panels().len(),
+                loaded_count
+            )
+        } else {
+            loaded_count
+        })
+        .size(15)
+        .color(TEXT_HELP_COLOR);
 
-        let paginator = Row::new()
-            .align_items(Align::Center)
-            .push(Space::with_width(Length::Fill))
-            .push(paginate_text)
-            .push(Space::with_width(Length::Units(16)))
-            .push(backward_button)
-            .push(Space::with_width(Length::Units(16)))
-            .push(forward_button);
+        let mut status = Column::new();
+        if let Some(error) = smmdb.get_error() {
+            let error_actions = error.get_actions();
+            let mut actions = Row::new();
+            if error_actions.contains(&ErrorAction::ReenterApiKey) {
+                actions = actions
+                    .push(
+                        Button::new(&mut self.apikey_state, Text::new("Change API key"))
+                            .style(DefaultButtonStyle)
+                            .on_press(Message::OpenSettings),
+                    )
+                    .push(Space::with_width(Length::Units(12)));
+            }
+            if error_actions.contains(&ErrorAction::Retry) {
+                actions = actions
+                    .push(
+                        Button::new(&mut self.retry_state, Text::new("Retry"))
+                            .style(DefaultButtonStyle)
+                            .on_press(Message::RetryFetch),
+                    )
+                    .push(Space::with_width(Length::Units(12)));
+            }
+            if error_actions.contains(&ErrorAction::GoOffline) {
+                actions = actions
+                    .push(
+                        Button::new(&mut self.offline_state, Text::new("Go offline"))
+                            .style(DefaultButtonStyle)
+                            .on_press(Message::GoOffline),
+                    )
+                    .push(Space::with_width(Length::Units(12)));
+            }
+            actions = actions.push(
+                Button::new(&mut self.dismiss_state, Text::new("Dismiss"))
+                    .style(DefaultButtonDangerStyle)
+                    .on_press(Message::DismissSmmdbError),
+            );
+            status = status
+                .push(
+                    Text::new(error.to_string())
+                        .font(font::HELVETICA_BOLD)
+                        .size(18)
+                        .color(COLOR_DARK_RED),
+                )
+                .push(Space::with_height(Length::Units(8)))
+                .push(actions);
+        } else if smmdb.is_offline() {
+            let offline_text = match smmdb.get_cached_at() {
+                Some(cached_at) => format!(
+                    "Offline, showing results from {}",
+                    format_timestamp(cached_at)
+                ),
+                None => "You are offline.".to_string(),
+            };
+            status = status.push(
+                Row::new()
+                    .align_items(Align::Center)
+                    .push(Text::new(offline_text).font(font::HELVETICA_BOLD).size(18))
+                    .push(Space::with_width(Length::Units(12)))
+                    .push(
+                        Button::new(&mut self.retry_state, Text::new("Reconnect"))
+                            .style(DefaultButtonStyle)
+                            .on_press(Message::RetryFetch),
+                    ),
+            );
+        }
 
+        let scroll = self.state;
         let mut content = Scrollable::new(&mut self.state)
             .padding(CONTAINER_PADDING)
             .spacing(LIST_SPACING)
             .push(Text::new("SMMDB").font(font::SMME))
+            .push(status)
             .push(filter)
             .push(Space::with_height(Length::Units(8)))
-            .push(paginator);
-        for panel in smmdb.get_course_panels().values_mut() {
-            content = content.push(panel.view(state));
+            .push(fill)
+            .push(Space::with_height(Length::Units(8)))
+            .push(course_count_text);
+
+        let end_content: Element<Message> = if smmdb.is_loading_more() {
+            Text::new("Loading more courses...").into()
+        } else if smmdb.is_end_reached() {
+            Text::new(if course_count == 0 {
+                "No courses found."
+            } else {
+                "No more courses."
+            })
+            .color(TEXT_HELP_COLOR)
+            .into()
+        } else {
+            let mut load_more_button =
+                Button::new(&mut self.load_more_state, Text::new("Load more"))
+                    .style(DefaultButtonStyle);
+            if !smmdb.is_offline() {
+                load_more_button = load_more_button.on_press(Message::LoadMoreCourses);
+            }
+            load_more_button.into()
+        };
+        let account_id = smmdb.get_account().map(|account| account.get_id().clone());
+        for panel in smmdb.get_visible_course_panels() {
+            content = content.push(panel.view(state, account_id.as_ref()));
         }
+        content = content.push(Container::new(end_content).width(Length::Fill).center_x());
 
-        content.width(Length::FillPortion(1)).into()
+        let mut sentinel = ScrollSentinel::new(
+            &mut self.sentinel_state,
+            scroll,
+            content.width(Length::FillPortion(1)),
+        );
+        if !smmdb.is_end_reached() && !smmdb.is_loading_more() && !smmdb.is_offline() {
+            sentinel = sentinel.on_approach(Message::LoadMoreCourses);
+        }
+        sentinel.into()
     }
 }
+
+fn meta_filter_row<'a, T>(name: &str, pick_list: PickList<'a, T, Message>) -> Row<'a, Message>
+where
+    T: Clone + ToString + Eq + 'static,
+    [T]: ToOwned<Owned = Vec<T>>,
+{
+    Row::new()
+        .align_items(Align::Center)
+        .push(Text::new(name).size(15).width(Length::Units(120)))
+        .push(pick_list.style(DefaultPickListStyle).padding(4))
+}
+
+#[derive(Clone, Debug, Default)]
+struct SortKeyState {
+    direction_state: button::State,
+    up_state: button::State,
+    remove_state: button::State,
+}